@@ -0,0 +1,860 @@
+use crate::bvh::Bvh;
+use crate::model::*;
+use crate::points3d::*;
+use crate::repair::RepairReport;
+use crate::validation::Edge;
+use fxhash::{FxHashMap, FxHashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BooleanOp {
+  Union,
+  Difference,
+  Intersection,
+}
+
+impl BooleanOp {
+  pub const ALL: [BooleanOp; 3] =
+    [BooleanOp::Union, BooleanOp::Difference, BooleanOp::Intersection];
+
+  pub fn name(self) -> &'static str {
+    match self {
+      BooleanOp::Union => "union",
+      BooleanOp::Difference => "difference",
+      BooleanOp::Intersection => "intersection",
+    }
+  }
+
+  pub fn from_name(name: &str) -> Option<Self> {
+    Self::ALL.into_iter().find(|op| op.name() == name)
+  }
+}
+
+impl Model {
+  pub fn union(&self, other: &Model) -> Model {
+    self.boolean(other, BooleanOp::Union)
+  }
+
+  pub fn difference(&self, other: &Model) -> Model {
+    self.boolean(other, BooleanOp::Difference)
+  }
+
+  pub fn intersection(&self, other: &Model) -> Model {
+    self.boolean(other, BooleanOp::Intersection)
+  }
+
+  /// Both models should be closed with welded vertices like the ones from `load_from_stl`
+  pub fn boolean(&self, other: &Model, op: BooleanOp) -> Model {
+    // meshes made on the same grid have edges crossing exactly in vertices and edges of the
+    // other mesh and coplanar faces, a small shift of the second one turns them into ordinary
+    // crossings, it is taken back from the result
+    let (min, max) = self.get_aabb();
+    let shift = Point { x: 1.0, y: 0.618034, z: 0.381966 }.scale((max - min).len() * 1.0e-5);
+    let original = other;
+    let mut other = other.clone();
+    other.map_points(|p| p + shift);
+
+    let mut cutter = Cutter::new([self, &other]);
+    for (i, j) in overlapping_pairs(self, &other) {
+      cutter.cut(i, j);
+    }
+
+    // vertices of the second model return to their places, crossings are moved to where edges
+    // cross planes of original triangles
+    let mut result = Model::new();
+    result.vertices = cutter.vertices.clone();
+    let offset = self.vertices.len();
+    result.vertices[offset..offset + original.vertices.len()].copy_from_slice(&original.vertices);
+    let models = [self, original];
+    let first_crossing = offset + original.vertices.len();
+    for (v, &(side, e, t)) in cutter.sources.iter().enumerate() {
+      let v = first_crossing + v;
+      let m = models[side];
+      let other = models[1 - side];
+      let (p0, p1) = (m.vertices[e.0 as usize], m.vertices[e.1 as usize]);
+      let tri = other.triangles[t as usize].map(|v| other.vertices[v as usize]);
+      result.vertices[v] = match plane_crossing(p0, p1, tri) {
+        Some(along) => p0 + (p1 - p0).scale(along),
+        // edges lying in the plane stay on their own lines
+        None if side == 1 => result.vertices[v] - shift,
+        None => result.vertices[v],
+      };
+    }
+
+    for side in 0..2 {
+      let pieces = cutter.split(side);
+      let inside = cutter.classify(side, &pieces);
+      let keep_inside = match op {
+        BooleanOp::Union => false,
+        BooleanOp::Intersection => true,
+        BooleanOp::Difference => side == 1,
+      };
+      let other = Bvh::new(models[1 - side]);
+      for (mut t, inside) in pieces.into_iter().zip(inside) {
+        // pieces lying on the other surface are kept once from the first model: with the same
+        // direction they bound a union or an intersection and with the opposite one a difference
+        let [v0, v1, v2] = t.map(|v| result.vertices[v as usize]);
+        let normal = cross(v1 - v0, v2 - v0);
+        let keep = match other.closest((v0 + v1 + v2).scale(1.0 / 3.0), shift.len()) {
+          Some(on) if is_parallel(normal, other.triangle(on.triangle)) => {
+            let [w0, w1, w2] = other.triangle(on.triangle);
+            let same = dot(normal, cross(w1 - w0, w2 - w0)) > 0.0;
+            side == 0 && same == (op != BooleanOp::Difference)
+          }
+          _ => inside == keep_inside,
+        };
+        if keep {
+          if op == BooleanOp::Difference && side == 1 {
+            t.swap(1, 2);
+          }
+          result.triangles.push(t);
+        }
+      }
+    }
+
+    // slivers between coplanar faces collapse and are welded away
+    result.clean(shift.len() * 2.0, &mut RepairReport::default());
+    split_open_edges(&mut result, shift.len() * 2.0);
+    result.delete_unused_v();
+    result
+  }
+}
+
+// a vertex of one model taken back from the shift may end on an edge of the other model which
+// was not cut there, open edges are split at ends of other open edges lying on them
+fn split_open_edges(model: &mut Model, tolerance: f32) {
+  let directed: FxHashSet<(u32, u32)> =
+    model.triangles.iter().flat_map(|t| (0..3).map(|k| (t[k], t[(k + 1) % 3]))).collect();
+  let open: Vec<(u32, u32)> =
+    directed.iter().filter(|&&(a, b)| !directed.contains(&(b, a))).copied().collect();
+  let mut ends: Vec<u32> = open.iter().flat_map(|&(a, b)| [a, b]).collect();
+  ends.sort();
+  ends.dedup();
+
+  // vertices lying inside of each open edge ordered from its start
+  let mut inner = FxHashMap::<(u32, u32), Vec<u32>>::default();
+  for &(a, b) in &open {
+    let (pa, pb) = (model.vertices[a as usize], model.vertices[b as usize]);
+    let d = pb - pa;
+    let mut on: Vec<(f32, u32)> = ends
+      .iter()
+      .filter(|&&v| v != a && v != b)
+      .filter_map(|&v| {
+        let p = model.vertices[v as usize];
+        let along = dot(p - pa, d) / d.sqr_len();
+        let near = (pa + d.scale(along) - p).sqr_len() <= tolerance * tolerance;
+        (along > 0.0 && along < 1.0 && near).then_some((along, v))
+      })
+      .collect();
+    if !on.is_empty() {
+      on.sort_by(|o0, o1| o0.0.total_cmp(&o1.0));
+      inner.insert((a, b), on.into_iter().map(|(_, v)| v).collect());
+    }
+  }
+
+  // a triangle is replaced by a fan from its opposite vertex, the pieces are checked again
+  let mut i = 0;
+  while i < model.triangles.len() {
+    let t = model.triangles[i];
+    let Some(k) = (0..3).find(|&k| inner.contains_key(&(t[k], t[(k + 1) % 3]))) else {
+      i += 1;
+      continue;
+    };
+    let (a, b, c) = (t[k], t[(k + 1) % 3], t[(k + 2) % 3]);
+    let chain: Vec<u32> =
+      [a].into_iter().chain(inner[&(a, b)].iter().copied()).chain([b]).collect();
+    model.triangles[i] = [chain[0], chain[1], c];
+    for w in chain[1..].windows(2) {
+      model.triangles.push([w[0], w[1], c]);
+    }
+  }
+}
+
+fn to_f64(p: Point) -> [f64; 3] {
+  [p.x as f64, p.y as f64, p.z as f64]
+}
+
+fn sub(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+  [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+fn dot3(a: [f64; 3], b: [f64; 3]) -> f64 {
+  a[0] * b[0] + a[1] * b[1] + a[2] * b[2]
+}
+
+fn cross3(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+  [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]]
+}
+
+fn len3(a: [f64; 3]) -> f64 {
+  dot3(a, a).sqrt()
+}
+
+// normal of the triangle goes along `normal` or against it, degenerate ones have no direction
+fn is_parallel(normal: Point, tri: [Point; 3]) -> bool {
+  let other = cross(tri[1] - tri[0], tri[2] - tri[0]);
+  dot(normal, other).abs() > normal.len() * other.len() * 0.9999
+}
+
+// the part of the edge from `p0` to `p1` where its line crosses the plane of the triangle
+fn plane_crossing(p0: Point, p1: Point, tri: [Point; 3]) -> Option<f32> {
+  let [p0, p1] = [p0, p1].map(to_f64);
+  let tri = tri.map(to_f64);
+  let normal = cross3(sub(tri[1], tri[0]), sub(tri[2], tri[0]));
+  let dir = sub(p1, p0);
+  let denominator = dot3(normal, dir);
+  if denominator.abs() <= 1.0e-9 * len3(normal) * len3(dir) {
+    return None;
+  }
+  Some((dot3(normal, sub(tri[0], p0)) / denominator).clamp(0.0, 1.0) as f32)
+}
+
+fn edge(v0: u32, v1: u32) -> Edge {
+  (v0.min(v1), v0.max(v1))
+}
+
+// pairs of triangles of `a` and `b` with touching boxes found by one hierarchy over both models
+fn overlapping_pairs(a: &Model, b: &Model) -> Vec<(u32, u32)> {
+  let triangles: Vec<[Point; 3]> = [a, b]
+    .into_iter()
+    .flat_map(|m| m.triangles.iter().map(|t| t.map(|v| m.vertices[v as usize])))
+    .collect();
+  let order = (0..triangles.len() as u32).collect();
+  let first = a.triangles.len() as u32;
+  let mut pairs: Vec<_> = Bvh::with_order(triangles, order)
+    .overlapping_pairs()
+    .into_iter()
+    .filter(|&(i, j)| i < first && j >= first)
+    .map(|(i, j)| (i, j - first))
+    .collect();
+  pairs.sort();
+  pairs
+}
+
+// intersection curves of two meshes, vertices of the second mesh go after the first one's
+struct Cutter<'a> {
+  models: [&'a Model; 2],
+  vertices: Vec<Point>,
+  // (side of the edge, edge, triangle of the other side) -> crossing vertex
+  crossings: FxHashMap<(usize, Edge, u32), u32>,
+  // crossing vertices by bits of their coordinates, crossings in the same place are one vertex
+  places: FxHashMap<[u32; 3], u32>,
+  // the first edge and triangle giving every crossing vertex in order of vertices
+  sources: Vec<(usize, Edge, u32)>,
+  // crossing vertices on edges of each side
+  on_edges: [FxHashMap<Edge, Vec<u32>>; 2],
+  // crossing vertices inside triangles of each side
+  inner: [FxHashMap<u32, Vec<u32>>; 2],
+  // pieces of intersection curves inside triangles of each side
+  segments: [FxHashMap<u32, Vec<(u32, u32)>>; 2],
+}
+
+impl<'a> Cutter<'a> {
+  fn new(models: [&'a Model; 2]) -> Self {
+    let mut vertices = models[0].vertices.clone();
+    vertices.extend_from_slice(&models[1].vertices);
+    Self {
+      models,
+      vertices,
+      crossings: FxHashMap::default(),
+      places: FxHashMap::default(),
+      sources: Vec::new(),
+      on_edges: Default::default(),
+      inner: Default::default(),
+      segments: Default::default(),
+    }
+  }
+
+  fn offset(&self, side: usize) -> u32 {
+    if side == 0 { 0 } else { self.models[0].vertices.len() as u32 }
+  }
+
+  // vertex where the edge of `side` crosses triangle `t` of the other side
+  fn crossing(&mut self, side: usize, e: Edge, t: u32) -> Option<u32> {
+    if let Some(&v) = self.crossings.get(&(side, e, t)) {
+      return Some(v);
+    }
+    let m = self.models[side];
+    let other = self.models[1 - side];
+    let p0 = to_f64(m.vertices[e.0 as usize]);
+    let p1 = to_f64(m.vertices[e.1 as usize]);
+    let tri = other.triangles[t as usize].map(|v| to_f64(other.vertices[v as usize]));
+
+    let dir = sub(p1, p0);
+    let e1 = sub(tri[1], tri[0]);
+    let e2 = sub(tri[2], tri[0]);
+    let h = cross3(dir, e2);
+    let det = dot3(e1, h);
+    if det.abs() <= 1.0e-12 * len3(e1) * len3(h) {
+      return None;
+    }
+    let s = sub(p0, tri[0]);
+    let u = dot3(s, h) / det;
+    let q = cross3(s, e1);
+    let v = dot3(dir, q) / det;
+    let along = dot3(e2, q) / det;
+    if u < 0.0 || v < 0.0 || u + v > 1.0 || along <= 0.0 || along >= 1.0 {
+      return None;
+    }
+
+    let p = [0, 1, 2].map(|k| (p0[k] + dir[k] * along) as f32);
+    let next = self.vertices.len() as u32;
+    let id = *self.places.entry(p.map(|c| (c + 0.0).to_bits())).or_insert(next);
+    if id == next {
+      self.vertices.push(Point { x: p[0], y: p[1], z: p[2] });
+      self.sources.push((side, e, t));
+    }
+    self.crossings.insert((side, e, t), id);
+    self.on_edges[side].entry(e).or_default().push(id);
+    self.inner[1 - side].entry(t).or_default().push(id);
+    Some(id)
+  }
+
+  // adds the intersection segment of triangle `i` of the first model and `j` of the second one
+  fn cut(&mut self, i: u32, j: u32) {
+    let mut points = Vec::new();
+    for (side, t, o) in [(0, i, j), (1, j, i)] {
+      let tri = self.models[side].triangles[t as usize];
+      for k in 0..3 {
+        if let Some(v) = self.crossing(side, edge(tri[k], tri[(k + 1) % 3]), o) {
+          points.push(v);
+        }
+      }
+    }
+    points.sort();
+    points.dedup();
+    // touching and coplanar triangles give other numbers of points
+    if let [v0, v1] = points[..] {
+      self.segments[0].entry(i).or_default().push((v0, v1));
+      self.segments[1].entry(j).or_default().push((v0, v1));
+    }
+  }
+
+  // triangles of `side` split along intersection curves
+  fn split(&self, side: usize) -> Vec<Triangle> {
+    let m = self.models[side];
+    let offset = self.offset(side);
+    let mut result = Vec::new();
+    for (i, t) in m.triangles.iter().enumerate() {
+      let i = i as u32;
+      let mut boundary = Vec::new();
+      for k in 0..3 {
+        let (v0, v1) = (t[k], t[(k + 1) % 3]);
+        boundary.push(v0 + offset);
+        if let Some(points) = self.on_edges[side].get(&edge(v0, v1)) {
+          let start = self.vertices[(v0 + offset) as usize];
+          let mut points = points.clone();
+          points.sort_by(|&p0, &p1| {
+            let d0 = (self.vertices[p0 as usize] - start).sqr_len();
+            let d1 = (self.vertices[p1 as usize] - start).sqr_len();
+            d0.total_cmp(&d1)
+          });
+          boundary.extend(points);
+        }
+      }
+      let inner = self.inner[side].get(&i).map_or(&[][..], |v| v.as_slice());
+      if boundary.len() == 3 && inner.is_empty() {
+        result.push(t.map(|v| v + offset));
+        continue;
+      }
+      let segments = self.segments[side].get(&i).map_or(&[][..], |v| v.as_slice());
+      let normal = cross(
+        m.vertices[t[1] as usize] - m.vertices[t[0] as usize],
+        m.vertices[t[2] as usize] - m.vertices[t[0] as usize],
+      );
+      let corners = t.map(|v| v + offset);
+      result.extend(self.triangulate(corners, &boundary, inner, segments, normal));
+    }
+    result
+  }
+
+  // constrained triangulation of a triangle with points on its edges and inside: intersection
+  // segments split the triangle into regions, holes of every region are bridged to its outline
+  // and the resulting polygon is cut by ear clipping
+  fn triangulate(
+    &self,
+    corners: Triangle,
+    boundary: &[u32],
+    inner: &[u32],
+    segments: &[(u32, u32)],
+    normal: Point,
+  ) -> Vec<Triangle> {
+    let mut ids: Vec<u32> = boundary.iter().chain(inner).copied().collect();
+    ids.sort();
+    ids.dedup();
+
+    // projection to the plane of the two axes closest to the triangle plane
+    let n = [normal.x.abs(), normal.y.abs(), normal.z.abs()];
+    let drop = if n[0] >= n[1] && n[0] >= n[2] {
+      0
+    } else if n[1] >= n[2] {
+      1
+    } else {
+      2
+    };
+    let pts: Vec<[f64; 2]> = ids
+      .iter()
+      .map(|&v| {
+        let p = to_f64(self.vertices[v as usize]);
+        [p[(drop + 1) % 3], p[(drop + 2) % 3]]
+      })
+      .collect();
+    let plane = Plane { pts: &pts };
+    let local = |v: u32| ids.binary_search(&v).unwrap();
+
+    // outline goes counter clockwise in the projection
+    let sign = plane.orient(local(corners[0]), local(corners[1]), local(corners[2])).signum();
+    let mut outline: Vec<usize> = boundary.iter().map(|&v| local(v)).collect();
+    outline.dedup();
+    if outline.len() > 1 && outline.first() == outline.last() {
+      outline.pop();
+    }
+    if sign < 0.0 {
+      outline.reverse();
+    }
+
+    // sides of the triangle every boundary point lies on, corners are on two of them
+    let mut sides = vec![0u8; ids.len()];
+    let mut side = 0;
+    for &v in boundary {
+      match corners.iter().position(|&c| c == v) {
+        Some(corner) => {
+          side = corner;
+          sides[local(v)] |= 1 << corner | 1 << ((corner + 2) % 3);
+        }
+        None => sides[local(v)] |= 1 << side,
+      }
+    }
+
+    let mut edges: Vec<(usize, usize)> =
+      (0..outline.len()).map(|k| (outline[k], outline[(k + 1) % outline.len()])).collect();
+    let mut neighbours = vec![Vec::<usize>::new(); ids.len()];
+    for &(a, b) in &edges {
+      neighbours[a].push(b);
+      neighbours[b].push(a);
+    }
+    for &(v0, v1) in segments {
+      let (a, b) = (local(v0), local(v1));
+      // points of one side are connected only along it, segments crossing others are dropped
+      if a == b || neighbours[a].contains(&b) || sides[a] & sides[b] != 0 {
+        continue;
+      }
+      if !plane.blocked(a, b, &edges) {
+        edges.push((a, b));
+        neighbours[a].push(b);
+        neighbours[b].push(a);
+      }
+    }
+    for (v, around) in neighbours.iter_mut().enumerate() {
+      around.sort_by(|&p, &q| plane.angle(v, p).total_cmp(&plane.angle(v, q)));
+    }
+
+    // every walk keeps its face on the left, so regions go counter clockwise and holes clockwise,
+    // the face outside of the triangle is skipped
+    let mut walked: FxHashSet<(usize, usize)> =
+      edges[..outline.len()].iter().map(|&(a, b)| (b, a)).collect();
+    let (mut regions, mut holes) = (Vec::new(), Vec::new());
+    for &(a, b) in &edges {
+      for start in [(a, b), (b, a)] {
+        if walked.contains(&start) {
+          continue;
+        }
+        let mut walk = Vec::new();
+        let (mut u, mut v) = start;
+        loop {
+          walked.insert((u, v));
+          walk.push(u);
+          let around = &neighbours[v];
+          let k = around.iter().position(|&w| w == u).unwrap();
+          (u, v) = (v, around[(k + around.len() - 1) % around.len()]);
+          if (u, v) == start {
+            break;
+          }
+        }
+        if plane.area(&walk) > 0.0 {
+          regions.push(walk);
+        } else {
+          holes.push(walk);
+        }
+      }
+    }
+    // points not connected to anything are holes too
+    holes.extend((0..ids.len()).filter(|&v| neighbours[v].is_empty()).map(|v| vec![v]));
+
+    // every hole belongs to the smallest region around it, the region inside of the hole
+    // goes through the same points
+    let mut region_holes = vec![Vec::new(); regions.len()];
+    for hole in holes {
+      let around = (0..regions.len())
+        .filter(|&r| !regions[r].contains(&hole[0]) && plane.contains(&regions[r], hole[0]))
+        .min_by(|&r0, &r1| plane.area(&regions[r0]).total_cmp(&plane.area(&regions[r1])));
+      if let Some(r) = around {
+        region_holes[r].push(hole);
+      }
+    }
+
+    let mut faces = Vec::new();
+    for (region, holes) in regions.into_iter().zip(region_holes) {
+      let polygon = plane.bridge_holes(region, holes);
+      faces.extend(plane.clip_ears(polygon));
+    }
+    faces
+      .into_iter()
+      .map(|[a, b, c]| if sign > 0.0 { [ids[a], ids[b], ids[c]] } else { [ids[a], ids[c], ids[b]] })
+      .collect()
+  }
+
+  // for every piece of `side` tells if it is inside the other model,
+  // pieces connected not through intersection curves are tested together
+  fn classify(&self, side: usize, pieces: &[Triangle]) -> Vec<bool> {
+    let curves: FxHashSet<Edge> =
+      self.segments[side].values().flatten().map(|&(v0, v1)| edge(v0, v1)).collect();
+    let mut edge_faces = FxHashMap::<Edge, Vec<usize>>::default();
+    for (i, t) in pieces.iter().enumerate() {
+      for k in 0..3 {
+        let e = edge(t[k], t[(k + 1) % 3]);
+        if !curves.contains(&e) {
+          edge_faces.entry(e).or_default().push(i);
+        }
+      }
+    }
+
+    let other = self.models[1 - side];
+    let area = |t: &Triangle| {
+      let [v0, v1, v2] = t.map(|v| self.vertices[v as usize]);
+      cross(v1 - v0, v2 - v0).len()
+    };
+    let mut result = vec![None; pieces.len()];
+    let mut region = Vec::new();
+    for start in 0..pieces.len() {
+      if result[start].is_some() {
+        continue;
+      }
+      region.clear();
+      region.push(start);
+      result[start] = Some(false);
+      let mut k = 0;
+      while k < region.len() {
+        let t = pieces[region[k]];
+        for j in 0..3 {
+          for &f in edge_faces.get(&edge(t[j], t[(j + 1) % 3])).into_iter().flatten() {
+            if result[f].is_none() {
+              result[f] = Some(false);
+              region.push(f);
+            }
+          }
+        }
+        k += 1;
+      }
+      // the biggest piece has its center far enough from the other surface
+      let best = *region
+        .iter()
+        .max_by(|&&f0, &&f1| area(&pieces[f0]).total_cmp(&area(&pieces[f1])))
+        .unwrap();
+      let [v0, v1, v2] = pieces[best].map(|v| self.vertices[v as usize]);
+      let inside = winding_number(other, (v0 + v1 + v2).scale(1.0 / 3.0)) > 0.5;
+      for &f in &region {
+        result[f] = Some(inside);
+      }
+    }
+    result.into_iter().map(|r| r.unwrap()).collect()
+  }
+}
+
+// projected points of a triangle split by `Cutter::triangulate`
+struct Plane<'a> {
+  pts: &'a [[f64; 2]],
+}
+
+impl Plane<'_> {
+  // positive if `a`, `b`, `c` go counter clockwise, the sign is exact so zero means collinear
+  fn orient(&self, a: usize, b: usize, c: usize) -> f64 {
+    let [pa, pb, pc] = [a, b, c].map(|k| self.pts[k]);
+    orient2d(pa, pb, pc)
+  }
+
+  fn sqr_dist(&self, a: usize, b: usize) -> f64 {
+    let [pa, pb] = [a, b].map(|k| self.pts[k]);
+    (pb[0] - pa[0]).powi(2) + (pb[1] - pa[1]).powi(2)
+  }
+
+  // direction from `a` to `b`
+  fn angle(&self, a: usize, b: usize) -> f64 {
+    let [pa, pb] = [a, b].map(|k| self.pts[k]);
+    (pb[1] - pa[1]).atan2(pb[0] - pa[0])
+  }
+
+  // signed area of the polygon, positive if it goes counter clockwise
+  fn area(&self, polygon: &[usize]) -> f64 {
+    let mut sum = 0.0;
+    for k in 0..polygon.len() {
+      let [p0, p1] = [polygon[k], polygon[(k + 1) % polygon.len()]].map(|v| self.pts[v]);
+      sum += p0[0] * p1[1] - p1[0] * p0[1];
+    }
+    sum * 0.5
+  }
+
+  // the point is strictly inside of the polygon by the number of crossings of a ray along x
+  fn contains(&self, polygon: &[usize], v: usize) -> bool {
+    let p = self.pts[v];
+    let mut inside = false;
+    for k in 0..polygon.len() {
+      let [p0, p1] = [polygon[k], polygon[(k + 1) % polygon.len()]].map(|v| self.pts[v]);
+      if (p0[1] > p[1]) != (p1[1] > p[1]) {
+        let x = p0[0] + (p[1] - p0[1]) / (p1[1] - p0[1]) * (p1[0] - p0[0]);
+        if x > p[0] {
+          inside = !inside;
+        }
+      }
+    }
+    inside
+  }
+
+  // segment from `a` to `b` passes through another point or crosses one of `edges`
+  fn blocked(&self, a: usize, b: usize, edges: &[(usize, usize)]) -> bool {
+    let len = self.sqr_dist(a, b);
+    let [pa, pb] = [a, b].map(|k| self.pts[k]);
+    let passes = |k: usize| {
+      let pk = self.pts[k];
+      let along = (pk[0] - pa[0]) * (pb[0] - pa[0]) + (pk[1] - pa[1]) * (pb[1] - pa[1]);
+      k != a && k != b && self.orient(a, b, k) == 0.0 && along > 0.0 && along < len
+    };
+    edges.iter().any(|&(c, d)| {
+      if passes(c) || passes(d) {
+        return true;
+      }
+      if c == a || c == b || d == a || d == b {
+        return false;
+      }
+      let (o1, o2) = (self.orient(a, b, c), self.orient(a, b, d));
+      let (o3, o4) = (self.orient(c, d, a), self.orient(c, d, b));
+      o1 * o2 < 0.0 && o3 * o4 < 0.0
+    })
+  }
+
+  // direction to `q` goes inside of the polygon at `v` between `prev` and `next`
+  fn in_cone(&self, prev: usize, v: usize, next: usize, q: usize) -> bool {
+    if self.orient(prev, v, next) >= 0.0 {
+      self.orient(prev, v, q) > 0.0 && self.orient(v, next, q) > 0.0
+    } else {
+      self.orient(prev, v, q) > 0.0 || self.orient(v, next, q) > 0.0
+    }
+  }
+
+  // joins clockwise holes to the counter clockwise outline by pairs of opposite edges going from
+  // the rightmost point of every hole to the nearest visible point of the polygon
+  fn bridge_holes(&self, mut polygon: Vec<usize>, mut holes: Vec<Vec<usize>>) -> Vec<usize> {
+    let right = |hole: &[usize]| {
+      (0..hole.len())
+        .max_by(|&i, &j| self.pts[hole[i]][0].total_cmp(&self.pts[hole[j]][0]))
+        .unwrap()
+    };
+    holes.sort_by(|h0, h1| self.pts[h1[right(h1)]][0].total_cmp(&self.pts[h0[right(h0)]][0]));
+    for (k, hole) in holes.iter().enumerate() {
+      let start = right(hole);
+      let h = hole[start];
+      let mut edges = Vec::new();
+      for p in std::iter::once(&polygon).chain(&holes[k..]) {
+        edges.extend((0..p.len()).map(|i| (p[i], p[(i + 1) % p.len()])));
+      }
+      let mut candidates: Vec<usize> = (0..polygon.len()).collect();
+      candidates
+        .sort_by(|&i, &j| self.sqr_dist(h, polygon[i]).total_cmp(&self.sqr_dist(h, polygon[j])));
+      let m = polygon.len();
+      let visible = candidates.into_iter().find(|&i| {
+        let (prev, v, next) = (polygon[(i + m - 1) % m], polygon[i], polygon[(i + 1) % m]);
+        self.in_cone(prev, v, next, h) && !self.blocked(h, v, &edges)
+      });
+      let Some(i) = visible else { continue };
+      let mut joined = polygon[..=i].to_vec();
+      // a single point is visited once
+      let around = if hole.len() > 1 { hole.len() + 1 } else { 1 };
+      joined.extend((0..around).map(|j| hole[(start + j) % hole.len()]));
+      joined.extend_from_slice(&polygon[i..]);
+      polygon = joined;
+    }
+    polygon
+  }
+
+  // counter clockwise triangles of the counter clockwise polygon, thin ears are cut only when
+  // there are no others, so every point is used and nearly collinear ones give caps at worst
+  fn clip_ears(&self, mut polygon: Vec<usize>) -> Vec<[usize; 3]> {
+    let mut faces = Vec::new();
+    let mut last = 0;
+    while polygon.len() > 3 {
+      let m = polygon.len();
+      let corner = |i: usize| (polygon[(i + m - 1) % m], polygon[i], polygon[(i + 1) % m]);
+      let is_ear = |i: usize, thin: bool| {
+        let (a, b, c) = corner(i);
+        let area = self.orient(a, b, c);
+        let diagonal = self.sqr_dist(a, c);
+        if area <= 0.0 || (!thin && area <= diagonal * 1e-6) {
+          return false;
+        }
+        // points nearly on the diagonal stop it too
+        polygon.iter().all(|&k| {
+          k == a
+            || k == b
+            || k == c
+            || self.orient(a, b, k) < 0.0
+            || self.orient(b, c, k) < 0.0
+            || self.orient(c, a, k) < -diagonal * 1e-6
+        })
+      };
+      let mut order = (0..m).map(|k| (last + k) % m);
+      let ear =
+        order.clone().find(|&i| is_ear(i, false)).or_else(|| order.find(|&i| is_ear(i, true)));
+      // degenerate rests are cut by the most convex corner
+      let i = ear.unwrap_or_else(|| {
+        let area = |i: usize| {
+          let (a, b, c) = corner(i);
+          self.orient(a, b, c)
+        };
+        (0..m).max_by(|&i, &j| area(i).total_cmp(&area(j))).unwrap()
+      });
+      let (a, b, c) = corner(i);
+      faces.push([a, b, c]);
+      polygon.remove(i);
+      last = i.saturating_sub(1);
+    }
+    if let [a, b, c] = polygon[..] {
+      faces.push([a, b, c]);
+    }
+    faces
+  }
+}
+
+// error bound of the rounded determinant from "Adaptive Precision Floating-Point Arithmetic and
+// Fast Robust Geometric Predicates" by J. R. Shewchuk
+const ORIENT_ERROR: f64 = (3.0 + 16.0 * f64::EPSILON / 2.0) * f64::EPSILON / 2.0;
+
+// the determinant is taken exactly by expansions when rounding may change its sign
+fn orient2d(a: [f64; 2], b: [f64; 2], c: [f64; 2]) -> f64 {
+  let left = (b[0] - a[0]) * (c[1] - a[1]);
+  let right = (b[1] - a[1]) * (c[0] - a[0]);
+  let det = left - right;
+  if det.abs() > ORIENT_ERROR * (left.abs() + right.abs()) {
+    return det;
+  }
+
+  let products = |p: (f64, f64), q: (f64, f64)| {
+    [(p.0, q.0), (p.0, q.1), (p.1, q.0), (p.1, q.1)].map(|(x, y)| two_product(x, y))
+  };
+  let (dx_b, dy_b) = (two_sum(b[0], -a[0]), two_sum(b[1], -a[1]));
+  let (dx_c, dy_c) = (two_sum(c[0], -a[0]), two_sum(c[1], -a[1]));
+  // nonoverlapping components in order of magnitude, the largest one has the sign of the sum
+  let mut sum: Vec<f64> = Vec::new();
+  let terms = products(dx_b, dy_c).into_iter().map(|(hi, lo)| [hi, lo]);
+  let negated = products(dy_b, dx_c).into_iter().map(|(hi, lo)| [-hi, -lo]);
+  for term in terms.chain(negated).flatten() {
+    let mut q = term;
+    let mut grown = Vec::with_capacity(sum.len() + 1);
+    for &e in &sum {
+      let (s, error) = two_sum(q, e);
+      if error != 0.0 {
+        grown.push(error);
+      }
+      q = s;
+    }
+    grown.push(q);
+    sum = grown;
+  }
+  sum.into_iter().rev().find(|&e| e != 0.0).unwrap_or(0.0)
+}
+
+// rounded sum and its error
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+  let s = a + b;
+  let b_part = s - a;
+  (s, (a - (s - b_part)) + (b - b_part))
+}
+
+// rounded product and its error
+fn two_product(a: f64, b: f64) -> (f64, f64) {
+  let p = a * b;
+  (p, a.mul_add(b, -p))
+}
+
+// sum of solid angles of triangles seen from `p` divided by the whole sphere
+fn winding_number(m: &Model, p: Point) -> f64 {
+  let p = to_f64(p);
+  let mut sum = 0.0;
+  for t in &m.triangles {
+    let [a, b, c] = t.map(|v| sub(to_f64(m.vertices[v as usize]), p));
+    let (la, lb, lc) = (len3(a), len3(b), len3(c));
+    let numerator = dot3(a, cross3(b, c));
+    let denominator = la * lb * lc + dot3(a, b) * lc + dot3(b, c) * la + dot3(c, a) * lb;
+    sum += 2.0 * numerator.atan2(denominator);
+  }
+  sum / (4.0 * std::f64::consts::PI)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::cmp::Ordering;
+
+  fn assert_aabb(m: &Model, min: [f32; 3], max: [f32; 3]) {
+    let (m_min, m_max) = m.get_aabb();
+    assert_eq!([m_min.x, m_min.y, m_min.z], min);
+    assert_eq!([m_max.x, m_max.y, m_max.z], max);
+  }
+
+  #[test]
+  fn cuboid_with_hole() {
+    let cube = Model::cuboid(2, 2, 2, 1.0);
+    let mut tool = Model::cuboid(1, 1, 1, 1.0);
+    tool.map_points(|p| Point { x: p.x + 0.5, y: p.y + 0.5, z: p.z * 4.0 - 1.0 });
+    let test = cube.difference(&tool);
+    assert!(test.validate().is_valid());
+    assert!((test.get_volume() - 6.0).abs() < 1.0e-3);
+    assert_aabb(&test, [0.0; 3], [2.0; 3]);
+    // the tool is not shifted, its edges cross the cube in quarters of the cell
+    for p in &test.vertices {
+      assert!([p.x, p.y, p.z].iter().all(|c| (c * 4.0 - (c * 4.0).round()).abs() < 1.0e-5));
+    }
+  }
+
+  #[test]
+  fn coincident_faces_of_same_grid() {
+    let first = Model::cuboid(2, 2, 2, 1.0);
+    let mut second = Model::cuboid(2, 2, 2, 1.0);
+    second.map_points(|p| Point { x: p.x + 1.0, ..p });
+    for (op, volume, min_x, max_x) in [
+      (BooleanOp::Union, 12.0, 0.0, 3.0),
+      (BooleanOp::Difference, 4.0, 0.0, 1.0),
+      (BooleanOp::Intersection, 4.0, 1.0, 2.0),
+    ] {
+      let test = first.boolean(&second, op);
+      let report = test.validate();
+      assert!(report.is_valid(), "{}: {}", op.name(), report.summary());
+      assert!((test.get_volume() - volume).abs() < 1.0e-3, "{}", op.name());
+      assert_aabb(&test, [min_x, 0.0, 0.0], [max_x, 2.0, 2.0]);
+    }
+  }
+
+  #[test]
+  fn touching_boxes_of_other_grids() {
+    // vertices of the lower box at z = 0.5 end on edges of the first one
+    let first = Model::cuboid(2, 2, 2, 1.0);
+    let mut second = Model::cuboid(2, 2, 2, 1.0);
+    second.map_points(|p| Point { x: p.x + 2.0, y: p.y, z: p.z * 0.5 });
+    for (op, volume) in
+      [(BooleanOp::Union, 12.0), (BooleanOp::Difference, 8.0), (BooleanOp::Intersection, 0.0)]
+    {
+      let test = first.boolean(&second, op);
+      let report = test.validate();
+      assert!(report.is_valid(), "{}: {}", op.name(), report.summary());
+      assert!((test.get_volume() - volume).abs() < 1.0e-3, "{}", op.name());
+    }
+  }
+
+  #[test]
+  fn orientation_of_nearly_collinear_points() {
+    // rounded differences lose the offset of the first point by the last bit of 0.5
+    let e = f64::EPSILON / 2.0;
+    for (offset, sign) in [(e, Ordering::Less), (0.0, Ordering::Equal), (-e, Ordering::Greater)] {
+      let orient = orient2d([0.5 + offset, 0.5], [12.0, 12.0], [24.0, 24.0]);
+      assert_eq!(orient.partial_cmp(&0.0), Some(sign), "offset {offset}");
+    }
+  }
+}
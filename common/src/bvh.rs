@@ -0,0 +1,481 @@
+use crate::model::*;
+use crate::points3d::*;
+
+const LEAF_SIZE: usize = 4;
+
+#[derive(Debug, Clone, Copy)]
+struct Node {
+  min: Point,
+  max: Point,
+  // children for inner nodes, range of `order` for leaves
+  first: u32,
+  second: u32,
+  leaf: bool,
+}
+
+/// Bounding volume hierarchy over triangles of a model for distance and inside queries
+#[derive(Debug, Clone)]
+pub struct Bvh {
+  triangles: Vec<[Point; 3]>,
+  // triangle indices, every leaf has a continuous range
+  order: Vec<u32>,
+  nodes: Vec<Node>,
+}
+
+/// Closest point of the surface
+#[derive(Debug, Clone, Copy)]
+pub struct SurfacePoint {
+  pub distance: f32,
+  pub point: Point,
+  /// index of the triangle in the model
+  pub triangle: u32,
+}
+
+/// Closest points of two surfaces
+#[derive(Debug, Clone, Copy)]
+pub struct SurfacePair {
+  pub distance: f32,
+  pub points: [Point; 2],
+  /// indices of triangles in both models
+  pub triangles: [u32; 2],
+}
+
+fn min_p(a: Point, b: Point) -> Point {
+  Point { x: a.x.min(b.x), y: a.y.min(b.y), z: a.z.min(b.z) }
+}
+
+fn max_p(a: Point, b: Point) -> Point {
+  Point { x: a.x.max(b.x), y: a.y.max(b.y), z: a.z.max(b.z) }
+}
+
+fn coord(p: Point, axis: usize) -> f32 {
+  match axis {
+    0 => p.x,
+    1 => p.y,
+    _ => p.z,
+  }
+}
+
+impl Bvh {
+  pub fn new(model: &Model) -> Self {
+    let triangles: Vec<[Point; 3]> =
+      model.triangles.iter().map(|t| t.map(|v| model.vertices[v as usize])).collect();
+    let order = (0..triangles.len() as u32).collect();
+    Self::with_order(triangles, order)
+  }
+
+  /// Hierarchy over the triangles listed in `order` only, the others are never found
+  pub fn with_order(triangles: Vec<[Point; 3]>, order: Vec<u32>) -> Self {
+    let mut result = Self { triangles, order, nodes: Vec::new() };
+    if !result.order.is_empty() {
+      result.build(0, result.order.len());
+    }
+    result
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.nodes.is_empty()
+  }
+
+  /// Box around all triangles
+  pub fn aabb(&self) -> Option<(Point, Point)> {
+    self.nodes.first().map(|n| (n.min, n.max))
+  }
+
+  pub fn triangle(&self, i: u32) -> [Point; 3] {
+    self.triangles[i as usize]
+  }
+
+  // splits by the median of centers along the longest side, returns the node index
+  fn build(&mut self, start: usize, end: usize) -> u32 {
+    let mut min = Point { x: f32::MAX, y: f32::MAX, z: f32::MAX };
+    let mut max = -min;
+    for &i in &self.order[start..end] {
+      for p in self.triangles[i as usize] {
+        min = min_p(min, p);
+        max = max_p(max, p);
+      }
+    }
+    let index = self.nodes.len() as u32;
+    self.nodes.push(Node { min, max, first: start as u32, second: end as u32, leaf: true });
+    if end - start <= LEAF_SIZE {
+      return index;
+    }
+
+    let size = max - min;
+    let axis = if size.x >= size.y && size.x >= size.z {
+      0
+    } else if size.y >= size.z {
+      1
+    } else {
+      2
+    };
+    let triangles = &self.triangles;
+    let center = |i: &u32| {
+      let [v0, v1, v2] = triangles[*i as usize];
+      coord(v0 + v1 + v2, axis)
+    };
+    let mid = (start + end) / 2;
+    self.order[start..end]
+      .select_nth_unstable_by(mid - start, |i, j| center(i).total_cmp(&center(j)));
+    let first = self.build(start, mid);
+    let second = self.build(mid, end);
+    self.nodes[index as usize] = Node { min, max, first, second, leaf: false };
+    index
+  }
+
+  /// The closest point of the surface if it is not farther than `max_distance`
+  pub fn closest(&self, p: Point, max_distance: f32) -> Option<SurfacePoint> {
+    let mut best: Option<SurfacePoint> = None;
+    let mut limit = max_distance * max_distance;
+    let mut for_visit = Vec::new();
+    if !self.is_empty() {
+      for_visit.push(0);
+    }
+    while let Some(n) = for_visit.pop() {
+      let node = self.nodes[n as usize];
+      if box_sqr_dist(p, node.min, node.max) > limit {
+        continue;
+      }
+      if !node.leaf {
+        // the nearer child is visited first
+        let d1 = self.node_sqr_dist(p, node.first);
+        let d2 = self.node_sqr_dist(p, node.second);
+        if d1 < d2 {
+          for_visit.extend([node.second, node.first]);
+        } else {
+          for_visit.extend([node.first, node.second]);
+        }
+        continue;
+      }
+      for &i in &self.order[node.first as usize..node.second as usize] {
+        let point = closest_on_triangle(p, self.triangles[i as usize]);
+        let d = (point - p).sqr_len();
+        if d <= limit {
+          limit = d;
+          best = Some(SurfacePoint { distance: d.sqrt(), point, triangle: i });
+        }
+      }
+    }
+    best
+  }
+
+  /// Some triangle is not farther than `distance` from `p`
+  pub fn is_near(&self, p: Point, distance: f32) -> bool {
+    let limit = distance * distance;
+    let mut for_visit = Vec::new();
+    if !self.is_empty() {
+      for_visit.push(0);
+    }
+    while let Some(n) = for_visit.pop() {
+      let node = self.nodes[n as usize];
+      if box_sqr_dist(p, node.min, node.max) > limit {
+        continue;
+      }
+      if !node.leaf {
+        for_visit.extend([node.first, node.second]);
+        continue;
+      }
+      for &i in &self.order[node.first as usize..node.second as usize] {
+        if (closest_on_triangle(p, self.triangles[i as usize]) - p).sqr_len() <= limit {
+          return true;
+        }
+      }
+    }
+    false
+  }
+
+  /// Point is inside of the closed surface, a ray from it crosses the surface odd number of times
+  pub fn is_inside(&self, p: Point) -> bool {
+    // not aligned with axes to not pass through edges of grid like meshes
+    const DIR: Point = Point { x: 0.9997, y: 0.0213, z: 0.0118 };
+    let inv = Point { x: DIR.x.recip(), y: DIR.y.recip(), z: DIR.z.recip() };
+    let mut crossings = 0;
+    let mut for_visit = Vec::new();
+    if !self.is_empty() {
+      for_visit.push(0);
+    }
+    while let Some(n) = for_visit.pop() {
+      let node = self.nodes[n as usize];
+      if !ray_hits_box(p, inv, node.min, node.max) {
+        continue;
+      }
+      if !node.leaf {
+        for_visit.extend([node.first, node.second]);
+        continue;
+      }
+      for &i in &self.order[node.first as usize..node.second as usize] {
+        if ray_crosses(p, DIR, self.triangles[i as usize]) {
+          crossings += 1;
+        }
+      }
+    }
+    crossings % 2 == 1
+  }
+
+  /// The closest points of this and `other` surfaces if they are not farther than `max_distance`,
+  /// crossing surfaces have zero distance
+  pub fn closest_pair(&self, other: &Bvh, max_distance: f32) -> Option<SurfacePair> {
+    let mut best: Option<SurfacePair> = None;
+    let mut limit = max_distance * max_distance;
+    let mut for_visit = Vec::new();
+    if !self.is_empty() && !other.is_empty() {
+      for_visit.push((0, 0));
+    }
+    while let Some((n1, n2)) = for_visit.pop() {
+      let (node1, node2) = (self.nodes[n1 as usize], other.nodes[n2 as usize]);
+      if boxes_sqr_dist(node1.min, node1.max, node2.min, node2.max) > limit {
+        continue;
+      }
+      // the bigger node is split
+      let split_first = !node1.leaf
+        && (node2.leaf || (node1.max - node1.min).sqr_len() >= (node2.max - node2.min).sqr_len());
+      if split_first {
+        for_visit.extend([(node1.first, n2), (node1.second, n2)]);
+        continue;
+      }
+      if !node2.leaf {
+        for_visit.extend([(n1, node2.first), (n1, node2.second)]);
+        continue;
+      }
+      for &i in &self.order[node1.first as usize..node1.second as usize] {
+        for &j in &other.order[node2.first as usize..node2.second as usize] {
+          let (d, p1, p2) =
+            closest_on_triangles(self.triangles[i as usize], other.triangles[j as usize]);
+          if d <= limit {
+            limit = d;
+            best = Some(SurfacePair { distance: d.sqrt(), points: [p1, p2], triangles: [i, j] });
+          }
+        }
+      }
+      if limit == 0.0 {
+        break;
+      }
+    }
+    best
+  }
+
+  /// Pairs of different triangles with touching boxes, the smaller index is the first
+  pub fn overlapping_pairs(&self) -> Vec<(u32, u32)> {
+    let mut result = Vec::new();
+    let mut add = |i: u32, j: u32| {
+      let (a, b) = (self.triangles[i as usize], self.triangles[j as usize]);
+      let box_of = |t: [Point; 3]| (min_p(min_p(t[0], t[1]), t[2]), max_p(max_p(t[0], t[1]), t[2]));
+      let ((min1, max1), (min2, max2)) = (box_of(a), box_of(b));
+      if boxes_sqr_dist(min1, max1, min2, max2) == 0.0 {
+        result.push((i.min(j), i.max(j)));
+      }
+    };
+    let mut for_visit = Vec::new();
+    if !self.is_empty() {
+      for_visit.push((0, 0));
+    }
+    while let Some((n1, n2)) = for_visit.pop() {
+      let (node1, node2) = (self.nodes[n1 as usize], self.nodes[n2 as usize]);
+      // pairs inside of one node are pairs inside of each child and between children
+      if n1 == n2 {
+        if node1.leaf {
+          let leaf = &self.order[node1.first as usize..node1.second as usize];
+          for (k, &i) in leaf.iter().enumerate() {
+            for &j in &leaf[k + 1..] {
+              add(i, j);
+            }
+          }
+        } else {
+          let (first, second) = (node1.first, node1.second);
+          for_visit.extend([(first, first), (second, second), (first, second)]);
+        }
+        continue;
+      }
+      if boxes_sqr_dist(node1.min, node1.max, node2.min, node2.max) > 0.0 {
+        continue;
+      }
+      let split_first = !node1.leaf
+        && (node2.leaf || (node1.max - node1.min).sqr_len() >= (node2.max - node2.min).sqr_len());
+      if split_first {
+        for_visit.extend([(node1.first, n2), (node1.second, n2)]);
+        continue;
+      }
+      if !node2.leaf {
+        for_visit.extend([(n1, node2.first), (n1, node2.second)]);
+        continue;
+      }
+      for &i in &self.order[node1.first as usize..node1.second as usize] {
+        for &j in &self.order[node2.first as usize..node2.second as usize] {
+          add(i, j);
+        }
+      }
+    }
+    result
+  }
+
+  fn node_sqr_dist(&self, p: Point, n: u32) -> f32 {
+    let node = &self.nodes[n as usize];
+    box_sqr_dist(p, node.min, node.max)
+  }
+}
+
+fn box_sqr_dist(p: Point, min: Point, max: Point) -> f32 {
+  let d = |c: f32, min: f32, max: f32| (min - c).max(0.0).max(c - max);
+  let (dx, dy, dz) = (d(p.x, min.x, max.x), d(p.y, min.y, max.y), d(p.z, min.z, max.z));
+  dx * dx + dy * dy + dz * dz
+}
+
+fn boxes_sqr_dist(min1: Point, max1: Point, min2: Point, max2: Point) -> f32 {
+  let d = |min1: f32, max1: f32, min2: f32, max2: f32| (min1 - max2).max(0.0).max(min2 - max1);
+  let dx = d(min1.x, max1.x, min2.x, max2.x);
+  let dy = d(min1.y, max1.y, min2.y, max2.y);
+  let dz = d(min1.z, max1.z, min2.z, max2.z);
+  dx * dx + dy * dy + dz * dz
+}
+
+fn ray_hits_box(p: Point, inv: Point, min: Point, max: Point) -> bool {
+  let mut t_min = 0.0f32;
+  let mut t_max = f32::INFINITY;
+  for axis in 0..3 {
+    let (o, i) = (coord(p, axis), coord(inv, axis));
+    let t1 = (coord(min, axis) - o) * i;
+    let t2 = (coord(max, axis) - o) * i;
+    t_min = t_min.max(t1.min(t2));
+    t_max = t_max.min(t1.max(t2));
+  }
+  t_min <= t_max
+}
+
+fn ray_crosses(p: Point, dir: Point, t: [Point; 3]) -> bool {
+  let e1 = t[1] - t[0];
+  let e2 = t[2] - t[0];
+  let h = cross(dir, e2);
+  let det = dot(e1, h);
+  if det == 0.0 {
+    return false;
+  }
+  let s = p - t[0];
+  let u = dot(s, h) / det;
+  if !(0.0..1.0).contains(&u) {
+    return false;
+  }
+  let q = cross(s, e1);
+  let v = dot(dir, q) / det;
+  v >= 0.0 && u + v < 1.0 && dot(e2, q) / det > 0.0
+}
+
+/// Closest to `p` point of the triangle
+pub fn closest_on_triangle(p: Point, t: [Point; 3]) -> Point {
+  let [a, b, c] = t;
+  let ab = b - a;
+  let ac = c - a;
+  let ap = p - a;
+  let d1 = dot(ab, ap);
+  let d2 = dot(ac, ap);
+  if d1 <= 0.0 && d2 <= 0.0 {
+    return a;
+  }
+  let bp = p - b;
+  let d3 = dot(ab, bp);
+  let d4 = dot(ac, bp);
+  if d3 >= 0.0 && d4 <= d3 {
+    return b;
+  }
+  let vc = d1 * d4 - d3 * d2;
+  if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+    return a + ab.scale(d1 / (d1 - d3));
+  }
+  let cp = p - c;
+  let d5 = dot(ab, cp);
+  let d6 = dot(ac, cp);
+  if d6 >= 0.0 && d5 <= d6 {
+    return c;
+  }
+  let vb = d5 * d2 - d1 * d6;
+  if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+    return a + ac.scale(d2 / (d2 - d6));
+  }
+  let va = d3 * d6 - d5 * d4;
+  if va <= 0.0 && d4 - d3 >= 0.0 && d5 - d6 >= 0.0 {
+    return b + (c - b).scale((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+  }
+  let denom = (va + vb + vc).recip();
+  a + ab.scale(vb * denom) + ac.scale(vc * denom)
+}
+
+// squared distance and the closest points of two triangles, zero for crossing ones
+fn closest_on_triangles(a: [Point; 3], b: [Point; 3]) -> (f32, Point, Point) {
+  for k in 0..3 {
+    if let Some(p) = segment_hit(a[k], a[(k + 1) % 3], b) {
+      return (0.0, p, p);
+    }
+    if let Some(p) = segment_hit(b[k], b[(k + 1) % 3], a) {
+      return (0.0, p, p);
+    }
+  }
+  let mut best = (f32::MAX, a[0], b[0]);
+  let mut check = |p1: Point, p2: Point| {
+    let d = (p1 - p2).sqr_len();
+    if d < best.0 {
+      best = (d, p1, p2);
+    }
+  };
+  for k in 0..3 {
+    check(a[k], closest_on_triangle(a[k], b));
+    check(closest_on_triangle(b[k], a), b[k]);
+    for l in 0..3 {
+      let (p1, p2) = closest_on_segments(a[k], a[(k + 1) % 3], b[l], b[(l + 1) % 3]);
+      check(p1, p2);
+    }
+  }
+  best
+}
+
+// point where the segment passes through the triangle, parallel segments have none
+fn segment_hit(p0: Point, p1: Point, t: [Point; 3]) -> Option<Point> {
+  let dir = p1 - p0;
+  let e1 = t[1] - t[0];
+  let e2 = t[2] - t[0];
+  let h = cross(dir, e2);
+  let det = dot(e1, h);
+  if det == 0.0 {
+    return None;
+  }
+  let s = p0 - t[0];
+  let u = dot(s, h) / det;
+  let q = cross(s, e1);
+  let v = dot(dir, q) / det;
+  let along = dot(e2, q) / det;
+  let inside = u >= 0.0 && v >= 0.0 && u + v <= 1.0 && (0.0..=1.0).contains(&along);
+  inside.then(|| p0 + dir.scale(along))
+}
+
+/// Closest points of segments `p1 q1` and `p2 q2`
+pub fn closest_on_segments(p1: Point, q1: Point, p2: Point, q2: Point) -> (Point, Point) {
+  let d1 = q1 - p1;
+  let d2 = q2 - p2;
+  let r = p1 - p2;
+  let a = d1.sqr_len();
+  let e = d2.sqr_len();
+  let f = dot(d2, r);
+  let (s, t) = if a <= f32::EPSILON && e <= f32::EPSILON {
+    (0.0, 0.0)
+  } else if a <= f32::EPSILON {
+    (0.0, (f / e).clamp(0.0, 1.0))
+  } else {
+    let c = dot(d1, r);
+    if e <= f32::EPSILON {
+      ((-c / a).clamp(0.0, 1.0), 0.0)
+    } else {
+      let b = dot(d1, d2);
+      let denom = a * e - b * b;
+      let mut s = if denom > 0.0 { ((b * f - c * e) / denom).clamp(0.0, 1.0) } else { 0.0 };
+      let mut t = (b * s + f) / e;
+      if t < 0.0 {
+        t = 0.0;
+        s = (-c / a).clamp(0.0, 1.0);
+      } else if t > 1.0 {
+        t = 1.0;
+        s = ((b - c) / a).clamp(0.0, 1.0);
+      }
+      (s, t)
+    }
+  };
+  (p1 + d1.scale(s), p2 + d2.scale(t))
+}
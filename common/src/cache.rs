@@ -0,0 +1,227 @@
+use crate::hollow::{HollowParams, HollowReport};
+use crate::model::*;
+use crate::octree::AdaptiveParams;
+use crate::sharp::SharpParams;
+use crate::solid::{MeshDomain, PartIndex};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// Everything generated models depend on besides the creator's own code
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheInputs {
+  pub creator: String,
+  /// json of effective parameters, `None` for creators without them
+  pub params: Option<String>,
+  pub quality: usize,
+  pub size: f32,
+  /// version of the package and hash of the sources it is built from
+  pub version: String,
+  /// limits of `Model::decimate` applied to finished models
+  pub max_triangles: Option<usize>,
+  pub max_deviation: Option<f32>,
+  /// `Model::hollow` applied before decimation
+  pub hollow: Option<HollowParams>,
+  /// models are meshed by `OctreeCreator` instead of `ModelCreator`
+  pub adaptive: Option<AdaptiveParams>,
+  /// grid of `ModelCreator`, only its steps and offsets are in the key,
+  /// so parts meshed in smaller boxes of the same grid are reused
+  pub domain: MeshDomain,
+  /// `Model::sharpen` applied before smoothing
+  pub sharp: Option<SharpParams>,
+}
+
+impl CacheInputs {
+  /// `part_key` is `PartCreator::get_cache_key` of the part,
+  /// it replaces parameters of the whole creator if the part depends only on some of them
+  pub fn key(&self, part_key: Option<&str>) -> String {
+    let params = part_key.or(self.params.as_deref());
+    let decimate = (self.max_triangles, self.max_deviation.map(f32::to_bits));
+    let hollow = self.hollow.as_ref().map(|h| {
+      [h.wall, h.cell_size, h.drain_radius, h.lattice_step, h.lattice_width].map(f32::to_bits)
+    });
+    let adaptive =
+      self.adaptive.as_ref().map(|a| (a.min_depth, a.max_depth, a.max_angle.to_bits()));
+    // offsets are rounded to ignore errors of their calculation in boxes of different sizes
+    let offset = |(step, offset): (f32, f32)| (offset / step * 1.0e4).round() as i32;
+    let lattice = self.domain.lattice().map(|l| (l.0.to_bits(), offset(l)));
+    let domain = (lattice, self.domain.sweep);
+    let sharp = self.sharp.as_ref().map(|s| [s.angle, s.cell_size].map(f32::to_bits));
+    let hash = fxhash::hash64(&(
+      &self.creator,
+      params,
+      self.quality,
+      self.size.to_bits(),
+      &self.version,
+      decimate,
+      hollow,
+      adaptive,
+      domain,
+      sharp,
+    ));
+    format!("{hash:016x}")
+  }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedPart {
+  key: String,
+  file: String,
+  // what hollowing removed from the saved model
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  hollow: Option<HollowReport>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct CacheMeta {
+  creator: String,
+  parts: BTreeMap<PartIndex, CachedPart>,
+  // only some parts were meshed, others may be missing
+  #[serde(default)]
+  partial: bool,
+}
+
+/// Finished models of the last runs stored in `dir` with `cache.json` describing them,
+/// a model is reused while the key it was saved with is the same
+pub struct PartCache {
+  dir: PathBuf,
+  meta: CacheMeta,
+}
+
+impl PartCache {
+  const META_FILE: &'static str = "cache.json";
+
+  /// Missing or broken metadata gives an empty cache
+  pub fn open(dir: &Path, creator: &str) -> Self {
+    let meta = std::fs::read_to_string(dir.join(Self::META_FILE))
+      .ok()
+      .and_then(|json| serde_json::from_str::<CacheMeta>(&json).ok())
+      .filter(|meta| meta.creator == creator)
+      .unwrap_or_else(|| CacheMeta { creator: creator.to_string(), ..Default::default() });
+    Self { dir: dir.to_path_buf(), meta }
+  }
+
+  /// Parts saved by the last run, some of them may be outdated
+  pub fn parts(&self) -> impl Iterator<Item = PartIndex> + '_ {
+    self.meta.parts.keys().copied()
+  }
+
+  /// `parts` are not all parts of the creator, runs meshed only some of them
+  pub fn is_partial(&self) -> bool {
+    self.meta.partial
+  }
+
+  pub fn set_partial(&mut self, partial: bool) {
+    self.meta.partial = partial;
+  }
+
+  /// Model saved with the same `key`
+  pub fn load(&self, m_index: PartIndex, key: &str) -> Option<Model> {
+    let part = self.meta.parts.get(&m_index).filter(|part| part.key == key)?;
+    Model::load_from_stl(&self.dir.join(&part.file)).ok()
+  }
+
+  /// Hollowed models keep the report of `Model::hollow` which can't be found from them
+  pub fn store(
+    &mut self,
+    m_index: PartIndex,
+    key: String,
+    model: &Model,
+    hollow: Option<HollowReport>,
+  ) -> Result<(), String> {
+    std::fs::create_dir_all(&self.dir)
+      .map_err(|e| format!("Unable to create directory {}: {}", self.dir.to_string_lossy(), e))?;
+    let file = format!("part_{m_index}.stl");
+    model.save_to_stl(&self.dir.join(&file))?;
+    self.meta.parts.insert(m_index, CachedPart { key, file, hollow });
+    Ok(())
+  }
+
+  /// Report of hollowing the saved model, `None` if it was not hollowed
+  pub fn hollow_report(&self, m_index: PartIndex) -> Option<&HollowReport> {
+    self.meta.parts.get(&m_index)?.hollow.as_ref()
+  }
+
+  /// Forgets parts which were not generated by the current run
+  pub fn retain(&mut self, f: impl Fn(PartIndex) -> bool) {
+    self.meta.parts.retain(|&m_index, _| f(m_index));
+  }
+
+  pub fn save(&self) -> Result<(), String> {
+    let path = self.dir.join(Self::META_FILE);
+    let json = serde_json::to_string_pretty(&self.meta)
+      .map_err(|e| format!("Unable to serialize cache metadata: {}", e))?;
+    std::fs::create_dir_all(&self.dir)
+      .and_then(|_| std::fs::write(&path, json))
+      .map_err(|e| format!("Unable to write cache metadata to {}: {}", path.to_string_lossy(), e))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::points3d::Point;
+  use crate::test_dir::TestDir;
+
+  fn inputs(domain: MeshDomain) -> CacheInputs {
+    CacheInputs {
+      creator: "test".to_string(),
+      params: None,
+      quality: 40,
+      size: 20.0,
+      version: "1".to_string(),
+      max_triangles: None,
+      max_deviation: None,
+      hollow: None,
+      adaptive: None,
+      domain,
+      sharp: None,
+    }
+  }
+
+  #[test]
+  fn key_of_grid() {
+    let domain = MeshDomain::cube(40, 20.0);
+    let key = inputs(domain.clone()).key(None);
+    let min = Point { x: -3.0, y: 1.0, z: 2.0 };
+    let sub_domain = domain.sub_domain(min, min + Point { x: 4.0, y: 5.0, z: 6.0 });
+    assert_eq!(inputs(sub_domain).key(None), key);
+    assert_ne!(inputs(domain.clone().with_sweep(0)).key(None), key);
+    assert_ne!(inputs(MeshDomain::cube(41, 20.0)).key(None), key);
+    assert_ne!(inputs(domain.clone()).key(Some("part")), key);
+    let version = CacheInputs { version: "2".to_string(), ..inputs(domain) };
+    assert_ne!(version.key(None), key);
+  }
+
+  #[test]
+  fn store_and_load() {
+    let dir = TestDir::new("part_cache");
+    let cube = Model::cuboid(2, 2, 2, 0.5);
+    let mut cache = PartCache::open(&dir, "test");
+    assert_eq!(cache.parts().count(), 0);
+    let hollow =
+      HollowReport { volume_before: 2.0, volume_after: 1.0, cavities: 1, drain_holes: 0 };
+    cache.store(3, "key".to_string(), &cube, Some(hollow)).unwrap();
+    cache.store(5, "key".to_string(), &cube, None).unwrap();
+    cache.set_partial(true);
+    cache.retain(|m_index| m_index != 5);
+    cache.save().unwrap();
+
+    let cache = PartCache::open(&dir, "test");
+    assert_eq!(cache.parts().collect::<Vec<_>>(), [3]);
+    assert!(cache.is_partial());
+    let loaded = cache.load(3, "key").unwrap();
+    assert_eq!(loaded.triangles.len(), cube.triangles.len());
+    assert!((loaded.get_volume() - 1.0).abs() < 1.0e-5);
+    assert_eq!(cache.hollow_report(3).map(HollowReport::saved_volume), Some(1.0));
+    assert!(cache.load(3, "other key").is_none());
+    assert!(cache.load(5, "key").is_none());
+    // parts of other creators are not reused
+    assert_eq!(PartCache::open(&dir, "other").parts().count(), 0);
+
+    let mut cache = PartCache::open(&dir, "test");
+    cache.set_partial(false);
+    cache.save().unwrap();
+    assert!(!PartCache::open(&dir, "test").is_partial());
+  }
+}
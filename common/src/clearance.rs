@@ -0,0 +1,208 @@
+use crate::bvh::*;
+use crate::model::*;
+use crate::points3d::*;
+use crate::progress::*;
+use crate::solid::PartIndex;
+use fxhash::FxHashMap;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ClearanceParams {
+  /// narrower gaps between parts are reported
+  pub min_gap: f32,
+  /// parts farther from each other are not neighbours and are not measured
+  pub max_distance: f32,
+}
+
+impl Default for ClearanceParams {
+  fn default() -> Self {
+    Self { min_gap: 0.2, max_distance: 1.0 }
+  }
+}
+
+/// The tightest spot between two neighbouring parts
+#[derive(Debug, Clone, Serialize)]
+pub struct PartsClearance {
+  pub parts: [PartIndex; 2],
+  /// zero for intersecting parts
+  pub distance: f32,
+  /// closest points of both parts, a common point for intersecting ones
+  pub points: [Point; 2],
+  /// surfaces cross or touch, or one part is inside the other
+  pub intersecting: bool,
+}
+
+impl PartsClearance {
+  pub fn location(&self) -> Point {
+    (self.points[0] + self.points[1]).scale(0.5)
+  }
+}
+
+/// Result of `check_clearance`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ClearanceReport {
+  pub min_gap: f32,
+  /// all neighbouring pairs from the tightest one
+  pub pairs: Vec<PartsClearance>,
+}
+
+impl ClearanceReport {
+  pub fn intersections(&self) -> impl Iterator<Item = &PartsClearance> {
+    self.pairs.iter().filter(|p| p.intersecting)
+  }
+
+  /// Gaps narrower than `min_gap` between not intersecting parts
+  pub fn too_tight(&self) -> impl Iterator<Item = &PartsClearance> {
+    self.pairs.iter().filter(|p| !p.intersecting && p.distance < self.min_gap)
+  }
+
+  pub fn tightest(&self) -> Option<&PartsClearance> {
+    self.pairs.first()
+  }
+
+  pub fn is_ok(&self) -> bool {
+    self.pairs.iter().all(|p| !p.intersecting && p.distance >= self.min_gap)
+  }
+
+  pub fn summary(&self) -> String {
+    let mut result = format!(
+      "{} neighbouring pairs, {} intersecting, {} closer than {}",
+      self.pairs.len(),
+      self.intersections().count(),
+      self.too_tight().count(),
+      self.min_gap
+    );
+    if let Some(p) = self.tightest() {
+      let l = p.location();
+      result += &format!(
+        ", the tightest gap {} between {} and {} at {} {} {}",
+        p.distance, p.parts[0], p.parts[1], l.x, l.y, l.z
+      );
+    }
+    result
+  }
+
+  pub fn save_to_json(&self, path: &Path) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(self)
+      .map_err(|e| format!("Unable to serialize clearance report: {}", e))?;
+    std::fs::write(path, json)
+      .map_err(|e| format!("Unable to write clearance report to {}: {}", path.to_string_lossy(), e))
+  }
+}
+
+/// Measures gaps between every two parts closer than `params.max_distance`
+pub fn check_clearance(
+  models: &FxHashMap<PartIndex, Model>,
+  params: &ClearanceParams,
+  progress: &dyn Progress,
+) -> ClearanceReport {
+  let start = std::time::Instant::now();
+  let mut parts: Vec<(PartIndex, Bvh)> =
+    models.iter().map(|(&i, m)| (i, Bvh::new(m))).filter(|(_, bvh)| !bvh.is_empty()).collect();
+  parts.sort_by_key(|(i, _)| *i);
+
+  let mut pairs = Vec::new();
+  for (k, (i1, bvh1)) in parts.iter().enumerate() {
+    for (i2, bvh2) in &parts[k + 1..] {
+      pairs.extend(parts_clearance([*i1, *i2], bvh1, bvh2, params.max_distance));
+    }
+    progress.progress("checked parts", k + 1, parts.len(), start.elapsed());
+  }
+  pairs.sort_by(|p1, p2| p1.distance.total_cmp(&p2.distance).then(p1.parts.cmp(&p2.parts)));
+  ClearanceReport { min_gap: params.min_gap, pairs }
+}
+
+fn parts_clearance(
+  parts: [PartIndex; 2],
+  bvh1: &Bvh,
+  bvh2: &Bvh,
+  max_distance: f32,
+) -> Option<PartsClearance> {
+  let (min1, max1) = bvh1.aabb()?;
+  let (min2, max2) = bvh2.aabb()?;
+  let gap = |min1: f32, max1: f32, min2: f32, max2: f32| (min1 - max2).max(min2 - max1);
+  let gaps = [
+    gap(min1.x, max1.x, min2.x, max2.x),
+    gap(min1.y, max1.y, min2.y, max2.y),
+    gap(min1.z, max1.z, min2.z, max2.z),
+  ];
+  if gaps.iter().any(|&g| g > max_distance) {
+    return None;
+  }
+  let closest = bvh1.closest_pair(bvh2, max_distance);
+  if let Some(pair) = closest.filter(|pair| pair.distance == 0.0) {
+    return Some(PartsClearance { parts, distance: 0.0, points: pair.points, intersecting: true });
+  }
+  // surfaces don't touch, but one part can be inside the other one
+  if gaps.iter().all(|&g| g < 0.0) {
+    for (inner, outer) in [(bvh1, bvh2), (bvh2, bvh1)] {
+      let p = inner.triangle(0)[0];
+      if outer.is_inside(p) {
+        let points = closest.map_or([p, p], |pair| pair.points);
+        return Some(PartsClearance { parts, distance: 0.0, points, intersecting: true });
+      }
+    }
+  }
+  closest.map(|pair| PartsClearance {
+    parts,
+    distance: pair.distance,
+    points: pair.points,
+    intersecting: false,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn clearance_of_cubes() {
+    let cube = |x: f32, yz: f32, size: f32| {
+      let mut m = Model::cuboid(1, 1, 1, size);
+      m.map_points(|p| p + Point { x, y: yz, z: yz });
+      m
+    };
+    let models = FxHashMap::from_iter([
+      (1, cube(0.0, 0.0, 1.0)),
+      (2, cube(1.15, 0.0, 1.0)),
+      (3, cube(2.5, 0.0, 1.0)),
+      (4, cube(2.75, 0.25, 0.5)),
+      (5, cube(10.0, 0.0, 1.0)),
+    ]);
+    let params = ClearanceParams { min_gap: 0.2, max_distance: 1.0 };
+    let report = check_clearance(&models, &params, &SilentProgress);
+    let pairs: Vec<_> = report.pairs.iter().map(|p| (p.parts, p.intersecting)).collect();
+    // 4 is inside of 3, 5 is too far from others
+    assert_eq!(pairs, [([3, 4], true), ([1, 2], false), ([2, 3], false), ([2, 4], false)]);
+    assert!((report.pairs[1].distance - 0.15).abs() < 1.0e-4);
+    assert!((report.pairs[1].location().x - 1.075).abs() < 1.0e-4);
+    assert_eq!(report.too_tight().count(), 1);
+  }
+
+  #[test]
+  fn clearance_of_touching_cubes() {
+    let cube = |x: f32, y: f32, z: f32, size: f32| {
+      let mut m = Model::cuboid(1, 1, 1, size);
+      m.map_points(|p| p + Point { x, y, z });
+      m
+    };
+    // a common face, a smaller face inside of a face, a common edge and a common corner
+    let models = FxHashMap::from_iter([
+      (1, cube(0.0, 0.0, 0.0, 1.0)),
+      (2, cube(1.0, 0.0, 0.0, 1.0)),
+      (3, cube(0.25, 0.25, -0.5, 0.5)),
+      (4, cube(-1.0, 1.0, 0.0, 1.0)),
+      (5, cube(1.0, -1.0, -1.0, 1.0)),
+    ]);
+    let params = ClearanceParams { min_gap: 0.2, max_distance: 0.1 };
+    let report = check_clearance(&models, &params, &SilentProgress);
+    let pairs: Vec<_> = report.pairs.iter().map(|p| p.parts).collect();
+    assert_eq!(pairs, [[1, 2], [1, 3], [1, 4], [1, 5], [2, 5]]);
+    for pair in &report.pairs {
+      assert!(pair.intersecting && pair.distance == 0.0, "{:?}", pair.parts);
+    }
+    assert!(!report.is_ok());
+    assert_eq!(report.too_tight().count(), 0);
+  }
+}
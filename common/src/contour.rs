@@ -1,5 +1,6 @@
 use crate::bit_buffer::*;
 use crate::points2d::*;
+use crate::progress::*;
 use dxf::Drawing;
 use dxf::entities::*;
 use dxf::objects::*;
@@ -69,6 +70,11 @@ impl FlatFigure {
     Self { contours: Vec::new(), triangles: Vec::new() }
   }
 
+  /// Figure without triangulation, outer contours go counterclockwise and holes clockwise
+  pub fn from_contours(contours: Vec<Contour>) -> Self {
+    Self { contours, triangles: Vec::new() }
+  }
+
   pub fn get_square(&self) -> f32 {
     self.contours.iter().map(|c| c.get_square()).sum()
   }
@@ -822,7 +828,12 @@ impl ContourCreator {
     cell.corner_part = part_f(cell.corner);
   }
 
-  pub fn make_topology(mut self, part_f: &dyn Fn(Point) -> PartIndex) -> GeneratorResult {
+  pub fn make_topology(
+    mut self,
+    part_f: &dyn Fn(Point) -> PartIndex,
+    progress: &dyn Progress,
+  ) -> GeneratorResult {
+    let start = std::time::Instant::now();
     if self.size_x == 0 || self.size_y == 0 {
       return GeneratorResult::default();
     }
@@ -1016,6 +1027,7 @@ impl ContourCreator {
         cells[ofs_prev + x] = ContourCell::new();
       }
       (ofs_prev, ofs_cur) = (ofs_cur, ofs_prev);
+      progress.progress("contour rows", y, szy - 1, start.elapsed());
     }
 
     result.to_generator_result()
@@ -0,0 +1,372 @@
+use crate::model::*;
+use crate::points3d::*;
+use crate::progress::*;
+use crate::validation::Edge;
+use fxhash::{FxHashMap, FxHashSet};
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+
+/// Limits of `Model::decimate`, it stops at the first reached one
+#[derive(Debug, Clone, Default)]
+pub struct DecimateParams {
+  /// triangle budget of the result
+  pub max_triangles: Option<usize>,
+  /// max distance from moved vertices to planes of the original faces around them
+  pub max_deviation: Option<f32>,
+}
+
+impl DecimateParams {
+  /// false when no limit is given and decimation leaves models as they are
+  pub fn is_limited(&self) -> bool {
+    self.max_triangles.is_some() || self.max_deviation.is_some()
+  }
+}
+
+// symmetric 4x4 matrix of squared distance to planes
+#[derive(Debug, Clone, Copy, Default)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+  fn plane(n: [f64; 3], d: f64) -> Self {
+    let [a, b, c] = n;
+    Self([a * a, a * b, a * c, a * d, b * b, b * c, b * d, c * c, c * d, d * d])
+  }
+
+  fn add(&mut self, other: &Quadric) {
+    for k in 0..10 {
+      self.0[k] += other.0[k];
+    }
+  }
+
+  fn error(&self, p: [f64; 3]) -> f64 {
+    let [aa, ab, ac, ad, bb, bc, bd, cc, cd, dd] = self.0;
+    let [x, y, z] = p;
+    x * x * aa
+      + y * y * bb
+      + z * z * cc
+      + 2.0 * (x * y * ab + x * z * ac + y * z * bc + x * ad + y * bd + z * cd)
+      + dd
+  }
+
+  // point with the minimal error if it is defined well enough
+  fn minimum(&self) -> Option<[f64; 3]> {
+    let [aa, ab, ac, ad, bb, bc, bd, cc, cd, _] = self.0;
+    let det = aa * (bb * cc - bc * bc) - ab * (ab * cc - bc * ac) + ac * (ab * bc - bb * ac);
+    let scale = (aa + bb + cc).powi(3);
+    if det.abs() <= scale * 1.0e-9 {
+      return None;
+    }
+    let (rx, ry, rz) = (-ad, -bd, -cd);
+    let x = (rx * (bb * cc - bc * bc) - ab * (ry * cc - bc * rz) + ac * (ry * bc - bb * rz)) / det;
+    let y = (aa * (ry * cc - rz * bc) - rx * (ab * cc - bc * ac) + ac * (ab * rz - ry * ac)) / det;
+    let z = (aa * (bb * rz - bc * ry) - ab * (ab * rz - ry * ac) + rx * (ab * bc - bb * ac)) / det;
+    Some([x, y, z])
+  }
+}
+
+fn to_f64(p: Point) -> [f64; 3] {
+  [p.x as f64, p.y as f64, p.z as f64]
+}
+
+fn to_point(p: [f64; 3]) -> Point {
+  Point { x: p[0] as f32, y: p[1] as f32, z: p[2] as f32 }
+}
+
+// candidate collapse, the cheapest one is the greatest
+struct Collapse {
+  cost: f64,
+  keep: u32,
+  remove: u32,
+  // versions of both vertices when the collapse was computed
+  versions: (u32, u32),
+  position: Point,
+}
+
+impl PartialEq for Collapse {
+  fn eq(&self, other: &Self) -> bool {
+    self.cost == other.cost
+  }
+}
+
+impl Eq for Collapse {}
+
+impl PartialOrd for Collapse {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl Ord for Collapse {
+  fn cmp(&self, other: &Self) -> Ordering {
+    other.cost.total_cmp(&self.cost)
+  }
+}
+
+struct Decimator<'a> {
+  model: &'a mut Model,
+  quadrics: Vec<Quadric>,
+  faces: Vec<Vec<u32>>,
+  alive: Vec<bool>,
+  // vertices on open or non manifold edges or fixed by the caller which never move
+  locked: Vec<bool>,
+  versions: Vec<u32>,
+  heap: BinaryHeap<Collapse>,
+}
+
+impl Model {
+  /// Collapses edges in order of quadric error while the limits allow it.
+  /// Open and non manifold edges are kept, collapses breaking manifoldness
+  /// or flipping triangles are skipped
+  pub fn decimate(&mut self, params: &DecimateParams, progress: &dyn Progress) {
+    self.decimate_keeping(params, &[], progress);
+  }
+
+  /// `decimate` which does not move `fixed` vertices, like the ones shared with other parts
+  pub fn decimate_keeping(
+    &mut self,
+    params: &DecimateParams,
+    fixed: &[bool],
+    progress: &dyn Progress,
+  ) {
+    let target = params.max_triangles.unwrap_or(0);
+    let max_error = params.max_deviation.map_or(f64::INFINITY, |d| (d as f64).powi(2));
+    if !params.is_limited() || target >= self.triangles.len() {
+      return;
+    }
+
+    let start = std::time::Instant::now();
+    let total = self.triangles.len();
+    let mut decimator = Decimator::new(self, fixed);
+    let mut count = total;
+    while count > target {
+      let Some(c) = decimator.heap.pop() else { break };
+      if c.cost > max_error {
+        break;
+      }
+      if decimator.versions[c.keep as usize] != c.versions.0
+        || decimator.versions[c.remove as usize] != c.versions.1
+      {
+        continue;
+      }
+      if let Some(removed) = decimator.collapse(&c) {
+        count -= removed;
+        if count % 1000 < removed {
+          progress.progress("decimated triangles", total - count, total - target, start.elapsed());
+        }
+      }
+    }
+
+    let alive = decimator.alive;
+    let mut i = 0;
+    self.triangles.retain(|_| {
+      i += 1;
+      alive[i - 1]
+    });
+    self.delete_unused_v();
+    progress.log(&format!("decimated from {total} to {count} triangles in {:?}", start.elapsed()));
+  }
+
+  /// Vertices lying in one of `points`
+  pub fn vertices_at(&self, points: &FxHashSet<[u32; 3]>) -> Vec<bool> {
+    self.vertices.iter().map(|p| points.contains(&point_key(*p))).collect()
+  }
+}
+
+fn point_key(p: Point) -> [u32; 3] {
+  [p.x, p.y, p.z].map(|c| (c + 0.0).to_bits())
+}
+
+/// Positions of vertices found in at least two of `models`, where touching parts meet.
+/// Decimating and smoothing parts one by one keeps them if they stay fixed
+pub fn shared_points<'a>(models: impl IntoIterator<Item = &'a Model>) -> FxHashSet<[u32; 3]> {
+  let mut seen = FxHashSet::default();
+  let mut shared = FxHashSet::default();
+  for m in models {
+    let points: FxHashSet<_> = m.vertices.iter().map(|p| point_key(*p)).collect();
+    for p in points {
+      if !seen.insert(p) {
+        shared.insert(p);
+      }
+    }
+  }
+  shared
+}
+
+impl<'a> Decimator<'a> {
+  fn new(model: &'a mut Model, fixed: &[bool]) -> Self {
+    let vc = model.vertices.len();
+    let mut quadrics = vec![Quadric::default(); vc];
+    let mut faces = vec![Vec::new(); vc];
+    let mut edges = FxHashMap::<Edge, usize>::default();
+    for (i, t) in model.triangles.iter().enumerate() {
+      let [v0, v1, v2] = t.map(|v| model.vertices[v as usize]);
+      let n = cross(v1 - v0, v2 - v0);
+      if n.len() > 0.0 {
+        let n = to_f64(n.norm());
+        let d = -(n[0] * v0.x as f64 + n[1] * v0.y as f64 + n[2] * v0.z as f64);
+        let plane = Quadric::plane(n, d);
+        for &v in t {
+          quadrics[v as usize].add(&plane);
+        }
+      }
+      for k in 0..3 {
+        faces[t[k] as usize].push(i as u32);
+        let (a, b) = (t[k], t[(k + 1) % 3]);
+        *edges.entry((a.min(b), a.max(b))).or_default() += 1;
+      }
+    }
+    let mut locked: Vec<bool> = (0..vc).map(|v| fixed.get(v) == Some(&true)).collect();
+    for (&(a, b), &count) in &edges {
+      if count != 2 {
+        locked[a as usize] = true;
+        locked[b as usize] = true;
+      }
+    }
+
+    let alive = vec![true; model.triangles.len()];
+    let versions = vec![0; vc];
+    let mut result =
+      Self { model, quadrics, faces, alive, locked, versions, heap: BinaryHeap::new() };
+    let mut sorted: Vec<_> = edges.into_keys().collect();
+    sorted.sort();
+    for (a, b) in sorted {
+      result.push(a, b);
+    }
+    result
+  }
+
+  fn push(&mut self, a: u32, b: u32) {
+    let (keep, remove) = if self.locked[b as usize] { (b, a) } else { (a, b) };
+    if self.locked[remove as usize] {
+      return;
+    }
+    let mut q = self.quadrics[keep as usize];
+    q.add(&self.quadrics[remove as usize]);
+    let pa = self.model.vertices[keep as usize];
+    let pb = self.model.vertices[remove as usize];
+    let candidates = if self.locked[keep as usize] {
+      vec![to_f64(pa)]
+    } else {
+      let mut candidates = vec![to_f64(pa), to_f64(pb), to_f64((pa + pb).scale(0.5))];
+      candidates.extend(q.minimum());
+      candidates
+    };
+    let (cost, position) = candidates
+      .into_iter()
+      .map(|p| (q.error(p).max(0.0), p))
+      .min_by(|c0, c1| c0.0.total_cmp(&c1.0))
+      .unwrap();
+    let versions = (self.versions[keep as usize], self.versions[remove as usize]);
+    self.heap.push(Collapse { cost, keep, remove, versions, position: to_point(position) });
+  }
+
+  fn neighbours(&self, v: u32) -> FxHashSet<u32> {
+    let mut result = FxHashSet::default();
+    for &f in &self.faces[v as usize] {
+      result.extend(self.model.triangles[f as usize].iter().filter(|&&n| n != v));
+    }
+    result
+  }
+
+  // returns the number of removed triangles or `None` if the collapse is not allowed
+  fn collapse(&mut self, c: &Collapse) -> Option<usize> {
+    let (keep, remove) = (c.keep, c.remove);
+    let nk = self.neighbours(keep);
+    let nr = self.neighbours(remove);
+    // link condition, the edge has exactly two triangles and their third vertices are the only
+    // common neighbours, otherwise the result is not manifold
+    let common: Vec<u32> = nk.intersection(&nr).copied().collect();
+    let shared: Vec<u32> = self.faces[remove as usize]
+      .iter()
+      .copied()
+      .filter(|&f| self.model.triangles[f as usize].contains(&keep))
+      .collect();
+    if common.len() != 2 || shared.len() != 2 {
+      return None;
+    }
+
+    // moved triangles should not flip or become degenerate
+    let vertices = &self.model.vertices;
+    for &v in &[keep, remove] {
+      for &f in &self.faces[v as usize] {
+        if shared.contains(&f) {
+          continue;
+        }
+        let t = self.model.triangles[f as usize];
+        let old = cross(
+          vertices[t[1] as usize] - vertices[t[0] as usize],
+          vertices[t[2] as usize] - vertices[t[0] as usize],
+        );
+        let moved =
+          t.map(|w| if w == keep || w == remove { c.position } else { vertices[w as usize] });
+        let new = cross(moved[1] - moved[0], moved[2] - moved[0]);
+        if dot(old, new) <= 0.2 * old.len() * new.len() || new.len() <= f32::EPSILON * old.len() {
+          return None;
+        }
+      }
+    }
+
+    for &f in &shared {
+      self.alive[f as usize] = false;
+      let t = self.model.triangles[f as usize];
+      for &w in &t {
+        self.faces[w as usize].retain(|&g| g != f);
+      }
+    }
+    for f in std::mem::take(&mut self.faces[remove as usize]) {
+      for w in self.model.triangles[f as usize].iter_mut() {
+        if *w == remove {
+          *w = keep;
+        }
+      }
+      self.faces[keep as usize].push(f);
+    }
+    self.model.vertices[keep as usize] = c.position;
+    let q = self.quadrics[remove as usize];
+    self.quadrics[keep as usize].add(&q);
+    self.versions[keep as usize] += 1;
+    self.versions[remove as usize] += 1;
+
+    // costs of other edges are the same, they are checked again when taken from the heap
+    let mut neighbours: Vec<u32> = self.neighbours(keep).into_iter().collect();
+    neighbours.sort();
+    for n in neighbours {
+      self.push(keep, n);
+    }
+    Some(shared.len())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn decimate_flat_cuboid() {
+    let mut test = Model::cuboid(4, 4, 4, 1.0);
+    let params = DecimateParams { max_deviation: Some(1.0e-4), ..Default::default() };
+    test.decimate(&params, &SilentProgress);
+    assert!(test.triangles.len() <= 24);
+    assert!(test.validate().is_manifold());
+    assert!((test.get_volume() - 64.0).abs() < 1.0e-3);
+  }
+
+  #[test]
+  fn decimate_touching_cuboids() {
+    let mut second = Model::cuboid(4, 4, 4, 1.0);
+    second.map_points(|p| Point { x: p.x + 4.0, ..p });
+    let mut models = [Model::cuboid(4, 4, 4, 1.0), second];
+    let shared = shared_points(&models);
+    assert_eq!(shared.len(), 25);
+    let params = DecimateParams { max_deviation: Some(1.0e-4), ..Default::default() };
+    for m in &mut models {
+      let fixed = m.vertices_at(&shared);
+      m.decimate_keeping(&params, &fixed, &SilentProgress);
+      assert!(m.validate().is_manifold());
+      assert!((m.get_volume() - 64.0).abs() < 1.0e-3);
+      // both sides of the touching faces keep all their vertices
+      assert_eq!(m.vertices_at(&shared).iter().filter(|&&f| f).count(), 25);
+      assert!(m.triangles.len() < 4 * 4 * 2 * 6);
+    }
+  }
+}
@@ -0,0 +1,204 @@
+use crate::bvh::*;
+use crate::model::*;
+use crate::points3d::*;
+use crate::progress::*;
+use crate::solid::*;
+use crate::validation::DisjointSets;
+use fxhash::FxHashMap;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HollowParams {
+  /// thickness of walls left around the cavity
+  pub wall: f32,
+  /// distance between points of the cavity grid, it is made smaller for thin walls
+  pub cell_size: f32,
+  /// radius of holes going down from the lowest point of every cavity, 0 for no holes
+  pub drain_radius: f32,
+  /// distance between beams of the internal lattice along every axis, 0 for no lattice
+  pub lattice_step: f32,
+  pub lattice_width: f32,
+}
+
+impl Default for HollowParams {
+  fn default() -> Self {
+    Self { wall: 2.0, cell_size: 1.0, drain_radius: 0.0, lattice_step: 0.0, lattice_width: 1.0 }
+  }
+}
+
+/// What `Model::hollow` removed
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HollowReport {
+  pub volume_before: f32,
+  pub volume_after: f32,
+  pub cavities: usize,
+  pub drain_holes: usize,
+}
+
+impl HollowReport {
+  pub fn saved_volume(&self) -> f32 {
+    self.volume_before - self.volume_after
+  }
+
+  pub fn summary(&self) -> String {
+    format!(
+      "volume {} to {}, {} saved, {} cavities, {} drain holes",
+      self.volume_before,
+      self.volume_after,
+      self.saved_volume(),
+      self.cavities,
+      self.drain_holes
+    )
+  }
+}
+
+impl Model {
+  /// Removes everything farther than `params.wall` from the surface besides lattice beams.
+  /// The model should be closed, parts too thin for a cavity stay as they are
+  pub fn hollow(&mut self, params: &HollowParams, progress: &dyn Progress) -> HollowReport {
+    let volume_before = self.get_volume();
+    let mut report =
+      HollowReport { volume_before, volume_after: volume_before, ..Default::default() };
+    if params.wall <= 0.0 || self.triangles.is_empty() {
+      return report;
+    }
+
+    let bvh = Bvh::new(self);
+    let (min, max) = self.get_aabb();
+    let center = (min + max).scale(0.5);
+    // cavity surface lies between grid points, a cell must be thinner than walls and beams
+    let mut cell = params.cell_size.min(params.wall * 0.5);
+    if params.lattice_step > 0.0 {
+      cell = cell.min(params.lattice_width * 0.5);
+    }
+    let domain = MeshDomain::with_cell_size(min, max, Point { x: cell, y: cell, z: cell });
+
+    let step = params.lattice_step;
+    let half_width = params.lattice_width * 0.5;
+    // beams go along axes through the center and every `step` from it
+    let near_beam = |c: f32, margin: f32| {
+      step > 0.0 && (c - (c / step).round() * step).abs() <= half_width + margin
+    };
+    let cavity_f = |p: Point| -> PartIndex {
+      let q = p - center;
+      let beams = [q.x, q.y, q.z].into_iter().filter(|&c| near_beam(c, 0.0)).count();
+      if beams >= 2 || bvh.is_near(p, params.wall) || !bvh.is_inside(p) { 0 } else { 1 }
+    };
+    let mut mc = ModelCreator::with_domain(domain, 20, 0, &cavity_f);
+    while !mc.finished() {
+      mc.fill_next_layer(&cavity_f, progress);
+    }
+    let Some(mut cavity) = mc.get_models().remove(&1) else {
+      progress.log("nothing to hollow, walls are thicker than the model");
+      return report;
+    };
+    // raw surface of the grid has small folds
+    for _ in 0..5 {
+      cavity.smooth(0.1);
+    }
+
+    // holes should not touch feet of vertical beams
+    let radius = params.drain_radius + cell * 2.0;
+    let drain_points = cavity.drain_points(cell, |p| {
+      !near_beam(p.x - center.x, radius) || !near_beam(p.y - center.y, radius)
+    });
+    report.cavities = drain_points.len();
+    // cavities above each other share one hole, overlapping holes are not joined well
+    let r = params.drain_radius;
+    let mut drains: Vec<Point> = Vec::new();
+    for p in drain_points.into_iter().filter(|_| r > 0.0) {
+      let Some(p) = p else {
+        progress.log("no place for a drain hole between lattice beams");
+        continue;
+      };
+      let xy_dist = |d: &Point| ((d.x - p.x).powi(2) + (d.y - p.y).powi(2)).sqrt();
+      match drains.iter_mut().find(|d| xy_dist(d) < 2.0 * r) {
+        Some(d) if xy_dist(d) < r => d.z = d.z.max(p.z),
+        Some(_) => progress
+          .log(&format!("drain hole at {} {} {} is too close to another one", p.x, p.y, p.z)),
+        None => drains.push(p),
+      }
+    }
+    let mut tool = cavity;
+    for p in drains {
+      let with_drain = tool.union(&drain(p, min.z - params.wall, r));
+      // a broken union would make the whole part broken
+      if with_drain.validate().is_manifold() {
+        tool = with_drain;
+        report.drain_holes += 1;
+      } else {
+        progress.log(&format!("unable to add drain hole at {} {} {}", p.x, p.y, p.z));
+      }
+    }
+    *self = self.difference(&tool);
+    report.volume_after = self.get_volume();
+    report
+  }
+
+  // bottom points of every part connected by triangles, the allowed ones closest to the middle
+  // of vertices less than `tolerance` higher than the lowest vertex
+  fn drain_points(&self, tolerance: f32, allowed: impl Fn(Point) -> bool) -> Vec<Option<Point>> {
+    let mut parts = DisjointSets::new(self.vertices.len());
+    for t in &self.triangles {
+      parts.union(t[0] as usize, t[1] as usize);
+      parts.union(t[1] as usize, t[2] as usize);
+    }
+    let mut vertices = FxHashMap::<usize, Vec<Point>>::default();
+    let mut used = vec![false; self.vertices.len()];
+    for &v in self.triangles.iter().flatten() {
+      if !used[v as usize] {
+        used[v as usize] = true;
+        vertices.entry(parts.find(v as usize)).or_default().push(self.vertices[v as usize]);
+      }
+    }
+
+    let mut result = Vec::new();
+    for mut points in vertices.into_values() {
+      let min_z = points.iter().map(|p| p.z).fold(f32::MAX, f32::min);
+      points.retain(|p| p.z <= min_z + tolerance);
+      let mut middle = Point::ZERO;
+      for &p in &points {
+        middle += p;
+      }
+      middle = middle.scale(1.0 / points.len() as f32);
+      let xy_dist = |p: &Point| (p.x - middle.x).powi(2) + (p.y - middle.y).powi(2);
+      let p = points
+        .into_iter()
+        .filter(|&p| allowed(p))
+        .min_by(|p1, p2| xy_dist(p1).total_cmp(&xy_dist(p2)));
+      result.push(p.map(|p| Point { z: min_z, ..p }));
+    }
+    let key = |p: &Option<Point>| p.map(|p| [p.x, p.y, p.z]);
+    result.sort_by(|p1, p2| key(p1).partial_cmp(&key(p2)).unwrap());
+    result
+  }
+}
+
+// vertical cylinder from `from` height up into the cavity above `p`
+fn drain(p: Point, from: f32, radius: f32) -> Model {
+  const SEGMENTS: usize = 16;
+  let cell = radius * 2.0 * std::f32::consts::PI / SEGMENTS as f32;
+  let scale = (p.z + radius - from) / cell;
+  let mut result = Model::cylinder(SEGMENTS, 1, cell);
+  result.map_points(|q| Point { x: q.x + p.x, y: q.y + p.y, z: q.z * scale + from });
+  result
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn hollow_cuboid_with_drain() {
+    let mut test = Model::cuboid(4, 4, 4, 5.0);
+    let params =
+      HollowParams { wall: 4.0, cell_size: 2.0, drain_radius: 1.5, ..Default::default() };
+    let report = test.hollow(&params, &SilentProgress);
+    assert_eq!(report.drain_holes, 1);
+    // cavity is close to 12 * 12 * 12 cube
+    assert!(report.saved_volume() > 1500.0 && report.saved_volume() < 1900.0);
+    let validation = test.validate();
+    assert!(validation.is_manifold());
+    assert_eq!(validation.shells.len(), 1);
+  }
+}
@@ -1,11 +1,32 @@
 #![allow(unused)]
 
 pub mod bit_buffer;
+pub mod boolean;
+pub mod bvh;
+pub mod cache;
+pub mod clearance;
 pub mod common_for_twisty_puzzles;
 pub mod contour;
+pub mod decimate;
+pub mod hollow;
+pub mod mass;
+pub mod material;
 pub mod matrix;
+pub mod mesh_io;
 pub mod model;
+pub mod octree;
+pub mod part_creator;
 pub mod points2d;
 pub mod points3d;
+pub mod preview;
+pub mod progress;
+pub mod repair;
+pub mod report;
+pub mod sharp;
+pub mod slice;
 pub mod slots_and_holes;
 pub mod solid;
+#[cfg(test)]
+mod test_dir;
+pub mod validation;
+pub mod zip;
@@ -0,0 +1,329 @@
+use crate::model::*;
+use crate::points3d::*;
+use serde::Serialize;
+
+/// Box along `axes` with sizes `2 * half_sizes`
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct Obb {
+  pub center: Point,
+  pub axes: [Point; 3],
+  pub half_sizes: [f32; 3],
+}
+
+impl Obb {
+  pub fn corners(&self) -> [Point; 8] {
+    std::array::from_fn(|i| {
+      let mut p = self.center;
+      for (k, axis) in self.axes.iter().enumerate() {
+        let sign = if i >> k & 1 == 0 { -1.0 } else { 1.0 };
+        p += axis.scale(sign * self.half_sizes[k]);
+      }
+      p
+    })
+  }
+
+  pub fn volume(&self) -> f32 {
+    8.0 * self.half_sizes[0] * self.half_sizes[1] * self.half_sizes[2]
+  }
+
+  fn around(points: &[Point], axes: [Point; 3]) -> Self {
+    let mut min = [f32::MAX; 3];
+    let mut max = [f32::MIN; 3];
+    for &p in points {
+      for k in 0..3 {
+        let d = dot(p, axes[k]);
+        min[k] = min[k].min(d);
+        max[k] = max[k].max(d);
+      }
+    }
+    if points.is_empty() {
+      return Self { axes, ..Default::default() };
+    }
+    let mut center = Point::ZERO;
+    for k in 0..3 {
+      center += axes[k].scale((min[k] + max[k]) * 0.5);
+    }
+    Self { center, axes, half_sizes: std::array::from_fn(|k| (max[k] - min[k]) * 0.5) }
+  }
+}
+
+/// Integral properties of a solid with uniform density, mass is in units of density times volume
+#[derive(Debug, Clone, Copy, Default, Serialize)]
+pub struct MassProperties {
+  pub volume: f32,
+  pub mass: f32,
+  pub area: f32,
+  pub center_of_mass: Point,
+  /// inertia tensor about the center of mass
+  pub inertia: [[f32; 3]; 3],
+  /// eigenvalues of `inertia` from the smallest one
+  pub principal_moments: [f32; 3],
+  /// right handed unit axes of `principal_moments`
+  pub principal_axes: [Point; 3],
+  /// box around vertices used by triangles
+  pub aabb_min: Point,
+  pub aabb_max: Point,
+  /// box along principal axes
+  pub obb: Obb,
+}
+
+impl Model {
+  /// Volume integrals over the closed surface, `density` is mass of a unit of volume
+  pub fn mass_properties(&self, density: f32) -> MassProperties {
+    let mut used = vec![false; self.vertices.len()];
+    for &v in self.triangles.iter().flatten() {
+      used[v as usize] = true;
+    }
+    let points: Vec<Point> =
+      self.vertices.iter().zip(&used).filter(|(_, &used)| used).map(|(&p, _)| p).collect();
+    let Some(&first) = points.first() else {
+      return MassProperties::default();
+    };
+    let (aabb_min, aabb_max) = points.iter().fold((first, first), |(min, max), p| {
+      (
+        Point { x: min.x.min(p.x), y: min.y.min(p.y), z: min.z.min(p.z) },
+        Point { x: max.x.max(p.x), y: max.y.max(p.y), z: max.z.max(p.z) },
+      )
+    });
+
+    // polyhedral mass properties by D. Eberly, relative to the box center for precision
+    let origin = to_f64((aabb_min + aabb_max).scale(0.5));
+    let mut integrals = [0.0f64; 10];
+    for t in &self.triangles {
+      let [p0, p1, p2] = t.map(|v| {
+        let p = to_f64(self.vertices[v as usize]);
+        [p[0] - origin[0], p[1] - origin[1], p[2] - origin[2]]
+      });
+      let (a, b) = (sub(p1, p0), sub(p2, p0));
+      let d = [a[1] * b[2] - a[2] * b[1], a[2] * b[0] - a[0] * b[2], a[0] * b[1] - a[1] * b[0]];
+      let [x, y, z] = [0, 1, 2].map(|k| subexpressions(p0[k], p1[k], p2[k]));
+      integrals[0] += d[0] * x.f1;
+      integrals[1] += d[0] * x.f2;
+      integrals[2] += d[1] * y.f2;
+      integrals[3] += d[2] * z.f2;
+      integrals[4] += d[0] * x.f3;
+      integrals[5] += d[1] * y.f3;
+      integrals[6] += d[2] * z.f3;
+      integrals[7] += d[0] * (p0[1] * x.g[0] + p1[1] * x.g[1] + p2[1] * x.g[2]);
+      integrals[8] += d[1] * (p0[2] * y.g[0] + p1[2] * y.g[1] + p2[2] * y.g[2]);
+      integrals[9] += d[2] * (p0[0] * z.g[0] + p1[0] * z.g[1] + p2[0] * z.g[2]);
+    }
+    let factors = [6.0, 24.0, 24.0, 24.0, 60.0, 60.0, 60.0, 120.0, 120.0, 120.0];
+    let [volume, sx, sy, sz, sxx, syy, szz, sxy, syz, szx] =
+      std::array::from_fn(|i| integrals[i] / factors[i]);
+
+    // flat and open models have no volume, their center is the center of vertices
+    let c = if volume.abs() > 0.0 {
+      [sx / volume, sy / volume, sz / volume]
+    } else {
+      let mut sum = [0.0; 3];
+      for p in &points {
+        let p = to_f64(*p);
+        sum = [0, 1, 2].map(|k| sum[k] + p[k] - origin[k]);
+      }
+      sum.map(|s| s / points.len() as f64)
+    };
+    let rho = density as f64;
+    let xx = rho * (syy + szz - volume * (c[1] * c[1] + c[2] * c[2]));
+    let yy = rho * (szz + sxx - volume * (c[2] * c[2] + c[0] * c[0]));
+    let zz = rho * (sxx + syy - volume * (c[0] * c[0] + c[1] * c[1]));
+    let xy = -rho * (sxy - volume * c[0] * c[1]);
+    let yz = -rho * (syz - volume * c[1] * c[2]);
+    let zx = -rho * (szx - volume * c[2] * c[0]);
+    let inertia = [[xx, xy, zx], [xy, yy, yz], [zx, yz, zz]];
+
+    let center_of_mass = Point {
+      x: (c[0] + origin[0]) as f32,
+      y: (c[1] + origin[1]) as f32,
+      z: (c[2] + origin[2]) as f32,
+    };
+    let (principal_moments, principal_axes) = principal(inertia);
+    MassProperties {
+      volume: volume as f32,
+      mass: (volume * rho) as f32,
+      area: self.get_area(),
+      center_of_mass,
+      inertia: inertia.map(|row| row.map(|v| v as f32)),
+      principal_moments,
+      principal_axes,
+      aabb_min,
+      aabb_max,
+      obb: Obb::around(&points, principal_axes),
+    }
+  }
+}
+
+impl MassProperties {
+  /// Properties of parts together, every part keeps its own density.
+  /// The oriented box contains boxes of all parts
+  pub fn combine(parts: &[MassProperties]) -> Self {
+    let Some(first) = parts.first() else {
+      return Self::default();
+    };
+    let mass: f64 = parts.iter().map(|p| p.mass as f64).sum();
+    let volume: f64 = parts.iter().map(|p| p.volume as f64).sum();
+    // weightless parts are weighted by volume
+    let weight = |p: &MassProperties| if mass != 0.0 { p.mass as f64 } else { p.volume as f64 };
+    let total = if mass != 0.0 { mass } else { volume };
+    let mut c = [0.0f64; 3];
+    for p in parts {
+      let pc = to_f64(p.center_of_mass);
+      c = [0, 1, 2].map(|k| c[k] + pc[k] * weight(p));
+    }
+    if total != 0.0 {
+      c = c.map(|v| v / total);
+    }
+
+    // parallel axis theorem for every part
+    let mut inertia = [[0.0f64; 3]; 3];
+    for p in parts {
+      let pc = to_f64(p.center_of_mass);
+      let d = [pc[0] - c[0], pc[1] - c[1], pc[2] - c[2]];
+      let sqr_d = d[0] * d[0] + d[1] * d[1] + d[2] * d[2];
+      for i in 0..3 {
+        for j in 0..3 {
+          let shift = if i == j { sqr_d - d[i] * d[j] } else { -d[i] * d[j] };
+          inertia[i][j] += p.inertia[i][j] as f64 + p.mass as f64 * shift;
+        }
+      }
+    }
+
+    let (principal_moments, principal_axes) = principal(inertia);
+    let corners: Vec<Point> = parts.iter().flat_map(|p| p.obb.corners()).collect();
+    let mut aabb_min = first.aabb_min;
+    let mut aabb_max = first.aabb_max;
+    for p in parts {
+      aabb_min = Point {
+        x: aabb_min.x.min(p.aabb_min.x),
+        y: aabb_min.y.min(p.aabb_min.y),
+        z: aabb_min.z.min(p.aabb_min.z),
+      };
+      aabb_max = Point {
+        x: aabb_max.x.max(p.aabb_max.x),
+        y: aabb_max.y.max(p.aabb_max.y),
+        z: aabb_max.z.max(p.aabb_max.z),
+      };
+    }
+    Self {
+      volume: volume as f32,
+      mass: mass as f32,
+      area: parts.iter().map(|p| p.area).sum(),
+      center_of_mass: Point { x: c[0] as f32, y: c[1] as f32, z: c[2] as f32 },
+      inertia: inertia.map(|row| row.map(|v| v as f32)),
+      principal_moments,
+      principal_axes,
+      aabb_min,
+      aabb_max,
+      obb: Obb::around(&corners, principal_axes),
+    }
+  }
+
+  pub fn summary(&self) -> String {
+    let c = self.center_of_mass;
+    let [i1, i2, i3] = self.principal_moments;
+    format!(
+      "mass {}, volume {}, area {}, center of mass {} {} {}, principal moments {} {} {}",
+      self.mass, self.volume, self.area, c.x, c.y, c.z, i1, i2, i3
+    )
+  }
+}
+
+struct Subexpressions {
+  f1: f64,
+  f2: f64,
+  f3: f64,
+  g: [f64; 3],
+}
+
+fn subexpressions(w0: f64, w1: f64, w2: f64) -> Subexpressions {
+  let temp0 = w0 + w1;
+  let f1 = temp0 + w2;
+  let temp1 = w0 * w0;
+  let temp2 = temp1 + w1 * temp0;
+  let f2 = temp2 + w2 * f1;
+  let f3 = w0 * temp1 + w1 * temp2 + w2 * f2;
+  let g = [w0, w1, w2].map(|w| f2 + w * (f1 + w));
+  Subexpressions { f1, f2, f3, g }
+}
+
+fn to_f64(p: Point) -> [f64; 3] {
+  [p.x as f64, p.y as f64, p.z as f64]
+}
+
+fn sub(a: [f64; 3], b: [f64; 3]) -> [f64; 3] {
+  [a[0] - b[0], a[1] - b[1], a[2] - b[2]]
+}
+
+// eigenvalues from the smallest one and eigenvectors of a symmetric matrix by Jacobi rotations
+fn principal(matrix: [[f64; 3]; 3]) -> ([f32; 3], [Point; 3]) {
+  let mut a = matrix;
+  let mut v = [[1.0, 0.0, 0.0], [0.0, 1.0, 0.0], [0.0, 0.0, 1.0]];
+  for _ in 0..50 {
+    let off = a[0][1].abs() + a[0][2].abs() + a[1][2].abs();
+    let diagonal = a[0][0].abs() + a[1][1].abs() + a[2][2].abs();
+    if off <= diagonal * 1.0e-15 {
+      break;
+    }
+    for (p, q) in [(0, 1), (0, 2), (1, 2)] {
+      if a[p][q] == 0.0 {
+        continue;
+      }
+      let theta = (a[q][q] - a[p][p]) / (2.0 * a[p][q]);
+      let t = theta.signum() / (theta.abs() + (theta * theta + 1.0).sqrt());
+      let cos = (t * t + 1.0).sqrt().recip();
+      let sin = t * cos;
+      for row in &mut a {
+        let (akp, akq) = (row[p], row[q]);
+        row[p] = cos * akp - sin * akq;
+        row[q] = sin * akp + cos * akq;
+      }
+      // p < q
+      let (rows_p, rows_q) = a.split_at_mut(q);
+      for (apk, aqk) in rows_p[p].iter_mut().zip(&mut rows_q[0]) {
+        (*apk, *aqk) = (cos * *apk - sin * *aqk, sin * *apk + cos * *aqk);
+      }
+      for row in &mut v {
+        let (vp, vq) = (row[p], row[q]);
+        row[p] = cos * vp - sin * vq;
+        row[q] = sin * vp + cos * vq;
+      }
+    }
+  }
+
+  let mut order = [0, 1, 2];
+  order.sort_by(|&i, &j| a[i][i].total_cmp(&a[j][j]));
+  let moments = order.map(|i| a[i][i] as f32);
+  let mut axes = order.map(|i| Point { x: v[0][i] as f32, y: v[1][i] as f32, z: v[2][i] as f32 });
+  axes[2] = cross(axes[0], axes[1]).norm();
+  (moments, axes)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn cuboid_mass_properties() {
+    let mut test = Model::cuboid(1, 1, 1, 1.0);
+    test.map_points(|p| Point { x: p.x * 2.0, y: p.y * 3.0, z: p.z * 4.0 });
+    let props = test.mass_properties(1.0);
+    assert!((props.volume - 24.0).abs() < 1.0e-4);
+    let c = props.center_of_mass;
+    assert!((c - Point { x: 1.0, y: 1.5, z: 2.0 }).len() < 1.0e-4);
+    // m * (a * a + b * b) / 12 for every pair of sides
+    for (moment, expected) in props.principal_moments.into_iter().zip([26.0, 40.0, 50.0]) {
+      assert!((moment - expected).abs() < 1.0e-3);
+    }
+    assert!((props.principal_axes[0].z.abs() - 1.0).abs() < 1.0e-4);
+    assert!((props.obb.volume() - 24.0).abs() < 1.0e-3);
+
+    let light = Model::cuboid(1, 1, 1, 1.0);
+    let mut heavy = light.clone();
+    heavy.map_points(|p| Point { x: p.x + 2.0, ..p });
+    let both = MassProperties::combine(&[light.mass_properties(1.0), heavy.mass_properties(3.0)]);
+    assert!((both.mass - 4.0).abs() < 1.0e-4);
+    assert!((both.center_of_mass.x - 2.0).abs() < 1.0e-4);
+    // own moments of cubes and their shifts by 1.5 and 0.5 from the common center
+    assert!((both.inertia[1][1] - (4.0 / 6.0 + 1.0 * 2.25 + 3.0 * 0.25)).abs() < 1.0e-3);
+  }
+}
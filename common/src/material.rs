@@ -0,0 +1,44 @@
+/// Density is in grams per cubic millimeter, sizes are in millimeters
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Material {
+  pub name: &'static str,
+  pub density: f32,
+  pub cost_per_kg: Option<f32>,
+  /// for sheet materials, cost of a square meter of the sheet of any thickness
+  pub cost_per_m2: Option<f32>,
+}
+
+pub const STEEL: Material =
+  Material { name: "steel", density: 7.850 * 0.001, cost_per_kg: None, cost_per_m2: None };
+pub const PLA: Material =
+  Material { name: "pla", density: 1.24 * 0.001, cost_per_kg: Some(20.0), cost_per_m2: None };
+pub const PETG: Material =
+  Material { name: "petg", density: 1.27 * 0.001, cost_per_kg: Some(22.0), cost_per_m2: None };
+pub const PLYWOOD: Material =
+  Material { name: "plywood", density: 0.6 * 0.001, cost_per_kg: None, cost_per_m2: Some(15.0) };
+pub const ACRYLIC: Material =
+  Material { name: "acrylic", density: 1.18 * 0.001, cost_per_kg: None, cost_per_m2: Some(40.0) };
+
+pub const MATERIALS: &[Material] = &[STEEL, PLA, PETG, PLYWOOD, ACRYLIC];
+
+/// Used for parts without assigned material
+pub const DEFAULT_MATERIAL: Material = STEEL;
+
+pub fn find_material(name: &str) -> Option<Material> {
+  MATERIALS.iter().find(|m| m.name == name).copied()
+}
+
+impl Material {
+  /// mass in grams
+  pub fn mass(&self, volume: f32) -> f32 {
+    volume * self.density
+  }
+
+  /// `sheet_area` is used only for flat parts cut from sheets
+  pub fn cost(&self, mass: f32, sheet_area: Option<f32>) -> Option<f32> {
+    match (self.cost_per_m2, sheet_area) {
+      (Some(per_m2), Some(area)) => Some(area * 0.000001 * per_m2),
+      _ => self.cost_per_kg.map(|per_kg| mass * 0.001 * per_kg),
+    }
+  }
+}
@@ -55,6 +55,16 @@ impl Matrix {
   pub fn as_ptr(&self) -> *const f32 {
     self.data[0].as_ptr()
   }
+
+  /// Transforms `(p, 1)` the same way as shaders do, returns homogeneous coordinates
+  pub fn apply(&self, p: Point) -> [f32; 4] {
+    let mut result = [0.0; 4];
+    for i in 0..4 {
+      result[i] =
+        self.data[0][i] * p.x + self.data[1][i] * p.y + self.data[2][i] * p.z + self.data[3][i];
+    }
+    result
+  }
 }
 
 pub fn mat_mul(left: Matrix, right: Matrix) -> Matrix {
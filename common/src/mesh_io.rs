@@ -0,0 +1,452 @@
+use crate::model::*;
+use crate::points3d::*;
+use std::fmt::Write;
+use std::path::Path;
+
+/// File formats of `Model`, all except binary stl keep shared vertices
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MeshFormat {
+  Stl,
+  AsciiStl,
+  Obj,
+  Ply,
+}
+
+impl MeshFormat {
+  pub const ALL: [MeshFormat; 4] = [Self::Stl, Self::AsciiStl, Self::Obj, Self::Ply];
+
+  /// Ascii stl files are named like `*.ascii.stl`
+  pub fn extension(&self) -> &'static str {
+    match self {
+      Self::Stl => "stl",
+      Self::AsciiStl => "ascii.stl",
+      Self::Obj => "obj",
+      Self::Ply => "ply",
+    }
+  }
+
+  pub fn from_extension(extension: &str) -> Option<Self> {
+    Self::ALL.into_iter().find(|f| f.extension().eq_ignore_ascii_case(extension))
+  }
+
+  pub fn from_path(path: &Path) -> Option<Self> {
+    let name = path.file_name()?.to_str()?.to_ascii_lowercase();
+    // the longest matching extension wins, so `x.ascii.stl` is not a binary stl
+    Self::ALL
+      .into_iter()
+      .filter(|f| name.ends_with(&format!(".{}", f.extension())))
+      .max_by_key(|f| f.extension().len())
+  }
+}
+
+fn read_file(path: &Path) -> Result<String, String> {
+  std::fs::read_to_string(path)
+    .map_err(|e| format!("Unable to open file {} for reading: {}", path.to_string_lossy(), e))
+}
+
+fn write_file(path: &Path, data: &[u8]) -> Result<(), String> {
+  std::fs::write(path, data)
+    .map_err(|e| format!("Unable to open file {} for writing: {}", path.to_string_lossy(), e))
+}
+
+fn color_components(color: u32) -> [u8; 3] {
+  [(color >> 16) as u8, (color >> 8) as u8, color as u8]
+}
+
+impl Model {
+  /// Saves in format chosen by extension of `path`,
+  /// `color` (0xRRGGBB) is written as vertex colors to obj and ply files
+  pub fn save_to_file(&self, path: &Path, color: Option<u32>) -> Result<(), String> {
+    match MeshFormat::from_path(path) {
+      Some(MeshFormat::Stl) => self.save_to_stl(path),
+      Some(MeshFormat::AsciiStl) => self.save_to_ascii_stl(path),
+      Some(MeshFormat::Obj) => self.save_to_obj(path, color),
+      Some(MeshFormat::Ply) => self.save_to_ply(path, color),
+      None => Err(format!("Unknown mesh format of file {}", path.to_string_lossy())),
+    }
+  }
+
+  /// Loads file in format chosen by extension of `path`, colors are ignored
+  pub fn load_from_file(path: &Path) -> Result<Self, String> {
+    match MeshFormat::from_path(path) {
+      Some(MeshFormat::Stl | MeshFormat::AsciiStl) => Self::load_from_stl(path),
+      Some(MeshFormat::Obj) => Self::load_from_obj(path),
+      Some(MeshFormat::Ply) => Self::load_from_ply(path),
+      None => Err(format!("Unknown mesh format of file {}", path.to_string_lossy())),
+    }
+  }
+
+  pub fn save_to_ascii_stl(&self, path: &Path) -> Result<(), String> {
+    let mut stl = String::from("solid model\n");
+    for t in &self.triangles {
+      let [v0, v1, v2] = t.map(|i| self.vertices[i as usize]);
+      let n = cross(v1 - v0, v2 - v0).norm();
+      writeln!(stl, "  facet normal {} {} {}", n.x, n.y, n.z).unwrap();
+      stl += "    outer loop\n";
+      for v in [v0, v1, v2] {
+        writeln!(stl, "      vertex {} {} {}", v.x, v.y, v.z).unwrap();
+      }
+      stl += "    endloop\n  endfacet\n";
+    }
+    stl += "endsolid model\n";
+    write_file(path, stl.as_bytes())
+  }
+
+  pub fn save_to_obj(&self, path: &Path, color: Option<u32>) -> Result<(), String> {
+    let mut obj = String::new();
+    let rgb = color.map(|c| color_components(c).map(|c| c as f32 / 255.0));
+    for v in &self.vertices {
+      match rgb {
+        Some([r, g, b]) => writeln!(obj, "v {} {} {} {r} {g} {b}", v.x, v.y, v.z),
+        None => writeln!(obj, "v {} {} {}", v.x, v.y, v.z),
+      }
+      .unwrap();
+    }
+    for t in &self.triangles {
+      writeln!(obj, "f {} {} {}", t[0] + 1, t[1] + 1, t[2] + 1).unwrap();
+    }
+    write_file(path, obj.as_bytes())
+  }
+
+  /// Polygons are split to triangle fans, texture coordinates and normals are ignored
+  pub fn load_from_obj(path: &Path) -> Result<Self, String> {
+    let text = read_file(path)?;
+    let error = |line: usize, msg: &str| {
+      format!("Failed to load obj from file {}, line {}: {}", path.to_string_lossy(), line, msg)
+    };
+
+    let mut result = Self::new();
+    for (n, line) in text.lines().enumerate() {
+      let mut tokens = line.split_whitespace();
+      match tokens.next() {
+        Some("v") => {
+          let mut c = tokens.take(3).map(|t| t.parse::<f32>());
+          let mut next = || c.next().and_then(Result::ok).ok_or_else(|| error(n + 1, "bad vertex"));
+          result.vertices.push(Point { x: next()?, y: next()?, z: next()? });
+        }
+        Some("f") => {
+          let indices = tokens
+            .map(|t| {
+              // `v/vt/vn`, negative indices count from the last vertex
+              let i: i64 = t.split('/').next().unwrap().parse().map_err(|_| error(n + 1, t))?;
+              let count = result.vertices.len() as i64;
+              let i = if i < 0 { count + i } else { i - 1 };
+              match i >= 0 && i < count {
+                true => Ok(i as u32),
+                false => Err(error(n + 1, "vertex index is out of range")),
+              }
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+          if indices.len() < 3 {
+            return Err(error(n + 1, "face has less than 3 vertices"));
+          }
+          for i in 1..indices.len() - 1 {
+            result.triangles.push([indices[0], indices[i], indices[i + 1]]);
+          }
+        }
+        _ => {}
+      }
+    }
+    Ok(result)
+  }
+
+  /// Ascii ply with optional vertex colors
+  pub fn save_to_ply(&self, path: &Path, color: Option<u32>) -> Result<(), String> {
+    let mut ply = String::from("ply\nformat ascii 1.0\n");
+    writeln!(ply, "element vertex {}", self.vertices.len()).unwrap();
+    ply += "property float x\nproperty float y\nproperty float z\n";
+    if color.is_some() {
+      ply += "property uchar red\nproperty uchar green\nproperty uchar blue\n";
+    }
+    writeln!(ply, "element face {}", self.triangles.len()).unwrap();
+    ply += "property list uchar int vertex_indices\nend_header\n";
+
+    let rgb = color.map(color_components);
+    for v in &self.vertices {
+      match rgb {
+        Some([r, g, b]) => writeln!(ply, "{} {} {} {r} {g} {b}", v.x, v.y, v.z),
+        None => writeln!(ply, "{} {} {}", v.x, v.y, v.z),
+      }
+      .unwrap();
+    }
+    for t in &self.triangles {
+      writeln!(ply, "3 {} {} {}", t[0], t[1], t[2]).unwrap();
+    }
+    write_file(path, ply.as_bytes())
+  }
+
+  /// Reads ascii and binary little endian ply, polygons are split to triangle fans
+  pub fn load_from_ply(path: &Path) -> Result<Self, String> {
+    let data = std::fs::read(path)
+      .map_err(|e| format!("Unable to open file {} for reading: {}", path.to_string_lossy(), e))?;
+    PlyReader::read(&data)
+      .map_err(|e| format!("Failed to load ply from file {}: {}", path.to_string_lossy(), e))
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PlyType {
+  I8,
+  U8,
+  I16,
+  U16,
+  I32,
+  U32,
+  F32,
+  F64,
+}
+
+impl PlyType {
+  fn parse(name: &str) -> Result<Self, String> {
+    Ok(match name {
+      "char" | "int8" => Self::I8,
+      "uchar" | "uint8" => Self::U8,
+      "short" | "int16" => Self::I16,
+      "ushort" | "uint16" => Self::U16,
+      "int" | "int32" => Self::I32,
+      "uint" | "uint32" => Self::U32,
+      "float" | "float32" => Self::F32,
+      "double" | "float64" => Self::F64,
+      _ => return Err(format!("unknown property type {name}")),
+    })
+  }
+
+  fn size(&self) -> usize {
+    match self {
+      Self::I8 | Self::U8 => 1,
+      Self::I16 | Self::U16 => 2,
+      Self::I32 | Self::U32 | Self::F32 => 4,
+      Self::F64 => 8,
+    }
+  }
+}
+
+struct PlyProperty {
+  name: String,
+  // count type for lists
+  list: Option<PlyType>,
+  value: PlyType,
+}
+
+struct PlyElement {
+  name: String,
+  count: usize,
+  properties: Vec<PlyProperty>,
+}
+
+struct PlyReader<'a> {
+  data: &'a [u8],
+  pos: usize,
+  binary: bool,
+}
+
+impl<'a> PlyReader<'a> {
+  fn read(data: &'a [u8]) -> Result<Model, String> {
+    const END: &[u8] = b"end_header";
+    let end = data.windows(END.len()).position(|w| w == END).ok_or("no end_header")?;
+    let body = data[end..].iter().position(|&c| c == b'\n').ok_or("no data")? + end + 1;
+    let header = std::str::from_utf8(&data[..end]).map_err(|e| e.to_string())?;
+
+    let mut binary = None;
+    let mut elements: Vec<PlyElement> = Vec::new();
+    for line in header.lines() {
+      let tokens: Vec<_> = line.split_whitespace().collect();
+      match tokens.as_slice() {
+        ["format", "ascii", _] => binary = Some(false),
+        ["format", "binary_little_endian", _] => binary = Some(true),
+        ["format", format, _] => return Err(format!("unsupported format {format}")),
+        ["element", name, count] => elements.push(PlyElement {
+          name: name.to_string(),
+          count: count.parse().map_err(|_| format!("bad count of {name}"))?,
+          properties: Vec::new(),
+        }),
+        ["property", "list", count, value, name] => {
+          let element = elements.last_mut().ok_or("property before element")?;
+          let list = Some(PlyType::parse(count)?);
+          let value = PlyType::parse(value)?;
+          element.properties.push(PlyProperty { name: name.to_string(), list, value });
+        }
+        ["property", value, name] => {
+          let element = elements.last_mut().ok_or("property before element")?;
+          let value = PlyType::parse(value)?;
+          element.properties.push(PlyProperty { name: name.to_string(), list: None, value });
+        }
+        _ => {}
+      }
+    }
+
+    let binary = binary.ok_or("no format")?;
+    let mut reader = PlyReader { data, pos: body, binary };
+    let mut result = Model::new();
+    for element in &elements {
+      let x = element.properties.iter().position(|p| p.name == "x");
+      let y = element.properties.iter().position(|p| p.name == "y");
+      let z = element.properties.iter().position(|p| p.name == "z");
+      let indices = element.properties.iter().position(|p| {
+        p.list.is_some() && matches!(p.name.as_str(), "vertex_indices" | "vertex_index")
+      });
+
+      let mut values = Vec::new();
+      for _ in 0..element.count {
+        let mut list = Vec::new();
+        values.clear();
+        for (i, p) in element.properties.iter().enumerate() {
+          match p.list {
+            Some(count_type) => {
+              let count = reader.value(count_type)? as usize;
+              let items =
+                (0..count).map(|_| reader.value(p.value)).collect::<Result<Vec<_>, _>>()?;
+              if Some(i) == indices {
+                list = items;
+              }
+              values.push(0.0);
+            }
+            None => values.push(reader.value(p.value)?),
+          }
+        }
+
+        match (element.name.as_str(), x, y, z, indices) {
+          ("vertex", Some(x), Some(y), Some(z), _) => {
+            let (x, y, z) = (values[x] as f32, values[y] as f32, values[z] as f32);
+            result.vertices.push(Point { x, y, z });
+          }
+          ("face", _, _, _, Some(_)) => {
+            if list.len() < 3 {
+              return Err("face has less than 3 vertices".to_string());
+            }
+            for i in 1..list.len() - 1 {
+              result.triangles.push([list[0] as u32, list[i] as u32, list[i + 1] as u32]);
+            }
+          }
+          _ => {}
+        }
+      }
+    }
+
+    let count = result.vertices.len() as u32;
+    if result.triangles.iter().flatten().any(|&v| v >= count) {
+      return Err("vertex index is out of range".to_string());
+    }
+    Ok(result)
+  }
+
+  fn value(&mut self, t: PlyType) -> Result<f64, String> {
+    if !self.binary {
+      let rest = &self.data[self.pos..];
+      let start = rest.iter().position(|c| !c.is_ascii_whitespace()).ok_or("data is truncated")?;
+      let len =
+        rest[start..].iter().position(|c| c.is_ascii_whitespace()).unwrap_or(rest.len() - start);
+      self.pos += start + len;
+      let token = std::str::from_utf8(&rest[start..start + len]).map_err(|e| e.to_string())?;
+      return token.parse().map_err(|_| format!("bad value {token}"));
+    }
+
+    let bytes = self.data.get(self.pos..self.pos + t.size()).ok_or("data is truncated")?;
+    self.pos += t.size();
+    Ok(match t {
+      PlyType::I8 => bytes[0] as i8 as f64,
+      PlyType::U8 => bytes[0] as f64,
+      PlyType::I16 => i16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+      PlyType::U16 => u16::from_le_bytes([bytes[0], bytes[1]]) as f64,
+      PlyType::I32 => i32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+      PlyType::U32 => u32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+      PlyType::F32 => f32::from_le_bytes(bytes.try_into().unwrap()) as f64,
+      PlyType::F64 => f64::from_le_bytes(bytes.try_into().unwrap()),
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_dir::TestDir;
+
+  fn test_model() -> Model {
+    let mut m = Model::cuboid(2, 2, 2, 0.1);
+    m.map_points(|p| Point { x: p.x + 0.3, y: p.y - 1.7, z: p.z * 3.0 });
+    m
+  }
+
+  fn coords(points: &[Point]) -> Vec<[f32; 3]> {
+    points.iter().map(|p| [p.x, p.y, p.z]).collect()
+  }
+
+  fn triangle_points(m: &Model) -> Vec<[[f32; 3]; 3]> {
+    let p = |i: u32| m.vertices[i as usize];
+    m.triangles.iter().map(|t| t.map(|i| [p(i).x, p(i).y, p(i).z])).collect()
+  }
+
+  fn round_trip(name: &str, color: Option<u32>) -> (Model, Model) {
+    let dir = TestDir::new(&format!("mesh_io_{name}"));
+    let path = dir.join(name);
+    let m = test_model();
+    m.save_to_file(&path, color).unwrap();
+    let loaded = Model::load_from_file(&path);
+    (m, loaded.unwrap())
+  }
+
+  #[test]
+  fn obj_round_trip() {
+    for color in [None, Some(0x2080FF)] {
+      let (m, loaded) = round_trip("model.obj", color);
+      assert_eq!(coords(&loaded.vertices), coords(&m.vertices));
+      assert_eq!(loaded.triangles, m.triangles);
+    }
+  }
+
+  #[test]
+  fn ascii_ply_round_trip() {
+    for color in [None, Some(0x2080FF)] {
+      let (m, loaded) = round_trip("model.ply", color);
+      assert_eq!(coords(&loaded.vertices), coords(&m.vertices));
+      assert_eq!(loaded.triangles, m.triangles);
+    }
+  }
+
+  #[test]
+  fn ascii_stl_round_trip() {
+    let (m, loaded) = round_trip("model.ascii.stl", None);
+    // stl has no shared vertices, so they are merged again in another order
+    assert_eq!(loaded.vertices.len(), m.vertices.len());
+    assert_eq!(triangle_points(&loaded), triangle_points(&m));
+  }
+
+  #[test]
+  fn binary_ply() {
+    let m = test_model();
+    let mut data = format!(
+      "ply\nformat binary_little_endian 1.0\ncomment written by hand\n\
+       element vertex {}\nproperty float x\nproperty float y\nproperty float z\n\
+       property uchar red\nproperty uchar green\nproperty uchar blue\n\
+       element face {}\nproperty list uchar int vertex_indices\nend_header\n",
+      m.vertices.len(),
+      m.triangles.len() + 1,
+    )
+    .into_bytes();
+    for v in &m.vertices {
+      for c in [v.x, v.y, v.z] {
+        data.extend_from_slice(&c.to_le_bytes());
+      }
+      data.extend_from_slice(&[1, 2, 3]);
+    }
+    for t in &m.triangles {
+      data.push(3);
+      for i in t {
+        data.extend_from_slice(&(*i as i32).to_le_bytes());
+      }
+    }
+    // a quad is split to a fan of two triangles
+    data.push(4);
+    for i in [0i32, 1, 2, 3] {
+      data.extend_from_slice(&i.to_le_bytes());
+    }
+
+    let loaded = PlyReader::read(&data).unwrap();
+    assert_eq!(coords(&loaded.vertices), coords(&m.vertices));
+    assert_eq!(loaded.triangles[..m.triangles.len()], m.triangles);
+    assert_eq!(loaded.triangles[m.triangles.len()..], [[0, 1, 2], [0, 2, 3]]);
+
+    data.truncate(data.len() - 1);
+    assert_eq!(PlyReader::read(&data).err().unwrap(), "data is truncated");
+  }
+}
@@ -3,6 +3,8 @@ use rand::seq::SliceRandom;
 
 use crate::contour::*;
 use crate::points3d::*;
+use crate::progress::*;
+use crate::solid::PartIndex;
 use fxhash::{FxHashMap, FxHashSet};
 
 // use u32 because of memory optimization
@@ -124,6 +126,11 @@ impl Model {
   }
 
   pub fn smooth(&mut self, delta: f32) {
+    self.smooth_keeping(delta, &[]);
+  }
+
+  /// `smooth` which does not move `fixed` vertices, like the ones `sharpen` put on edges
+  pub fn smooth_keeping(&mut self, delta: f32, fixed: &[bool]) {
     let mut positions = vec![(Point::ZERO, 0); self.vertices.len()];
     for t in &self.triangles {
       let t0 = t[0] as usize;
@@ -141,6 +148,9 @@ impl Model {
     }
 
     for i in 0..self.vertices.len() {
+      if fixed.get(i) == Some(&true) {
+        continue;
+      }
       let dp = positions[i].0.scale((positions[i].1 as f32).recip()) - self.vertices[i];
       self.vertices[i] += dp.scale(delta);
     }
@@ -212,6 +222,7 @@ impl Model {
     top: &MeshTopology,
     max_tol: f32,
     min_group_size: f32,
+    progress: &dyn Progress,
   ) -> NormalGroups {
     struct GroupInfo {
       extremal: [(f32, f32); REPER_VECTORS.len()],
@@ -273,10 +284,7 @@ impl Model {
 
     let start = std::time::Instant::now();
     loop {
-      println!(
-        "get_normal_groups with tol {tol}, {} grouped...",
-        cnt_grouped as f32 * 100.0 / self.triangles.len() as f32
-      );
+      progress.progress("grouped by normals", cnt_grouped, self.triangles.len(), start.elapsed());
       let next_tol = tol + f32::min(tol * 9.0, max_step);
       let last_iter = next_tol > max_tol;
       let mut complete = true;
@@ -346,13 +354,13 @@ impl Model {
       }
     }
 
-    println!("generating normals elapsed {:?}", std::time::Instant::now() - start);
-    println!(
+    progress.log(&format!("generating normals elapsed {:?}", start.elapsed()));
+    progress.log(&format!(
       "{} grouped in {} groups, {} ungrouped",
       cnt_grouped as f32 * 100.0 / self.triangles.len() as f32,
       normal_of_g.len(),
       self.triangles.len() - cnt_grouped
-    );
+    ));
 
     NormalGroups { group_of_t, normal_of_g }
   }
@@ -580,11 +588,11 @@ impl Model {
     true
   }
 
-  pub fn optimize(&mut self, max_tol: f32, min_group_size: f32) {
-    println!("get topology...");
+  pub fn optimize(&mut self, max_tol: f32, min_group_size: f32, progress: &dyn Progress) {
+    progress.log("get topology...");
     let top = self.get_topology();
-    println!("get normal groups...");
-    let mut ng = self.get_normal_groups(&top, max_tol, min_group_size);
+    progress.log("get normal groups...");
+    let mut ng = self.get_normal_groups(&top, max_tol, min_group_size, progress);
 
     let buf = ChangeTriangleBuffer::default();
     let mut ctx = self.create_merge_context(top, ng, buf);
@@ -598,7 +606,7 @@ impl Model {
 
     let start = std::time::Instant::now();
     loop {
-      println!("start loop iter with {ttc} valid triangles");
+      progress.progress("merged triangles", ti.len() - ttc, ti.len(), start.elapsed());
       let mut merged = false;
       ti.shuffle(&mut rng);
       'enumerate_triangles: for &t in &ti {
@@ -621,10 +629,7 @@ impl Model {
         break;
       }
     }
-    println!(
-      "end loop with {ttc} valid triangles and {:?} time",
-      std::time::Instant::now() - start
-    );
+    progress.log(&format!("end loop with {ttc} valid triangles and {:?} time", start.elapsed()));
 
     let mut j = 0;
     for i in 0..self.triangles.len() {
@@ -633,7 +638,7 @@ impl Model {
         j += 1;
       }
     }
-    println!("check: {j} valid triangles");
+    progress.log(&format!("check: {j} valid triangles"));
 
     self.triangles.truncate(j);
   }
@@ -761,9 +766,14 @@ impl Model {
     mappings.into_iter().map(|(_, m)| m.m).collect()
   }
 
-  pub fn split_by_normal(mut self, max_tol: f32, min_group_size: f32) -> Vec<Model> {
+  pub fn split_by_normal(
+    mut self,
+    max_tol: f32,
+    min_group_size: f32,
+    progress: &dyn Progress,
+  ) -> Vec<Model> {
     let top = self.get_topology();
-    let mut ng = self.get_normal_groups(&top, max_tol, min_group_size);
+    let mut ng = self.get_normal_groups(&top, max_tol, min_group_size, progress);
     self.split_by(&|i| ng.group_of_t[i as usize].wrapping_add(2))
   }
 
@@ -903,6 +913,31 @@ impl Model {
     result / 6.0
   }
 
+  pub fn get_area(&self) -> f32 {
+    let mut result = 0.0;
+    for t in &self.triangles {
+      let v0 = self.vertices[t[0] as usize];
+      let v1 = self.vertices[t[1] as usize];
+      let v2 = self.vertices[t[2] as usize];
+      result += cross(v1 - v0, v2 - v0).len();
+    }
+
+    result * 0.5
+  }
+
+  /// min and max corners of bounding box, zeros for empty model
+  pub fn get_aabb(&self) -> (Point, Point) {
+    let Some(&first) = self.vertices.first() else {
+      return (Point::ZERO, Point::ZERO);
+    };
+    self.vertices.iter().fold((first, first), |(min, max), p| {
+      (
+        Point { x: min.x.min(p.x), y: min.y.min(p.y), z: min.z.min(p.z) },
+        Point { x: max.x.max(p.x), y: max.y.max(p.y), z: max.z.max(p.z) },
+      )
+    })
+  }
+
   pub fn convex_triangles(vertices: &[Point], eps: f32) -> Option<Vec<Triangle>> {
     let mut i1 = 1;
     let mut i2 = 2;
@@ -1414,9 +1449,120 @@ impl Model {
   }
 }
 
+/// How a model is placed to 3mf package
+pub struct ObjectInfo {
+  pub name: String,
+  /// 0xRRGGBB, higher bits are ignored
+  pub color: u32,
+  /// number of build items, the first one is in place of the model,
+  /// others are placed along x axis after all models
+  pub count: usize,
+}
+
+// distance between copies of the same object
+const BUILD_GAP: f32 = 5.0;
+
+/// Saves all models to one 3mf package as named objects with colored base materials
+pub fn save_models_to_3mf(
+  models: &FxHashMap<PartIndex, Model>,
+  info: impl Fn(PartIndex) -> ObjectInfo,
+  path: &std::path::Path,
+) -> Result<(), String> {
+  use std::fmt::Write;
+
+  let mut indices: Vec<_> = models.keys().copied().collect();
+  indices.sort();
+  let infos: Vec<_> = indices.iter().map(|&m_index| info(m_index)).collect();
+
+  let rgb = |object: &ObjectInfo| object.color & 0xFFFFFF;
+  let mut colors: Vec<u32> = infos.iter().map(rgb).collect();
+  colors.sort();
+  colors.dedup();
+
+  let mut xml = String::from(
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+     <model unit=\"millimeter\" xml:lang=\"en-US\" \
+     xmlns=\"http://schemas.microsoft.com/3dmanufacturing/core/2015/02\">\n\
+     <resources>\n<basematerials id=\"1\">\n",
+  );
+  for c in &colors {
+    writeln!(xml, "<base name=\"#{c:06X}\" displaycolor=\"#{c:06X}FF\"/>").unwrap();
+  }
+  xml += "</basematerials>\n";
+
+  for (i, (m_index, object)) in indices.iter().zip(&infos).enumerate() {
+    let m = &models[m_index];
+    let pindex = colors.binary_search(&rgb(object)).unwrap();
+    writeln!(
+      xml,
+      "<object id=\"{}\" name=\"{}\" type=\"model\" pid=\"1\" pindex=\"{pindex}\">\n\
+       <mesh>\n<vertices>",
+      i + 2,
+      xml_escape(&object.name)
+    )
+    .unwrap();
+    for v in &m.vertices {
+      writeln!(xml, "<vertex x=\"{}\" y=\"{}\" z=\"{}\"/>", v.x, v.y, v.z).unwrap();
+    }
+    xml += "</vertices>\n<triangles>\n";
+    for t in &m.triangles {
+      writeln!(xml, "<triangle v1=\"{}\" v2=\"{}\" v3=\"{}\"/>", t[0], t[1], t[2]).unwrap();
+    }
+    xml += "</triangles>\n</mesh>\n</object>\n";
+  }
+  xml += "</resources>\n<build>\n";
+
+  let aabbs: Vec<_> = indices.iter().map(|m_index| models[m_index].get_aabb()).collect();
+  // copies do not overlap any model
+  let mut next_x = aabbs.iter().fold(f32::MIN, |x, (_, max)| x.max(max.x)) + BUILD_GAP;
+  for (i, ((min, max), object)) in aabbs.iter().zip(&infos).enumerate() {
+    for k in 0..object.count {
+      let mut shift = 0.0;
+      if k > 0 {
+        shift = next_x - min.x;
+        next_x += max.x - min.x + BUILD_GAP;
+      }
+      writeln!(xml, "<item objectid=\"{}\" transform=\"1 0 0 0 1 0 0 0 1 {shift} 0 0\"/>", i + 2)
+        .unwrap();
+    }
+  }
+  xml += "</build>\n</model>\n";
+
+  let files: [(&str, &[u8]); 3] = [
+    (
+      "[Content_Types].xml",
+      b"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+        <Types xmlns=\"http://schemas.openxmlformats.org/package/2006/content-types\">\n\
+        <Default Extension=\"rels\" \
+        ContentType=\"application/vnd.openxmlformats-package.relationships+xml\"/>\n\
+        <Default Extension=\"model\" \
+        ContentType=\"application/vnd.ms-package.3dmanufacturing-3dmodel+xml\"/>\n\
+        </Types>\n",
+    ),
+    (
+      "_rels/.rels",
+      b"<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n\
+        <Relationships xmlns=\"http://schemas.openxmlformats.org/package/2006/relationships\">\n\
+        <Relationship Target=\"/3D/3dmodel.model\" Id=\"rel0\" \
+        Type=\"http://schemas.microsoft.com/3dmanufacturing/2013/01/3dmodel\"/>\n\
+        </Relationships>\n",
+    ),
+    ("3D/3dmodel.model", xml.as_bytes()),
+  ];
+  let zip = crate::zip::write_zip(&files)
+    .map_err(|e| format!("Unable to compress 3mf package {}: {}", path.to_string_lossy(), e))?;
+  std::fs::write(path, zip)
+    .map_err(|e| format!("Unable to write 3mf to file {}: {}", path.to_string_lossy(), e))
+}
+
+fn xml_escape(s: &str) -> String {
+  s.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
+  use crate::test_dir::TestDir;
 
   #[test]
   fn convex_floating_pituh() {
@@ -1435,4 +1581,49 @@ mod tests {
     let mut test = Model::convex(&v2c, 0.0).unwrap();
     test.validate_and_delete_small_groups();
   }
+
+  #[test]
+  fn save_models_to_3mf_package() {
+    let models: FxHashMap<PartIndex, Model> =
+      [1, 3, 7].into_iter().map(|m_index| (m_index, Model::cuboid(2, 2, 2, 1.0))).collect();
+    let info = |m_index: PartIndex| ObjectInfo {
+      name: format!("part <{m_index}>"),
+      // higher bits are not a part of the color
+      color: if m_index == 7 { 0xFF0000 } else { 0x7F0080FF },
+      count: [2, 1, 3][m_index as usize / 3],
+    };
+    let dir = TestDir::new("models_3mf");
+    let path = dir.join("models.3mf");
+    save_models_to_3mf(&models, info, &path).unwrap();
+
+    let files = crate::zip::tests::read_zip(&std::fs::read(&path).unwrap());
+    let names: Vec<_> = files.iter().map(|(name, _)| name.as_str()).collect();
+    assert_eq!(names, ["[Content_Types].xml", "_rels/.rels", "3D/3dmodel.model"]);
+    let xml = String::from_utf8(files[2].1.clone()).unwrap();
+    let elements = |tag: &str| -> Vec<&str> {
+      xml.lines().filter(|line| line.starts_with(&format!("<{tag} "))).collect()
+    };
+    let attr = |element: &str, name: &str| -> String {
+      let value = element.split(&format!(" {name}=\"")).nth(1).unwrap();
+      value[..value.find('"').unwrap()].to_string()
+    };
+
+    let bases: Vec<_> = elements("base").iter().map(|e| attr(e, "displaycolor")).collect();
+    assert_eq!(bases, ["#0080FFFF", "#FF0000FF"]);
+    let objects: Vec<_> = elements("object")
+      .iter()
+      .map(|e| [attr(e, "id"), attr(e, "name"), attr(e, "pid"), attr(e, "pindex")])
+      .collect();
+    assert_eq!(
+      objects,
+      [
+        ["2", "part &lt;1&gt;", "1", "0"],
+        ["3", "part &lt;3&gt;", "1", "0"],
+        ["4", "part &lt;7&gt;", "1", "1"],
+      ]
+    );
+    assert_eq!(elements("triangle").len(), 3 * models[&1].triangles.len());
+    let items: Vec<_> = elements("item").iter().map(|e| attr(e, "objectid")).collect();
+    assert_eq!(items, ["2", "2", "3", "4", "4", "4"]);
+  }
 }
@@ -0,0 +1,595 @@
+use crate::model::*;
+use crate::points3d::*;
+use crate::progress::*;
+use crate::solid::*;
+use fxhash::{FxHashMap, FxHashSet};
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct AdaptiveParams {
+  /// the first uniform grid has `2^min_depth` cells along every side of the meshed cube
+  pub min_depth: usize,
+  /// cells at borders of parts are split down to this depth
+  pub max_depth: usize,
+  /// borders where normals turn less than this angle in degrees keep bigger cells,
+  /// it needs gradients of parts, 0 splits all borders down to `max_depth`
+  pub max_angle: f32,
+}
+
+impl Default for AdaptiveParams {
+  fn default() -> Self {
+    Self { min_depth: 4, max_depth: 7, max_angle: 0.0 }
+  }
+}
+
+impl AdaptiveParams {
+  /// Smallest cells are not bigger than cells of `ModelCreator` with `quality` points along a side,
+  /// the first grid is `levels` times coarser
+  pub fn for_quality(quality: usize, levels: usize, max_angle: f32) -> Self {
+    let cells = (quality as f32 - 1.5).max(1.0);
+    let max_depth = (cells.log2().ceil() as usize).min(MAX_DEPTH);
+    Self { min_depth: max_depth.saturating_sub(levels), max_depth, max_angle }
+  }
+}
+
+// deeper cells don't fit into `Key`
+const MAX_DEPTH: usize = 16;
+const NO_EDGE: u32 = u32::MAX;
+
+// parts at a batch of points
+type EvalPoints<'a> = dyn Fn(&[Point]) -> Vec<PartIndex> + 'a;
+// borders on a batch of edges from a point inside a part to a point outside of it
+type FindRoots<'a> = dyn Fn(&[(Point, Point, PartIndex)]) -> Vec<Point> + 'a;
+
+// point of the lattice with steps of a half of the smallest cell, so centers of cells are on it
+type Key = [u32; 3];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct Node {
+  depth: usize,
+  origin: Key,
+}
+
+// border of a part crosses the edge from inside to outside point
+type EdgeKey = (Key, Key, PartIndex);
+
+/// Meshes parts on an octree whose cells are split only near borders of parts,
+/// so thin details get small cells while the bulk of parts and empty space stay coarse.
+/// Cells of different sizes share all lattice points on their common faces, so meshes have no
+/// cracks between levels.
+pub struct OctreeCreator {
+  params: AdaptiveParams,
+  solid_size: f32,
+  tries: usize,
+  skipped: FxHashSet<PartIndex>,
+  // `None` meshes all parts
+  selected: Option<FxHashSet<PartIndex>>,
+  samples: FxHashMap<Key, PartIndex>,
+  leaves: Vec<Node>,
+  split: FxHashSet<Node>,
+  // sorted corners of leaves on lines along an axis, keyed by the axis and other coordinates
+  lines: FxHashMap<(usize, [u32; 2]), Vec<u32>>,
+}
+
+impl OctreeCreator {
+  pub fn new(params: AdaptiveParams, solid_size: f32, tries: usize) -> Self {
+    let max_depth = params.max_depth.min(MAX_DEPTH);
+    let params = AdaptiveParams { min_depth: params.min_depth.min(max_depth), max_depth, ..params };
+    Self {
+      params,
+      solid_size,
+      tries,
+      skipped: FxHashSet::default(),
+      selected: None,
+      samples: FxHashMap::default(),
+      leaves: Vec::new(),
+      split: FxHashSet::default(),
+      lines: FxHashMap::default(),
+    }
+  }
+
+  /// Models which are not meshed, e.g. because they are taken from cache
+  pub fn skip_models(&mut self, models: impl IntoIterator<Item = PartIndex>) {
+    self.skipped.extend(models);
+  }
+
+  /// Only these models are meshed, cells are split only at borders of them
+  pub fn select_models(&mut self, models: impl IntoIterator<Item = PartIndex>) {
+    self.selected.get_or_insert_with(FxHashSet::default).extend(models);
+  }
+
+  // skipped parts are still refined, so other parts get the same cells as without skipping
+  fn is_selected(&self, m_index: PartIndex) -> bool {
+    m_index != 0 && self.selected.as_ref().is_none_or(|s| s.contains(&m_index))
+  }
+
+  /// Number of calls of the part function
+  pub fn got_samples(&self) -> usize {
+    self.samples.len()
+  }
+
+  /// Number of final cells of every depth from 0 to `max_depth`
+  pub fn leaves_per_depth(&self) -> Vec<usize> {
+    let mut result = vec![0; self.params.max_depth + 1];
+    self.leaves.iter().for_each(|leaf| result[leaf.depth] += 1);
+    result
+  }
+
+  pub fn mesh(
+    &mut self,
+    part_f: &dyn Fn(Point) -> PartIndex,
+    distance_f: &dyn Fn(Point, PartIndex) -> Option<PartDistance>,
+    progress: &dyn Progress,
+  ) -> FxHashMap<PartIndex, Model> {
+    let tries = self.tries;
+    self.build(
+      &|points| points.iter().map(|&p| part_f(p)).collect(),
+      &|edges| {
+        let root =
+          |&(inside, outside, m)| surface_root(part_f, distance_f, inside, outside, m, tries);
+        edges.iter().map(root).collect()
+      },
+      distance_f,
+      progress,
+    )
+  }
+
+  /// Same as `mesh` with `part_f` called from `threads` threads, models are the same
+  pub fn mesh_parallel(
+    &mut self,
+    part_f: &(dyn Fn(Point) -> PartIndex + Sync),
+    distance_f: &(dyn Fn(Point, PartIndex) -> Option<PartDistance> + Sync),
+    threads: usize,
+    progress: &dyn Progress,
+  ) -> FxHashMap<PartIndex, Model> {
+    let tries = self.tries;
+    self.build(
+      &|points| in_parallel(points, threads, |&p| part_f(p)),
+      &|edges| {
+        in_parallel(edges, threads, |&(inside, outside, m)| {
+          surface_root(part_f, distance_f, inside, outside, m, tries)
+        })
+      },
+      distance_f,
+      progress,
+    )
+  }
+
+  // `eval` finds parts at points and `roots` finds borders of parts on edges from inside
+  // to outside points, both take whole batches to spread them between threads
+  fn build(
+    &mut self,
+    eval: &EvalPoints,
+    roots: &FindRoots,
+    distance_f: &dyn Fn(Point, PartIndex) -> Option<PartDistance>,
+    progress: &dyn Progress,
+  ) -> FxHashMap<PartIndex, Model> {
+    self.refine(eval, distance_f, progress);
+    self.find_lines();
+
+    let start = Instant::now();
+    // centers of faces are the only points of tetrahedrons which are not sampled yet
+    let mut centers = Vec::new();
+    for &leaf in &self.leaves {
+      self.leaf_faces(leaf, &mut |center, _| centers.push(center));
+    }
+    self.sample(centers, eval);
+
+    let mut edge_ids = FxHashMap::<EdgeKey, u32>::default();
+    let mut edges = Vec::new();
+    // triangles and quads by ids of edges, `NO_EDGE` is the 4th edge of triangles
+    let mut faces = Vec::new();
+    let mut add_face = |face: &[EdgeKey]| {
+      let mut ids = [NO_EDGE; 4];
+      for (id, &edge) in ids.iter_mut().zip(face) {
+        *id = *edge_ids.entry(edge).or_insert_with(|| {
+          edges.push(edge);
+          edges.len() as u32 - 1
+        });
+      }
+      faces.push(ids);
+    };
+    for (i, &leaf) in self.leaves.iter().enumerate() {
+      let cell_center = self.center(leaf);
+      self.leaf_faces(leaf, &mut |face_center, border| {
+        for k in 0..border.len() {
+          let tetrahedron = [cell_center, face_center, border[k], border[(k + 1) % border.len()]];
+          self.march(tetrahedron, &mut add_face);
+        }
+      });
+      if i % 1024 == 0 {
+        progress.progress("meshed cells", i, self.leaves.len(), start.elapsed());
+      }
+    }
+    progress.progress("meshed cells", self.leaves.len(), self.leaves.len(), start.elapsed());
+
+    let points: Vec<_> = edges
+      .iter()
+      .map(|&(inside, outside, m)| (self.point(inside), self.point(outside), m))
+      .collect();
+    let roots = roots(&points);
+
+    let mut models = FxHashMap::<PartIndex, Model>::default();
+    let vertices: Vec<u32> = edges
+      .iter()
+      .zip(roots)
+      .map(|(&(_, _, m), root)| models.entry(m).or_insert_with(Model::new).add_vertex(root))
+      .collect();
+    for face in faces {
+      let m = edges[face[0] as usize].2;
+      let model = models.get_mut(&m).unwrap();
+      let v = face.map(|id| if id == NO_EDGE { NO_EDGE } else { vertices[id as usize] });
+      if v[3] == NO_EDGE {
+        model.triangles.push([v[0], v[1], v[2]]);
+        continue;
+      }
+      // quads are split by the shorter diagonal
+      let p = v.map(|v| model.vertices[v as usize]);
+      if (p[2] - p[0]).sqr_len() <= (p[3] - p[1]).sqr_len() {
+        model.triangles.push([v[0], v[1], v[2]]);
+        model.triangles.push([v[0], v[2], v[3]]);
+      } else {
+        model.triangles.push([v[1], v[2], v[3]]);
+        model.triangles.push([v[1], v[3], v[0]]);
+      }
+    }
+    models
+  }
+
+  fn refine(
+    &mut self,
+    eval: &EvalPoints,
+    distance_f: &dyn Fn(Point, PartIndex) -> Option<PartDistance>,
+    progress: &dyn Progress,
+  ) {
+    let start = Instant::now();
+    let AdaptiveParams { min_depth, max_depth, .. } = self.params;
+    self.samples.clear();
+    self.leaves.clear();
+    self.split.clear();
+
+    let cells = 1u32 << min_depth;
+    let size = self.node_size(min_depth);
+    let mut level = Vec::new();
+    for z in 0..cells {
+      for y in 0..cells {
+        for x in 0..cells {
+          level.push(Node { depth: min_depth, origin: [x * size, y * size, z * size] });
+        }
+      }
+    }
+
+    for depth in min_depth..=max_depth {
+      let keys = level.iter().flat_map(|&node| self.cell_samples(node)).collect();
+      self.sample(keys, eval);
+      // parts whose distance is asked in cells without borders
+      let mut known: Vec<_> =
+        self.samples.values().copied().filter(|&i| self.is_selected(i)).collect();
+      known.sort();
+      known.dedup();
+
+      let mut next = Vec::new();
+      for node in level {
+        if depth < max_depth && self.needs_split(node, &known, distance_f) {
+          self.split.insert(node);
+          next.extend(self.children(node));
+        } else {
+          self.leaves.push(node);
+        }
+      }
+      level = next;
+      progress.progress(
+        "refined levels",
+        depth - min_depth + 1,
+        max_depth - min_depth + 1,
+        start.elapsed(),
+      );
+    }
+  }
+
+  fn needs_split(
+    &self,
+    node: Node,
+    known: &[PartIndex],
+    distance_f: &dyn Fn(Point, PartIndex) -> Option<PartDistance>,
+  ) -> bool {
+    let samples = self.cell_samples(node);
+    let indices = samples.map(|key| self.samples[&key]);
+    if indices.iter().all(|&i| i == indices[0]) {
+      // a border closer to the center than corners can pass between samples
+      let center = self.point(samples[8]);
+      let half_diagonal = self.node_size(node.depth) as f32 * self.unit() * 3.0f32.sqrt() * 0.5;
+      return known
+        .iter()
+        .any(|&m| distance_f(center, m).is_some_and(|d| d.distance.abs() < half_diagonal));
+    }
+    if !indices.iter().any(|&i| self.is_selected(i)) {
+      return false;
+    }
+    !(self.params.max_angle > 0.0 && self.is_flat(samples, indices, distance_f))
+  }
+
+  // border between two parts inside of the cell is nearly flat by gradients at samples
+  fn is_flat(
+    &self,
+    samples: [Key; 9],
+    indices: [PartIndex; 9],
+    distance_f: &dyn Fn(Point, PartIndex) -> Option<PartDistance>,
+  ) -> bool {
+    let mut parts: Vec<_> = indices.to_vec();
+    parts.sort();
+    parts.dedup();
+    if parts.len() != 2 {
+      return false;
+    }
+    let min_cos = self.params.max_angle.to_radians().cos();
+    parts.iter().filter(|&&m| m != 0).all(|&m| {
+      let Some(normal) = distance_f(self.point(samples[8]), m).and_then(|d| d.gradient) else {
+        return false;
+      };
+      let normal = normal.norm();
+      samples.iter().zip(indices).all(|(&key, index)| {
+        distance_f(self.point(key), m).is_some_and(|d| {
+          // distance should agree with the part function
+          let same_side = (d.distance <= 0.0) == (index == m);
+          same_side && d.gradient.is_some_and(|g| dot(g.norm(), normal) >= min_cos)
+        })
+      })
+    })
+  }
+
+  fn sample(&mut self, keys: Vec<Key>, eval: &EvalPoints) {
+    let mut seen = FxHashSet::default();
+    let keys: Vec<_> =
+      keys.into_iter().filter(|key| !self.samples.contains_key(key) && seen.insert(*key)).collect();
+    let points: Vec<_> = keys.iter().map(|&key| self.point(key)).collect();
+    self.samples.extend(keys.into_iter().zip(eval(&points)));
+  }
+
+  fn find_lines(&mut self) {
+    self.lines.clear();
+    for &leaf in &self.leaves {
+      for corner in self.corners(leaf) {
+        for axis in 0..3 {
+          let others = [corner[(axis + 1) % 3], corner[(axis + 2) % 3]];
+          self.lines.entry((axis, others)).or_default().push(corner[axis]);
+        }
+      }
+    }
+    for line in self.lines.values_mut() {
+      line.sort_unstable();
+      line.dedup();
+    }
+  }
+
+  // calls `f` with the center and the closed border of every square of faces of the leaf,
+  // faces touching smaller cells are split into their faces
+  fn leaf_faces(&self, leaf: Node, f: &mut dyn FnMut(Key, &[Key])) {
+    let size = self.node_size(leaf.depth);
+    let end = self.node_size(0);
+    let mut border = Vec::new();
+    for axis in 0..3 {
+      for positive in [false, true] {
+        let plane = if positive { leaf.origin[axis] + size } else { leaf.origin[axis] };
+        // faces of the meshed cube have no neighbours
+        let outside = plane == 0 || plane == end;
+        let mut neighbour = leaf;
+        if !outside {
+          neighbour.origin[axis] = if positive { plane } else { plane - size };
+        }
+        self.squares(neighbour, axis, plane, !outside, &mut |square| {
+          self.square_border(square, axis, plane, &mut border);
+          let half = self.node_size(square.depth) / 2;
+          let mut center = square.origin;
+          center[(axis + 1) % 3] += half;
+          center[(axis + 2) % 3] += half;
+          center[axis] = plane;
+          // both cells sharing the face get the same squares with the same borders
+          f(center, &border);
+        });
+      }
+    }
+  }
+
+  // splits the face of `node` lying on `plane` by faces of its descendants,
+  // only cells which are really split are descended
+  fn squares(&self, node: Node, axis: usize, plane: u32, descend: bool, f: &mut dyn FnMut(Node)) {
+    if !descend || !self.split.contains(&node) {
+      f(node);
+      return;
+    }
+    for child in self.children(node) {
+      let size = self.node_size(child.depth);
+      if child.origin[axis] == plane || child.origin[axis] + size == plane {
+        self.squares(child, axis, plane, true, f);
+      }
+    }
+  }
+
+  // corners of the square and all corners of leaves on its sides going around it
+  fn square_border(&self, square: Node, axis: usize, plane: u32, border: &mut Vec<Key>) {
+    let (u, v) = ((axis + 1) % 3, (axis + 2) % 3);
+    let size = self.node_size(square.depth);
+    let mut corner = square.origin;
+    corner[axis] = plane;
+    let mut corners = [corner; 4];
+    corners[1][u] += size;
+    corners[2][u] += size;
+    corners[2][v] += size;
+    corners[3][v] += size;
+
+    border.clear();
+    for k in 0..4 {
+      let (from, to) = (corners[k], corners[(k + 1) % 4]);
+      border.push(from);
+      let along = if from[u] != to[u] { u } else { v };
+      let others = [from[(along + 1) % 3], from[(along + 2) % 3]];
+      let Some(line) = self.lines.get(&(along, others)) else {
+        continue;
+      };
+      let (low, high) = (from[along].min(to[along]), from[along].max(to[along]));
+      let begin = line.partition_point(|&c| c <= low);
+      let end = line.partition_point(|&c| c < high);
+      let mut point = from;
+      let mut add = |c: u32| {
+        point[along] = c;
+        border.push(point);
+      };
+      if from[along] < to[along] {
+        line[begin..end].iter().for_each(|&c| add(c));
+      } else {
+        line[begin..end].iter().rev().for_each(|&c| add(c));
+      }
+    }
+  }
+
+  // marching tetrahedrons, faces are oriented by the sign of the tetrahedron volume
+  fn march(&self, tetrahedron: [Key; 4], add_face: &mut dyn FnMut(&[EdgeKey])) {
+    let indices = tetrahedron.map(|key| self.samples[&key]);
+    if indices.iter().all(|&i| i == indices[0]) {
+      return;
+    }
+    for (k, &m) in indices.iter().enumerate() {
+      if indices[..k].contains(&m) || self.skipped.contains(&m) || !self.is_selected(m) {
+        continue;
+      }
+      let (inside, outside): (Vec<_>, Vec<_>) =
+        tetrahedron.iter().zip(indices).partition(|&(_, index)| index == m);
+      let inside: Vec<Key> = inside.into_iter().map(|(&key, _)| key).collect();
+      let outside: Vec<Key> = outside.into_iter().map(|(&key, _)| key).collect();
+      let edge = |i: Key, o: Key| (i, o, m);
+      match (inside.as_slice(), outside.as_slice()) {
+        (&[a], &[b, c, d]) => {
+          if orientation(a, b, c, d) > 0 {
+            add_face(&[edge(a, b), edge(a, c), edge(a, d)]);
+          } else {
+            add_face(&[edge(a, b), edge(a, d), edge(a, c)]);
+          }
+        }
+        (&[b, c, d], &[a]) => {
+          if orientation(a, b, c, d) > 0 {
+            add_face(&[edge(b, a), edge(d, a), edge(c, a)]);
+          } else {
+            add_face(&[edge(b, a), edge(c, a), edge(d, a)]);
+          }
+        }
+        (&[a, b], &[c, d]) => {
+          if orientation(a, b, c, d) > 0 {
+            add_face(&[edge(a, c), edge(a, d), edge(b, d), edge(b, c)]);
+          } else {
+            add_face(&[edge(a, c), edge(b, c), edge(b, d), edge(a, d)]);
+          }
+        }
+        _ => {}
+      }
+    }
+  }
+
+  // size of cells of `depth` in lattice steps
+  fn node_size(&self, depth: usize) -> u32 {
+    1 << (self.params.max_depth + 1 - depth)
+  }
+
+  fn unit(&self) -> f32 {
+    self.solid_size / self.node_size(0) as f32
+  }
+
+  fn point(&self, key: Key) -> Point {
+    let unit = self.unit();
+    let shift = -self.solid_size * 0.5;
+    Point {
+      x: key[0] as f32 * unit + shift,
+      y: key[1] as f32 * unit + shift,
+      z: key[2] as f32 * unit + shift,
+    }
+  }
+
+  fn center(&self, node: Node) -> Key {
+    let half = self.node_size(node.depth) / 2;
+    node.origin.map(|c| c + half)
+  }
+
+  fn corners(&self, node: Node) -> [Key; 8] {
+    let size = self.node_size(node.depth);
+    let [x, y, z] = node.origin;
+    [
+      [x, y, z],
+      [x + size, y, z],
+      [x, y + size, z],
+      [x + size, y + size, z],
+      [x, y, z + size],
+      [x + size, y, z + size],
+      [x, y + size, z + size],
+      [x + size, y + size, z + size],
+    ]
+  }
+
+  // corners and the center
+  fn cell_samples(&self, node: Node) -> [Key; 9] {
+    let c = self.corners(node);
+    [c[0], c[1], c[2], c[3], c[4], c[5], c[6], c[7], self.center(node)]
+  }
+
+  fn children(&self, node: Node) -> [Node; 8] {
+    let depth = node.depth + 1;
+    self.corners(Node { depth, origin: node.origin }).map(|origin| Node { depth, origin })
+  }
+}
+
+// sign of the volume of tetrahedron `a b c d`, exact for lattice points
+fn orientation(a: Key, b: Key, c: Key, d: Key) -> i64 {
+  let sub = |p: Key| [0, 1, 2].map(|i| p[i] as i64 - a[i] as i64);
+  let (b, c, d) = (sub(b), sub(c), sub(d));
+  b[0] * (c[1] * d[2] - c[2] * d[1]) - b[1] * (c[0] * d[2] - c[2] * d[0])
+    + b[2] * (c[0] * d[1] - c[1] * d[0])
+}
+
+fn in_parallel<I: Sync, T: Send>(
+  items: &[I],
+  threads: usize,
+  f: impl Fn(&I) -> T + Sync,
+) -> Vec<T> {
+  let chunk = std::cmp::max(1, items.len().div_ceil(threads));
+  let f = &f;
+  std::thread::scope(|s| {
+    let handles: Vec<_> = items
+      .chunks(chunk)
+      .map(|chunk| s.spawn(move || chunk.iter().map(f).collect::<Vec<_>>()))
+      .collect();
+    handles.into_iter().flat_map(|handle| handle.join().unwrap()).collect()
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn octree_of_block_with_thin_slot() {
+    // two halves of a block split by a slot with a pin in it
+    let part_f = |p: Point| {
+      if p.x.abs() > 8.0 || p.y.abs() > 8.0 || p.z.abs() > 8.0 {
+        0
+      } else if p.x.abs() < 0.3 {
+        if p.y * p.y + p.z * p.z < 1.0 { 3 } else { 0 }
+      } else if p.x < 0.0 {
+        1
+      } else {
+        2
+      }
+    };
+    let params = AdaptiveParams { min_depth: 2, max_depth: 5, max_angle: 0.0 };
+    let mut oc = OctreeCreator::new(params, 20.0, 20);
+    let models = oc.mesh(&part_f, &|_, _| None, &SilentProgress);
+    let leaves = oc.leaves_per_depth();
+    // bulk of the block and empty space stay coarse
+    assert!(leaves[3] > 0 && leaves[5] > 0);
+    assert_eq!(models.len(), 3);
+    for (m_index, m) in &models {
+      let report = m.validate();
+      assert!(report.is_valid(), "{m_index}: {}", report.summary());
+    }
+    assert!((models[&1].get_volume() - 7.7 * 256.0).abs() < 20.0);
+  }
+}
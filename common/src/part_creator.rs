@@ -0,0 +1,276 @@
+use crate::material::Material;
+use crate::points2d;
+use crate::points2d::AABB;
+use crate::points3d;
+use crate::solid::{PartDistance, PartIndex};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use std::path::Path;
+use std::sync::Mutex;
+
+/// Common interface of every model generator, both 3d (meshed by `ModelCreator`)
+/// and 2d (flat faces contoured by `ContourCreator`).
+/// Faces are the flat parts, they are indexed from `0` to `faces() - 1`.
+pub trait PartCreator: Send {
+  fn faces(&self) -> usize {
+    0
+  }
+
+  fn get_height(&self, part_index: usize) -> f32 {
+    0.6
+  }
+
+  fn get_count(&self, part_index: usize) -> usize {
+    1
+  }
+
+  fn get_name(&self, part_index: usize) -> Option<String> {
+    None
+  }
+
+  fn get_sticker_index(&self, pos: points2d::Point, part_index: usize) -> PartIndex {
+    0
+  }
+
+  fn get_part_index(&self, pos: points3d::Point) -> PartIndex {
+    0
+  }
+
+  /// signed distance from `pos` to the surface of part `m_index`, negative inside,
+  /// lets the mesher place vertices on the surface instead of bisecting `get_part_index`,
+  /// `None` where the distance is unknown
+  fn get_part_distance(&self, pos: points3d::Point, m_index: PartIndex) -> Option<PartDistance> {
+    None
+  }
+
+  fn get_quality(&self) -> usize {
+    128
+  }
+
+  fn get_size(&self) -> f32 {
+    100.0
+  }
+
+  /// box holding all meshed parts, only it is covered by the grid,
+  /// `None` means the cube of `get_size()` around zero
+  fn mesh_aabb(&self) -> Option<(points3d::Point, points3d::Point)> {
+    None
+  }
+
+  /// area where face contour is searched, `None` means some big default area
+  fn aabb(&self, part_index: usize) -> Option<AABB> {
+    None
+  }
+
+  /// name of meshed part, `None` means `part_<m_index>`
+  fn get_model_name(&self, m_index: PartIndex) -> Option<String> {
+    None
+  }
+
+  /// number of pieces of meshed part needed for one assembly
+  fn get_model_count(&self, m_index: PartIndex) -> usize {
+    1
+  }
+
+  /// material of meshed part, `None` means `DEFAULT_MATERIAL`
+  fn get_model_material(&self, m_index: PartIndex) -> Option<Material> {
+    None
+  }
+
+  /// material the face is cut from, `None` means `DEFAULT_MATERIAL`
+  fn get_face_material(&self, part_index: usize) -> Option<Material> {
+    None
+  }
+
+  /// parameters the meshed part depends on, the cached model is reused while they are the same,
+  /// `None` means all parameters of the creator
+  fn get_cache_key(&self, m_index: PartIndex) -> Option<String> {
+    None
+  }
+}
+
+/// Creator whose dimensions come from a serializable parameter struct,
+/// `with_params(Default::default())` should give the same parts as `new()`
+pub trait ParamsCreator: PartCreator + Sized + 'static {
+  type Params: Serialize + DeserializeOwned + Default;
+
+  fn with_params(params: Self::Params) -> Self;
+}
+
+/// Created creator with json of parameters it was built from,
+/// `params` is `None` for creators without parameters
+pub struct CreatorInstance {
+  pub creator: Box<dyn PartCreator>,
+  pub params: Option<String>,
+}
+
+/// Takes json with parameter overrides, fields which are missing keep their defaults
+pub type CreatorFactory = fn(params: Option<&str>) -> Result<CreatorInstance, String>;
+
+/// Creator which can be selected by name at runtime
+pub struct RegisteredCreator {
+  pub name: &'static str,
+  pub create: CreatorFactory,
+  /// implements `ParamsCreator`, other creators reject parameter overrides
+  pub has_params: bool,
+}
+
+impl RegisteredCreator {
+  /// Creates the creator with overrides from json file `params_path` and saves parameters
+  /// it was built from to `output_dir/<name>_params.json`
+  pub fn instantiate(
+    &self,
+    params_path: Option<&Path>,
+    output_dir: &Path,
+  ) -> Result<CreatorInstance, String> {
+    let overrides = read_overrides(params_path)?;
+    let instance = (self.create)(overrides.as_deref())?;
+
+    if let Some(params) = &instance.params {
+      let path = output_dir.join(format!("{}_params.json", self.name));
+      std::fs::create_dir_all(output_dir)
+        .and_then(|_| std::fs::write(&path, params))
+        .map_err(|e| format!("Unable to write parameters to {}: {}", path.to_string_lossy(), e))?;
+    }
+    Ok(instance)
+  }
+
+  /// Creates `count` instances with the same overrides without saving parameters
+  pub fn instantiate_pool(
+    &self,
+    params_path: Option<&Path>,
+    count: usize,
+  ) -> Result<CreatorPool, String> {
+    let overrides = read_overrides(params_path)?;
+    let instances = (0..count)
+      .map(|_| (self.create)(overrides.as_deref()).map(|i| i.creator))
+      .collect::<Result<_, _>>()?;
+    Ok(CreatorPool::new(instances))
+  }
+}
+
+fn read_overrides(params_path: Option<&Path>) -> Result<Option<String>, String> {
+  let Some(path) = params_path else {
+    return Ok(None);
+  };
+  std::fs::read_to_string(path)
+    .map(Some)
+    .map_err(|e| format!("Unable to read parameters from {}: {}", path.to_string_lossy(), e))
+}
+
+/// Instances of the same creator to call `get_part_index` from several threads,
+/// each call takes any free instance, so creators may keep caches in `RefCell`s
+pub struct CreatorPool {
+  instances: Vec<Mutex<Box<dyn PartCreator>>>,
+}
+
+impl CreatorPool {
+  pub fn new(instances: Vec<Box<dyn PartCreator>>) -> Self {
+    Self { instances: instances.into_iter().map(Mutex::new).collect() }
+  }
+
+  pub fn len(&self) -> usize {
+    self.instances.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.instances.is_empty()
+  }
+
+  pub fn get_part_index(&self, pos: points3d::Point) -> PartIndex {
+    for instance in &self.instances {
+      if let Ok(creator) = instance.try_lock() {
+        return creator.get_part_index(pos);
+      }
+    }
+    // all instances were busy when tried, which is rare while the pool has an instance per
+    // thread as each one is locked only for a single call, so waiting for the first is cheap
+    self.instances[0].lock().unwrap().get_part_index(pos)
+  }
+
+  pub fn get_part_distance(
+    &self,
+    pos: points3d::Point,
+    m_index: PartIndex,
+  ) -> Option<PartDistance> {
+    for instance in &self.instances {
+      if let Ok(creator) = instance.try_lock() {
+        return creator.get_part_distance(pos, m_index);
+      }
+    }
+    self.instances[0].lock().unwrap().get_part_distance(pos, m_index)
+  }
+}
+
+/// Builds `&[RegisteredCreator]` from `"name" => path::ToCreator` pairs,
+/// every creator type should have `new() -> Self`.
+/// Creators implementing `ParamsCreator` are marked as `"name" => path::ToCreator [params]`
+#[macro_export]
+macro_rules! creator_registry {
+  ($($name: literal => $creator: ty $([$params: ident])?),* $(,)?) => {
+    &[$($crate::part_creator::RegisteredCreator {
+      name: $name,
+      create: $crate::creator_factory!($name, $creator $(, $params)?),
+      has_params: $crate::creator_has_params!($($params)?),
+    }),*]
+  };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! creator_factory {
+  ($name: literal, $creator: ty) => {
+    |params| $crate::part_creator::create_without_params($name, <$creator>::new, params)
+  };
+  ($name: literal, $creator: ty, params) => {
+    |params| $crate::part_creator::create_with_params::<$creator>(params)
+  };
+}
+
+#[doc(hidden)]
+#[macro_export]
+macro_rules! creator_has_params {
+  () => {
+    false
+  };
+  (params) => {
+    true
+  };
+}
+
+pub fn create_without_params<C: PartCreator + 'static>(
+  name: &str,
+  new: fn() -> C,
+  params: Option<&str>,
+) -> Result<CreatorInstance, String> {
+  if params.is_some() {
+    return Err(format!("Creator {} has no parameters, its dimensions are set in code", name));
+  }
+  Ok(CreatorInstance { creator: Box::new(new()), params: None })
+}
+
+pub fn create_with_params<C: ParamsCreator>(
+  params: Option<&str>,
+) -> Result<CreatorInstance, String> {
+  let params: C::Params = match params {
+    Some(json) => {
+      serde_json::from_str(json).map_err(|e| format!("Unable to parse parameters: {}", e))?
+    }
+    None => Default::default(),
+  };
+  let json = serde_json::to_string_pretty(&params)
+    .map_err(|e| format!("Unable to serialize parameters: {}", e))?;
+  Ok(CreatorInstance { creator: Box::new(C::with_params(params)), params: Some(json) })
+}
+
+/// Names of creators whose parameters can be overridden
+pub fn creators_with_params(registry: &[RegisteredCreator]) -> Vec<&'static str> {
+  registry.iter().filter(|c| c.has_params).map(|c| c.name).collect()
+}
+
+pub fn find_creator<'a>(
+  registry: &'a [RegisteredCreator],
+  name: &str,
+) -> Option<&'a RegisteredCreator> {
+  registry.iter().find(|c| c.name == name)
+}
@@ -0,0 +1,253 @@
+use crate::matrix::*;
+use crate::model::*;
+use crate::points3d::*;
+use crate::solid::PartIndex;
+use std::path::Path;
+
+// each pixel of the image is averaged from SAMPLES x SAMPLES rendered ones
+const SAMPLES: usize = 2;
+const FOVY: f32 = std::f32::consts::FRAC_PI_8;
+
+/// Color of the model in previews and in the viewer window
+pub fn part_color(m_index: PartIndex) -> u32 {
+  match m_index / 100000 {
+    1 => 0x00FF00,
+    2 => 0xFF2000,
+    3 => 0xFFFF00,
+    4 => 0x0080FF,
+    5 => 0xFF8000,
+    6 => 0xEEEEEE,
+    7 => 0xFF00FF,
+    8 => 0xFF80FF,
+    _ => m_index.wrapping_mul(0x2743811) | 0x808080,
+  }
+}
+
+/// Camera looking at the center of all models from outside of them,
+/// angles are in radians and have the same meaning as in `Matrix::new_view`
+#[derive(Debug, Clone, Copy)]
+pub struct Camera {
+  pub angle_y: f32,
+  pub angle_x: f32,
+}
+
+impl Camera {
+  pub const FRONT: Camera = Camera { angle_y: 0.0, angle_x: 0.0 };
+  pub const SIDE: Camera = Camera { angle_y: std::f32::consts::FRAC_PI_2, angle_x: 0.0 };
+  pub const TOP: Camera = Camera { angle_y: 0.0, angle_x: std::f32::consts::FRAC_PI_2 };
+  // angle_x is atan(1 / sqrt(2)), so the diagonal of a cube points at the camera
+  pub const ISOMETRIC: Camera = Camera { angle_y: std::f32::consts::FRAC_PI_4, angle_x: 0.6154797 };
+
+  pub const DEFAULT_VIEWS: [Camera; 4] = [Self::FRONT, Self::SIDE, Self::TOP, Self::ISOMETRIC];
+}
+
+/// Shaded snapshot of models made without any graphics api
+pub struct Preview {
+  width: usize,
+  height: usize,
+  // rgb of supersampled image
+  color: Vec<f32>,
+  depth: Vec<f32>,
+}
+
+impl Preview {
+  pub fn new(width: usize, height: usize) -> Self {
+    let size = width * SAMPLES * height * SAMPLES;
+    Self { width, height, color: vec![1.0; size * 3], depth: vec![f32::INFINITY; size] }
+  }
+
+  /// Renders models written by `Model::write_to_buffer`
+  pub fn render(buffer: &ArrayBuffer, camera: Camera, width: usize, height: usize) -> Self {
+    let mut preview = Self::new(width, height);
+    let positions: Vec<Point> =
+      buffer.v.chunks_exact(9).map(|v| Point { x: v[0], y: v[1], z: v[2] }).collect();
+    if positions.is_empty() {
+      return preview;
+    }
+
+    let mut min = positions[0];
+    let mut max = positions[0];
+    for p in &positions {
+      min = Point { x: min.x.min(p.x), y: min.y.min(p.y), z: min.z.min(p.z) };
+      max = Point { x: max.x.max(p.x), y: max.y.max(p.y), z: max.z.max(p.z) };
+    }
+    let center = (min + max).scale(0.5);
+    let radius = positions.iter().fold(0.0, |r: f32, p| r.max((*p - center).len())).max(0.001);
+
+    // whole bounding sphere should be visible in both directions
+    let aspect = width as f32 / height as f32;
+    let fov = f32::min(FOVY, 2.0 * ((FOVY * 0.5).tan() * aspect).atan());
+    let distance = radius / (fov * 0.5).sin() * 1.05;
+    let view = mat_mul(
+      Matrix::new_view(-center, camera.angle_y, camera.angle_x),
+      Matrix::translated(Point { x: 0.0, y: 0.0, z: distance }),
+    );
+    let proj = Matrix::new_proj(FOVY, aspect, distance + radius * 2.0, (distance - radius) * 0.5);
+
+    for t in buffer.i.chunks_exact(3) {
+      let v = [t[0] as usize, t[1] as usize, t[2] as usize];
+      let in_view = v.map(|i| {
+        let p = view.apply(positions[i]);
+        Point { x: p[0], y: p[1], z: p[2] }
+      });
+
+      // the nearer to the camera direction normal is, the brighter is triangle
+      let normal = cross(in_view[1] - in_view[0], in_view[2] - in_view[0]);
+      let to_triangle = in_view[0] + in_view[1] + in_view[2];
+      if normal.len() == 0.0 || to_triangle.len() == 0.0 {
+        continue;
+      }
+      let facing = dot(normal.norm(), to_triangle.norm()).abs();
+      let n_factor = 1.0 + (facing - 1.0) * 0.5;
+
+      let c = &buffer.v[v[0] * 9 + 6..v[0] * 9 + 9];
+      let color = [c[0] * n_factor, c[1] * n_factor, c[2] * n_factor];
+
+      let screen = in_view.map(|p| preview.to_screen(proj.apply(p)));
+      preview.fill_triangle(screen, color);
+    }
+    preview
+  }
+
+  // x and y in supersampled pixels, z is ndc depth
+  fn to_screen(&self, p: [f32; 4]) -> Point {
+    let w = p[3].max(f32::EPSILON);
+    Point {
+      x: (p[0] / w + 1.0) * 0.5 * (self.width * SAMPLES) as f32,
+      y: (1.0 - p[1] / w) * 0.5 * (self.height * SAMPLES) as f32,
+      z: p[2] / w,
+    }
+  }
+
+  fn fill_triangle(&mut self, t: [Point; 3], color: [f32; 3]) {
+    let size_x = self.width * SAMPLES;
+    let size_y = self.height * SAMPLES;
+
+    let area = edge(t[0], t[1], t[2]);
+    if area.abs() < f32::EPSILON {
+      return;
+    }
+
+    let x1 = t.iter().fold(f32::MAX, |m, p| m.min(p.x)).floor().max(0.0) as usize;
+    let y1 = t.iter().fold(f32::MAX, |m, p| m.min(p.y)).floor().max(0.0) as usize;
+    let x2 = (t.iter().fold(f32::MIN, |m, p| m.max(p.x)).ceil().max(0.0) as usize).min(size_x);
+    let y2 = (t.iter().fold(f32::MIN, |m, p| m.max(p.y)).ceil().max(0.0) as usize).min(size_y);
+
+    for y in y1..y2 {
+      for x in x1..x2 {
+        let p = Point { x: x as f32 + 0.5, y: y as f32 + 0.5, z: 0.0 };
+        let w0 = edge(t[1], t[2], p) / area;
+        let w1 = edge(t[2], t[0], p) / area;
+        let w2 = edge(t[0], t[1], p) / area;
+        if w0 < 0.0 || w1 < 0.0 || w2 < 0.0 {
+          continue;
+        }
+
+        let z = t[0].z * w0 + t[1].z * w1 + t[2].z * w2;
+        let index = x + y * size_x;
+        if z < -1.0 || z >= self.depth[index] {
+          continue;
+        }
+        self.depth[index] = z;
+        self.color[index * 3..index * 3 + 3].copy_from_slice(&color);
+      }
+    }
+  }
+
+  pub fn save_to_png(&self, path: &Path) -> Result<(), String> {
+    let size_x = self.width * SAMPLES;
+    let mut data = Vec::with_capacity(self.width * self.height * 3);
+    for y in 0..self.height {
+      for x in 0..self.width {
+        for c in 0..3 {
+          let mut sum = 0.0;
+          for sy in 0..SAMPLES {
+            for sx in 0..SAMPLES {
+              sum += self.color[((x * SAMPLES + sx) + (y * SAMPLES + sy) * size_x) * 3 + c];
+            }
+          }
+          let value = sum / (SAMPLES * SAMPLES) as f32;
+          data.push((value.clamp(0.0, 1.0) * 255.0).round() as u8);
+        }
+      }
+    }
+
+    let file =
+      std::io::BufWriter::new(std::fs::File::create(path).map_err(|e| {
+        format!("Unable to open file {} for writing: {}", path.to_string_lossy(), e)
+      })?);
+    let mut encoder = png::Encoder::new(file, self.width as u32, self.height as u32);
+    encoder.set_color(png::ColorType::Rgb);
+    encoder.set_depth(png::BitDepth::Eight);
+    encoder
+      .write_header()
+      .and_then(|mut writer| writer.write_image_data(&data))
+      .map_err(|e| format!("Unable to write png to file {}: {}", path.to_string_lossy(), e))
+  }
+}
+
+// doubled signed area of triangle (a, b, p) in screen plane
+fn edge(a: Point, b: Point, p: Point) -> f32 {
+  (b.x - a.x) * (p.y - a.y) - (b.y - a.y) * (p.x - a.x)
+}
+
+/// Saves `preview_<i>.png` for every camera to `dir`
+pub fn save_previews<'a>(
+  models: impl Iterator<Item = (PartIndex, &'a Model)>,
+  cameras: &[Camera],
+  width: usize,
+  height: usize,
+  dir: &Path,
+) -> Result<(), String> {
+  let mut buffer = ArrayBuffer::default();
+  for (m_index, m) in models {
+    m.write_to_buffer(&mut buffer, part_color(m_index));
+  }
+
+  std::fs::create_dir_all(dir)
+    .map_err(|e| format!("Unable to create directory {}: {}", dir.to_string_lossy(), e))?;
+  for (i, camera) in cameras.iter().enumerate() {
+    let preview = Preview::render(&buffer, *camera, width, height);
+    preview.save_to_png(&dir.join(format!("preview_{}.png", i + 1)))?;
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn front_preview_of_cube() {
+    let mut buffer = ArrayBuffer::default();
+    Model::cuboid(2, 2, 2, 1.0).write_to_buffer(&mut buffer, 0x0000FF);
+    let preview = Preview::render(&buffer, Camera::FRONT, 64, 64);
+
+    // the nearest face hides all others, its half size in supersampled pixels
+    let half = 1.0;
+    let distance = 3f32.sqrt() * half / (FOVY * 0.5).sin() * 1.05;
+    let ctg = (FOVY * 0.5).tan().recip();
+    let size = (64 * SAMPLES) as f32;
+    let face = half * ctg / (distance - half) * size * 0.5;
+
+    let mut covered = 0;
+    for y in 0..64 * SAMPLES {
+      for x in 0..64 * SAMPLES {
+        let index = x + y * 64 * SAMPLES;
+        let is_covered = preview.depth[index].is_finite();
+        let from_center =
+          f32::max((x as f32 + 0.5 - size * 0.5).abs(), (y as f32 + 0.5 - size * 0.5).abs());
+        if from_center < face - 1.0 {
+          assert!(is_covered, "pixel {x} {y} is not covered");
+          assert!(preview.color[index * 3] < 0.5 && preview.color[index * 3 + 2] > 0.5);
+        } else if from_center > face + 1.0 {
+          assert!(!is_covered, "pixel {x} {y} is covered");
+          assert_eq!(preview.color[index * 3..index * 3 + 3], [1.0; 3]);
+        }
+        covered += is_covered as usize;
+      }
+    }
+    let expected = (2.0 * face) * (2.0 * face);
+    assert!((covered as f32 - expected).abs() < 8.0 * face, "{covered} != {expected}");
+  }
+}
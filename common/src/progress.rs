@@ -0,0 +1,50 @@
+use std::cell::Cell;
+use std::io::Write;
+use std::time::Duration;
+
+/// Receives progress of long operations like meshing, contouring and optimization
+pub trait Progress {
+  /// `current` of `total` steps of `stage` are done, `elapsed` is time since the stage start
+  fn progress(&self, stage: &str, current: usize, total: usize, elapsed: Duration);
+
+  /// Message about intermediate results
+  fn log(&self, message: &str);
+}
+
+/// Prints progress to stdout rewriting the same line
+#[derive(Default)]
+pub struct ConsoleProgress {
+  // progress line is not finished by a new line yet
+  in_line: Cell<bool>,
+}
+
+impl ConsoleProgress {
+  pub fn new() -> Self {
+    Self::default()
+  }
+}
+
+impl Progress for ConsoleProgress {
+  fn progress(&self, stage: &str, current: usize, total: usize, elapsed: Duration) {
+    print!("\r{stage} [{current}/{total}] {elapsed:.1?}");
+    // stdout is line buffered, the line without a new line shows up only after a flush
+    let _ = std::io::stdout().flush();
+    self.in_line.set(true);
+  }
+
+  fn log(&self, message: &str) {
+    if self.in_line.replace(false) {
+      println!();
+    }
+    println!("{message}");
+  }
+}
+
+/// Ignores everything
+pub struct SilentProgress;
+
+impl Progress for SilentProgress {
+  fn progress(&self, stage: &str, current: usize, total: usize, elapsed: Duration) {}
+
+  fn log(&self, message: &str) {}
+}
@@ -0,0 +1,401 @@
+use crate::model::*;
+use crate::points3d::*;
+use crate::validation::*;
+use fxhash::FxHashMap;
+use serde::Serialize;
+
+#[derive(Debug, Clone)]
+pub struct RepairParams {
+  /// vertices closer than this are merged, 0 merges only equal ones
+  pub weld_tolerance: f32,
+  /// boundary loops with more edges stay open
+  pub max_hole_edges: usize,
+}
+
+impl Default for RepairParams {
+  fn default() -> Self {
+    Self { weld_tolerance: 1.0e-4, max_hole_edges: 32 }
+  }
+}
+
+/// What `Model::repair` changed
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct RepairReport {
+  pub welded_vertices: usize,
+  /// collapsed by a short edge or replaced by flipping their long edge
+  pub degenerate_faces: usize,
+  /// repeated faces and pairs of opposite ones
+  pub duplicate_faces: usize,
+  pub flipped_faces: usize,
+  pub filled_holes: usize,
+  /// too long or not closed boundary loops
+  pub unfilled_holes: usize,
+}
+
+impl RepairReport {
+  pub fn changed(&self) -> bool {
+    self.welded_vertices
+      + self.degenerate_faces
+      + self.duplicate_faces
+      + self.flipped_faces
+      + self.filled_holes
+      > 0
+  }
+
+  pub fn summary(&self) -> String {
+    format!(
+      "{} welded vertices, {} degenerate faces, {} duplicate faces, {} flipped faces, \
+       {} filled holes, {} unfilled holes",
+      self.welded_vertices,
+      self.degenerate_faces,
+      self.duplicate_faces,
+      self.flipped_faces,
+      self.filled_holes,
+      self.unfilled_holes
+    )
+  }
+}
+
+impl Model {
+  /// Welds vertices, drops degenerate and duplicate faces, fills small holes
+  /// and orients every shell to have positive volume
+  pub fn repair(&mut self, params: &RepairParams) -> RepairReport {
+    let mut report = RepairReport::default();
+    self.clean(params.weld_tolerance, &mut report);
+
+    let original = self.triangles.len();
+    let mut flipped = vec![false; original];
+    self.orient_neighbours(&mut flipped);
+    let (filled, unfilled) = self.fill_holes(params.max_hole_edges);
+    report.filled_holes = filled;
+    report.unfilled_holes = unfilled;
+    flipped.resize(self.triangles.len(), false);
+    self.orient_shells(&mut flipped);
+    report.flipped_faces = flipped[..original].iter().filter(|&&f| f).count();
+
+    self.delete_unused_v();
+    report
+  }
+
+  /// The first steps of `repair` which keep orientation of faces: welds vertices,
+  /// drops degenerate and duplicate faces
+  pub(crate) fn clean(&mut self, weld_tolerance: f32, report: &mut RepairReport) {
+    let mut merged = DisjointSets::new(self.vertices.len());
+    report.welded_vertices = self.weld(weld_tolerance, &mut merged);
+    let triangles_before = self.triangles.len();
+    let max_sqr_len = weld_tolerance * weld_tolerance;
+    loop {
+      self.remap_vertices(&mut merged);
+      // needles are collapsed by their short edge, caps are left for `flip_caps`
+      let mut collapsed = false;
+      for &t in &self.triangles {
+        if self.is_degenerate(t) {
+          let [v0, v1, v2] = t.map(|v| self.vertices[v as usize]);
+          let lens = [(v1 - v0).sqr_len(), (v2 - v1).sqr_len(), (v0 - v2).sqr_len()];
+          let k = (0..3).min_by(|&i, &j| lens[i].total_cmp(&lens[j])).unwrap();
+          let (a, b) = (t[k] as usize, t[(k + 1) % 3] as usize);
+          if lens[k] <= max_sqr_len && merged.find(a) != merged.find(b) {
+            merged.union(a, b);
+            collapsed = true;
+          }
+        }
+      }
+      if !collapsed {
+        break;
+      }
+    }
+    report.degenerate_faces = triangles_before - self.triangles.len();
+
+    // opposite faces of the same vertices cancel each other, repeated ones are kept once
+    let mut balance = FxHashMap::<Triangle, i32>::default();
+    for &t in &self.triangles {
+      let (key, even) = face_key(t);
+      *balance.entry(key).or_default() += if even { 1 } else { -1 };
+    }
+    let before_duplicates = self.triangles.len();
+    self.triangles.retain(|&t| {
+      let (key, even) = face_key(t);
+      let b = balance.get_mut(&key).unwrap();
+      let keep = if even { *b > 0 } else { *b < 0 };
+      if keep {
+        *b = 0;
+      }
+      keep
+    });
+    report.duplicate_faces = before_duplicates - self.triangles.len();
+    report.degenerate_faces += self.flip_caps();
+  }
+
+  // merges vertices into the first close one, returns the number of merged ones
+  fn weld(&mut self, tolerance: f32, merged: &mut DisjointSets) -> usize {
+    // equal points have equal bits besides the signed zero
+    let exact = tolerance <= 0.0;
+    let to_cell = |p: Point| {
+      if exact {
+        [p.x, p.y, p.z].map(|c| (c + 0.0).to_bits() as i32)
+      } else {
+        [p.x, p.y, p.z].map(|c| (c / tolerance).floor() as i32)
+      }
+    };
+    let r = if exact { 0 } else { 1 };
+    let mut grid = FxHashMap::<[i32; 3], Vec<u32>>::default();
+    let mut welded = 0;
+    for (i, &p) in self.vertices.iter().enumerate() {
+      let c = to_cell(p);
+      let mut found = None;
+      'search: for dx in -r..=r {
+        for dy in -r..=r {
+          for dz in -r..=r {
+            let Some(near) = grid.get(&[c[0] + dx, c[1] + dy, c[2] + dz]) else { continue };
+            for &j in near {
+              if (self.vertices[j as usize] - p).len() <= tolerance {
+                found = Some(j);
+                break 'search;
+              }
+            }
+          }
+        }
+      }
+      match found {
+        Some(j) => {
+          merged.union(i, j as usize);
+          welded += 1;
+        }
+        None => grid.entry(c).or_default().push(i as u32),
+      }
+    }
+    welded
+  }
+
+  // replaces merged vertices by their representatives and drops collapsed faces
+  fn remap_vertices(&mut self, merged: &mut DisjointSets) {
+    for t in &mut self.triangles {
+      for v in t.iter_mut() {
+        *v = merged.find(*v as usize) as u32;
+      }
+    }
+    self.triangles.retain(|t| t[0] != t[1] && t[1] != t[2] && t[2] != t[0]);
+  }
+
+  // replaces every cap and its neighbour over the long edge by two triangles going to the
+  // opposite vertex of the neighbour, returns the number of replaced caps
+  fn flip_caps(&mut self) -> usize {
+    let mut count = 0;
+    loop {
+      let edges = self.edge_faces();
+      let mut used = vec![false; self.triangles.len()];
+      let mut flipped = false;
+      for f in 0..self.triangles.len() {
+        let t = self.triangles[f];
+        if used[f] || !self.is_degenerate(t) {
+          continue;
+        }
+        let [p0, p1, p2] = t.map(|v| self.vertices[v as usize]);
+        let lens = [(p1 - p0).sqr_len(), (p2 - p1).sqr_len(), (p0 - p2).sqr_len()];
+        let k = (0..3).max_by(|&i, &j| lens[i].total_cmp(&lens[j])).unwrap();
+        let (a, b, c) = (t[k], t[(k + 1) % 3], t[(k + 2) % 3]);
+        let Some(faces) = edges.get(&(a.min(b), a.max(b))) else { continue };
+        let n = if faces[0] == f as u32 { faces[1] } else { faces[0] };
+        if used[n as usize] || !self.has_directed_edge(n, b, a) {
+          continue;
+        }
+        let d = *self.triangles[n as usize].iter().find(|&&v| v != a && v != b).unwrap();
+        let new = [[a, d, c], [d, b, c]];
+        let exists = edges.contains_key(&(c.min(d), c.max(d)));
+        if exists || new.iter().any(|&t| self.is_degenerate(t)) {
+          continue;
+        }
+        self.triangles[f] = new[0];
+        self.triangles[n as usize] = new[1];
+        used[f] = true;
+        used[n as usize] = true;
+        flipped = true;
+        count += 1;
+      }
+      if !flipped {
+        return count;
+      }
+    }
+  }
+
+  // triangles sharing each manifold edge
+  fn edge_faces(&self) -> FxHashMap<Edge, Vec<u32>> {
+    let mut edges = FxHashMap::<Edge, Vec<u32>>::default();
+    for (i, t) in self.triangles.iter().enumerate() {
+      for k in 0..3 {
+        let (v0, v1) = (t[k], t[(k + 1) % 3]);
+        edges.entry((v0.min(v1), v0.max(v1))).or_default().push(i as u32);
+      }
+    }
+    edges.retain(|_, faces| faces.len() == 2);
+    edges
+  }
+
+  fn has_directed_edge(&self, f: u32, v0: u32, v1: u32) -> bool {
+    let t = self.triangles[f as usize];
+    (0..3).any(|k| t[k] == v0 && t[(k + 1) % 3] == v1)
+  }
+
+  fn flip(&mut self, f: usize, flipped: &mut [bool]) {
+    self.triangles[f].swap(1, 2);
+    flipped[f] = !flipped[f];
+  }
+
+  // makes every neighbour pass the common edge in the opposite direction
+  fn orient_neighbours(&mut self, flipped: &mut [bool]) {
+    let edges = self.edge_faces();
+    let mut visited = vec![false; self.triangles.len()];
+    let mut for_visit = Vec::new();
+    for start in 0..self.triangles.len() {
+      if visited[start] {
+        continue;
+      }
+      visited[start] = true;
+      for_visit.push(start as u32);
+      while let Some(f) = for_visit.pop() {
+        let t = self.triangles[f as usize];
+        for k in 0..3 {
+          let (v0, v1) = (t[k], t[(k + 1) % 3]);
+          let Some(faces) = edges.get(&(v0.min(v1), v0.max(v1))) else { continue };
+          let n = if faces[0] == f { faces[1] } else { faces[0] };
+          if !visited[n as usize] {
+            if self.has_directed_edge(n, v0, v1) {
+              self.flip(n as usize, flipped);
+            }
+            visited[n as usize] = true;
+            for_visit.push(n);
+          }
+        }
+      }
+    }
+  }
+
+  // turns inside out shells
+  fn orient_shells(&mut self, flipped: &mut [bool]) {
+    let mut shells = DisjointSets::new(self.triangles.len());
+    for faces in self.edge_faces().values() {
+      shells.union(faces[0] as usize, faces[1] as usize);
+    }
+    let mut volumes = FxHashMap::<usize, f32>::default();
+    for (i, t) in self.triangles.iter().enumerate() {
+      let [v0, v1, v2] = t.map(|v| self.vertices[v as usize]);
+      *volumes.entry(shells.find(i)).or_default() += dot(v0, cross(v1, v2)) / 6.0;
+    }
+    for i in 0..self.triangles.len() {
+      if volumes[&shells.find(i)] < 0.0 {
+        self.flip(i, flipped);
+      }
+    }
+  }
+
+  // closes boundary loops by a fan around their center, returns filled and skipped loops
+  fn fill_holes(&mut self, max_edges: usize) -> (usize, usize) {
+    let mut counts = FxHashMap::<Edge, usize>::default();
+    for t in &self.triangles {
+      for k in 0..3 {
+        let (v0, v1) = (t[k], t[(k + 1) % 3]);
+        *counts.entry((v0.min(v1), v0.max(v1))).or_default() += 1;
+      }
+    }
+    // loops go against boundary edges of their triangles
+    let mut next = FxHashMap::<u32, Vec<u32>>::default();
+    for t in &self.triangles {
+      for k in 0..3 {
+        let (v0, v1) = (t[k], t[(k + 1) % 3]);
+        if counts[&(v0.min(v1), v0.max(v1))] == 1 {
+          next.entry(v1).or_default().push(v0);
+        }
+      }
+    }
+
+    let mut starts: Vec<u32> = next.keys().copied().collect();
+    starts.sort();
+    let (mut filled, mut unfilled) = (0, 0);
+    for start in starts {
+      while let Some(mut v) = next.get_mut(&start).and_then(|n| n.pop()) {
+        let mut hole = vec![start];
+        while v != start {
+          hole.push(v);
+          match next.get_mut(&v).and_then(|n| n.pop()) {
+            Some(n) => v = n,
+            None => break,
+          }
+        }
+        if v != start || hole.len() > max_edges {
+          unfilled += 1;
+          continue;
+        }
+        if hole.len() == 3 {
+          self.triangles.push([hole[0], hole[1], hole[2]]);
+        } else {
+          let mut center = Point::ZERO;
+          for &v in &hole {
+            center += self.vertices[v as usize];
+          }
+          let c = self.add_vertex(center.scale(1.0 / hole.len() as f32));
+          for k in 0..hole.len() {
+            self.triangles.push([hole[k], hole[(k + 1) % hole.len()], c]);
+          }
+        }
+        filled += 1;
+      }
+    }
+    (filled, unfilled)
+  }
+}
+
+// vertices in increasing order and `true` if the face goes through them in this order
+fn face_key(t: Triangle) -> (Triangle, bool) {
+  let mut key = t;
+  key.sort();
+  let even = (0..3).any(|k| [t[k], t[(k + 1) % 3], t[(k + 2) % 3]] == key);
+  (key, even)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn repair_inside_out_cuboid() {
+    let mut test = Model::cuboid(2, 2, 2, 1.0);
+    for t in &mut test.triangles {
+      t.swap(1, 2);
+    }
+    test.triangles.pop();
+    let report = test.repair(&RepairParams::default());
+    assert_eq!(report.filled_holes, 1);
+    assert!(test.validate().is_manifold());
+    assert!((test.get_volume() - 8.0).abs() < 1.0e-4);
+  }
+
+  #[test]
+  fn opposite_faces_cancel() {
+    let mut test = Model::cuboid(2, 2, 2, 1.0);
+    let [a, b, c] = test.triangles[0];
+    test.triangles.extend([[a, b, c], [b, c, a], [c, b, a], [a, c, b], [a, c, b]]);
+    let report = test.repair(&RepairParams::default());
+    // three faces going one way cancel three opposite ones, the hole is closed again
+    assert_eq!(report.duplicate_faces, 6);
+    assert_eq!(report.filled_holes, 1);
+    assert!(test.validate().is_valid());
+  }
+
+  #[test]
+  fn cap_is_flipped() {
+    let mut test = Model::cuboid(2, 2, 2, 1.0);
+    let [a, b, c] = test.triangles[0];
+    let middle = (test.vertices[a as usize] + test.vertices[b as usize]).scale(0.5);
+    let m = test.add_vertex(middle);
+    test.triangles[0] = [a, m, c];
+    test.triangles.extend([[m, b, c], [a, b, m]]);
+    let (vertices, triangles) = (test.vertices.len(), test.triangles.len());
+
+    let report = test.repair(&RepairParams::default());
+    assert_eq!(report.degenerate_faces, 1);
+    assert_eq!(test.vertices.len(), vertices);
+    assert_eq!(test.triangles.len(), triangles);
+    assert!(test.validate().is_valid());
+    assert!((test.get_volume() - 8.0).abs() < 1.0e-4);
+  }
+}
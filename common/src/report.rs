@@ -0,0 +1,295 @@
+use crate::contour::*;
+use crate::mass::*;
+use crate::material::*;
+use crate::model::*;
+use serde::Serialize;
+use std::path::Path;
+
+/// One generated part, sizes are for a single piece
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PartReport {
+  /// model index for meshed parts, face index for flat ones
+  pub index: u32,
+  pub name: String,
+  /// path of the saved file relative to the output dir
+  pub file: String,
+  pub count: usize,
+  pub material: String,
+  /// material thickness of flat parts
+  pub thickness: Option<f32>,
+  pub volume: f32,
+  pub mass: f32,
+  /// `None` if material has no price for this kind of parts
+  pub cost: Option<f32>,
+  pub triangles: usize,
+  pub surface_area: f32,
+  pub aabb_min: [f32; 3],
+  pub aabb_max: [f32; 3],
+  pub cut_length: Option<f32>,
+  pub sheet_area: Option<f32>,
+  /// removed by hollowing, `None` for solid parts
+  pub saved_volume: Option<f32>,
+  pub saved_mass: Option<f32>,
+  pub center_of_mass: [f32; 3],
+  /// moments of inertia about principal axes, from the smallest one
+  pub principal_moments: [f32; 3],
+}
+
+/// Sums of all parts multiplied by their counts
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ReportTotals {
+  pub count: usize,
+  pub volume: f32,
+  pub mass: f32,
+  pub cost: f32,
+  pub triangles: usize,
+  pub surface_area: f32,
+  pub cut_length: f32,
+  pub sheet_area: f32,
+  pub saved_volume: f32,
+  pub saved_mass: f32,
+}
+
+/// Bill of materials of one generation run
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct BuildReport {
+  pub creator: String,
+  pub parts: Vec<PartReport>,
+  pub totals: ReportTotals,
+  /// meshed parts together in their places, `None` when there are none
+  pub assembly: Option<MassProperties>,
+}
+
+/// Flat part cut `count` times from a sheet of `material`
+pub struct FlatFace {
+  pub index: u32,
+  pub name: String,
+  pub file: String,
+  pub count: usize,
+  pub thickness: f32,
+  pub material: Material,
+}
+
+impl PartReport {
+  pub fn from_model(
+    index: u32,
+    name: String,
+    file: String,
+    material: &Material,
+    model: &Model,
+  ) -> Self {
+    let props = model.mass_properties(material.density);
+    let (min, max) = (props.aabb_min, props.aabb_max);
+    let c = props.center_of_mass;
+    Self {
+      index,
+      name,
+      file,
+      count: 1,
+      material: material.name.to_string(),
+      thickness: None,
+      volume: props.volume,
+      mass: props.mass,
+      cost: material.cost(props.mass, None),
+      triangles: model.triangles.len(),
+      surface_area: props.area,
+      aabb_min: [min.x, min.y, min.z],
+      aabb_max: [max.x, max.y, max.z],
+      cut_length: None,
+      sheet_area: None,
+      saved_volume: None,
+      saved_mass: None,
+      center_of_mass: [c.x, c.y, c.z],
+      principal_moments: props.principal_moments,
+    }
+  }
+
+  /// `extruded` is the figure extruded by the thickness of the face
+  pub fn from_flat_figure(face: FlatFace, figure: &FlatFigure, extruded: &Model) -> Self {
+    let FlatFace { index, name, file, count, thickness, material } = face;
+    let sheet_area = figure.get_square();
+    let mass = material.mass(sheet_area * thickness);
+    Self {
+      count,
+      thickness: Some(thickness),
+      volume: sheet_area * thickness,
+      mass,
+      cost: material.cost(mass, Some(sheet_area)),
+      cut_length: Some(figure.get_length()),
+      sheet_area: Some(sheet_area),
+      ..Self::from_model(index, name, file, &material, extruded)
+    }
+  }
+}
+
+impl BuildReport {
+  pub fn new(creator: &str) -> Self {
+    Self { creator: creator.to_string(), ..Default::default() }
+  }
+
+  pub fn add(&mut self, part: PartReport) {
+    let count = part.count as f32;
+    let totals = &mut self.totals;
+    totals.count += part.count;
+    totals.volume += part.volume * count;
+    totals.mass += part.mass * count;
+    totals.cost += part.cost.unwrap_or(0.0) * count;
+    totals.triangles += part.triangles * part.count;
+    totals.surface_area += part.surface_area * count;
+    totals.cut_length += part.cut_length.unwrap_or(0.0) * count;
+    totals.sheet_area += part.sheet_area.unwrap_or(0.0) * count;
+    totals.saved_volume += part.saved_volume.unwrap_or(0.0) * count;
+    totals.saved_mass += part.saved_mass.unwrap_or(0.0) * count;
+    self.parts.push(part);
+  }
+
+  pub fn save_to_json(&self, path: &Path) -> Result<(), String> {
+    let json = serde_json::to_string_pretty(self)
+      .map_err(|e| format!("Unable to serialize report: {}", e))?;
+    std::fs::write(path, json)
+      .map_err(|e| format!("Unable to write report to {}: {}", path.to_string_lossy(), e))
+  }
+
+  /// One row per part and the last row with totals
+  pub fn save_to_csv(&self, path: &Path) -> Result<(), String> {
+    let mut csv = String::from(
+      "index,name,file,count,material,thickness,volume,mass,cost,triangles,surface_area,\
+       aabb_min_x,aabb_min_y,aabb_min_z,aabb_max_x,aabb_max_y,aabb_max_z,cut_length,sheet_area,\
+       saved_volume,saved_mass,center_of_mass_x,center_of_mass_y,center_of_mass_z,\
+       principal_moment_1,principal_moment_2,principal_moment_3\n",
+    );
+    let opt = |v: Option<f32>| v.map(|v| v.to_string()).unwrap_or_default();
+    for p in &self.parts {
+      csv += &format!(
+        "{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{},{}\n",
+        p.index,
+        csv_field(&p.name),
+        csv_field(&p.file),
+        p.count,
+        csv_field(&p.material),
+        opt(p.thickness),
+        p.volume,
+        p.mass,
+        opt(p.cost),
+        p.triangles,
+        p.surface_area,
+        p.aabb_min[0],
+        p.aabb_min[1],
+        p.aabb_min[2],
+        p.aabb_max[0],
+        p.aabb_max[1],
+        p.aabb_max[2],
+        opt(p.cut_length),
+        opt(p.sheet_area),
+        opt(p.saved_volume),
+        opt(p.saved_mass),
+        p.center_of_mass[0],
+        p.center_of_mass[1],
+        p.center_of_mass[2],
+        p.principal_moments[0],
+        p.principal_moments[1],
+        p.principal_moments[2],
+      );
+    }
+    let t = &self.totals;
+    csv += &format!(
+      ",total,,{},,,{},{},{},{},{},,,,,,,{},{},{},{},,,,,,\n",
+      t.count,
+      t.volume,
+      t.mass,
+      t.cost,
+      t.triangles,
+      t.surface_area,
+      t.cut_length,
+      t.sheet_area,
+      t.saved_volume,
+      t.saved_mass
+    );
+
+    std::fs::write(path, csv)
+      .map_err(|e| format!("Unable to write report to {}: {}", path.to_string_lossy(), e))
+  }
+
+  /// Saves `report.json` and `report.csv` to `dir`
+  pub fn save(&self, dir: &Path) -> Result<(), String> {
+    self.save_to_json(&dir.join("report.json"))?;
+    self.save_to_csv(&dir.join("report.csv"))
+  }
+}
+
+fn csv_field(s: &str) -> String {
+  if s.contains([',', '"', '\n']) {
+    format!("\"{}\"", s.replace('"', "\"\""))
+  } else {
+    s.to_string()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_dir::TestDir;
+
+  #[test]
+  fn csv_field_escaping() {
+    assert_eq!(csv_field("plain name"), "plain name");
+    assert_eq!(csv_field("a,b"), "\"a,b\"");
+    assert_eq!(csv_field("6\" pipe"), "\"6\"\" pipe\"");
+    assert_eq!(csv_field("two\nlines"), "\"two\nlines\"");
+    assert_eq!(csv_field(""), "");
+  }
+
+  #[test]
+  fn totals_of_parts_with_counts() {
+    let mut report = BuildReport::new("test");
+    report.add(PartReport {
+      count: 2,
+      volume: 1.5,
+      mass: 10.0,
+      cost: Some(3.0),
+      triangles: 12,
+      surface_area: 6.0,
+      saved_volume: Some(0.5),
+      saved_mass: Some(4.0),
+      ..Default::default()
+    });
+    report.add(PartReport {
+      name: "flat, \"thin\"".to_string(),
+      count: 3,
+      volume: 2.0,
+      mass: 1.0,
+      cost: None,
+      triangles: 4,
+      surface_area: 1.0,
+      cut_length: Some(5.0),
+      sheet_area: Some(0.25),
+      ..Default::default()
+    });
+
+    let t = &report.totals;
+    assert_eq!(t.count, 5);
+    assert_eq!(t.volume, 9.0);
+    assert_eq!(t.mass, 23.0);
+    assert_eq!(t.cost, 6.0);
+    assert_eq!(t.triangles, 36);
+    assert_eq!(t.surface_area, 15.0);
+    assert_eq!(t.cut_length, 15.0);
+    assert_eq!(t.sheet_area, 0.75);
+    assert_eq!(t.saved_volume, 1.0);
+    assert_eq!(t.saved_mass, 8.0);
+
+    let dir = TestDir::new("report");
+    let path = dir.join("report.csv");
+    report.save_to_csv(&path).unwrap();
+    let csv = std::fs::read_to_string(&path).unwrap();
+    let lines: Vec<&str> = csv.lines().collect();
+    assert_eq!(lines.len(), 4);
+    assert!(lines[2].starts_with("0,\"flat, \"\"thin\"\"\",,3,"));
+    assert_eq!(lines[3], ",total,,5,,,9,23,6,36,15,,,,,,,15,0.75,1,8,,,,,,");
+    // quoted commas aside every row has the same number of columns
+    let columns = lines[0].matches(',').count();
+    assert_eq!(lines[1].matches(',').count(), columns);
+    assert_eq!(lines[2].matches(',').count(), columns + 1);
+    assert_eq!(lines[3].matches(',').count(), columns);
+  }
+}
@@ -0,0 +1,523 @@
+use crate::model::*;
+use crate::points3d::*;
+use crate::progress::*;
+use fxhash::{FxHashMap, FxHashSet};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SharpParams {
+  /// faces meeting at a smaller angle in degrees are not an edge
+  pub angle: f32,
+  /// grid step of the meshed model, vertices move less than it
+  pub cell_size: f32,
+}
+
+impl Default for SharpParams {
+  fn default() -> Self {
+    Self { angle: 30.0, cell_size: 1.0 }
+  }
+}
+
+/// What `Model::sharpen` moved
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SharpReport {
+  pub edge_vertices: usize,
+  pub corner_vertices: usize,
+  /// vertices between features moved onto faces
+  pub face_vertices: usize,
+  /// vertices next to features which would fold triangles around them
+  pub skipped: usize,
+  /// edges crossing features turned to join vertices on them
+  pub flipped_edges: usize,
+}
+
+impl SharpReport {
+  pub fn summary(&self) -> String {
+    format!(
+      "{} vertices moved to edges, {} to corners, {} to faces, {} skipped, {} edges flipped",
+      self.edge_vertices,
+      self.corner_vertices,
+      self.face_vertices,
+      self.skipped,
+      self.flipped_edges
+    )
+  }
+}
+
+// tangent plane of the surface measured near a vertex
+#[derive(Debug, Clone, Copy)]
+struct Plane {
+  point: Point,
+  normal: Point,
+}
+
+impl Plane {
+  fn offset(&self) -> f32 {
+    dot(self.normal, self.point)
+  }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Feature {
+  /// direction of the edge line
+  Edge(Point),
+  Corner,
+}
+
+// where a vertex near a feature goes, it falls back to one of `faces` if the feature is taken
+#[derive(Debug, Clone)]
+struct Target {
+  point: Point,
+  feature: Feature,
+  faces: Vec<Plane>,
+}
+
+impl Model {
+  /// Moves vertices of grid chamfers onto edges and corners of the surface where `inside`
+  /// changes, like dual contouring does, and the rest of them onto faces around.
+  /// It is a post-pass over the already marched mesh, not a feature-preserving extraction mode,
+  /// it only moves vertices and keeps triangles, so features missed by the grid are not recovered.
+  /// Returns vertices which are on features now, `smooth_features` leaves them in place
+  pub fn sharpen(
+    &mut self,
+    inside: &dyn Fn(Point) -> bool,
+    params: &SharpParams,
+    progress: &dyn Progress,
+  ) -> (Vec<bool>, SharpReport) {
+    let mut report = SharpReport::default();
+    if params.cell_size <= 0.0 {
+      return (vec![false; self.vertices.len()], report);
+    }
+    let mut around = vec![Vec::<u32>::new(); self.vertices.len()];
+    for (i, t) in self.triangles.iter().enumerate() {
+      for &v in t {
+        around[v as usize].push(i as u32);
+      }
+    }
+    let normal_of = |vertices: &[Point], t: Triangle| {
+      let [v0, v1, v2] = t.map(|v| vertices[v as usize]);
+      cross(v1 - v0, v2 - v0)
+    };
+    let mut normals = vec![Point::ZERO; self.vertices.len()];
+    for &t in &self.triangles {
+      let n = normal_of(&self.vertices, t);
+      for v in t {
+        normals[v as usize] += n;
+      }
+    }
+
+    // triangles around vertices near features turn more than on curved faces
+    let cos_half = (params.angle.to_radians() * 0.5).cos();
+    let candidates: Vec<usize> = (0..self.vertices.len())
+      .filter(|&v| {
+        let normal = normals[v].norm();
+        around[v].iter().any(|&t| {
+          let n = normal_of(&self.vertices, self.triangles[t as usize]);
+          dot(n.norm(), normal) < cos_half
+        })
+      })
+      .collect();
+    progress.log(&format!("{} vertices may be near sharp features", candidates.len()));
+
+    let mut planes = vec![None; self.vertices.len()];
+    let mut measured = vec![false; self.vertices.len()];
+    let mut targets = Vec::new();
+    let start = std::time::Instant::now();
+    for (i, &v) in candidates.iter().enumerate() {
+      if i % 1000 == 0 || i + 1 == candidates.len() {
+        progress.progress("checked vertices", i + 1, candidates.len(), start.elapsed());
+      }
+      // two rings of neighbours have enough vertices far from features to measure faces
+      let mut ring = vec![v];
+      for _ in 0..2 {
+        ring = ring
+          .iter()
+          .flat_map(|&u| &around[u])
+          .flat_map(|&t| self.triangles[t as usize])
+          .map(|u| u as usize)
+          .collect();
+        ring.sort();
+        ring.dedup();
+      }
+      for &u in &ring {
+        if !measured[u] {
+          measured[u] = true;
+          planes[u] = tangent_plane(inside, self.vertices[u], normals[u], params.cell_size);
+        }
+      }
+      let ring_planes: Vec<Plane> = ring.iter().filter_map(|&u| planes[u]).collect();
+      if let Some(target) = feature_target(self.vertices[v], &ring_planes, params) {
+        targets.push((v, target));
+      }
+    }
+
+    // corners and then the closest vertices take features first,
+    // moving the rest would collapse triangles
+    let distance = |(v, target): &(usize, Target)| (target.point - self.vertices[*v]).sqr_len();
+    targets.sort_by(|t1, t2| {
+      let is_edge = |t: &(usize, Target)| matches!(t.1.feature, Feature::Edge(_));
+      is_edge(t1).cmp(&is_edge(t2)).then(distance(t1).total_cmp(&distance(t2)))
+    });
+    let folds = |vertices: &[Point], t: Triangle, n: Point| {
+      let [v0, v1, v2] = t.map(|v| vertices[v as usize]);
+      let max_edge = (v1 - v0).sqr_len().max((v2 - v1).sqr_len()).max((v0 - v2).sqr_len());
+      dot(n, cross(v1 - v0, v2 - v0)) <= max_edge * n.len() * 1.0e-3
+    };
+    // triangles should not turn over comparing to the mesh before all moves
+    let before: Vec<Point> = self.triangles.iter().map(|&t| normal_of(&self.vertices, t)).collect();
+    let move_to = |vertices: &mut Vec<Point>, v: usize, p: Point| {
+      let old = vertices[v];
+      vertices[v] = p;
+      let fold =
+        around[v].iter().any(|&t| folds(vertices, self.triangles[t as usize], before[t as usize]));
+      if fold {
+        vertices[v] = old;
+      }
+      !fold
+    };
+    // a vertex may fit after its neighbours have moved
+    let original = self.vertices.clone();
+    let mut moved_to = vec![None; self.vertices.len()];
+    let mut projected = vec![false; self.vertices.len()];
+    let mut moved = true;
+    while moved {
+      moved = false;
+      targets.retain(|(v, target)| {
+        if !move_to(&mut self.vertices, *v, target.point) {
+          return true;
+        }
+        moved_to[*v] = Some(target.feature);
+        moved = true;
+        false
+      });
+    }
+    // the rest are between features and vertices on them
+    targets.retain(|(v, target)| {
+      let p = self.vertices[*v];
+      let mut projections: Vec<Point> =
+        target.faces.iter().map(|f| p - f.normal.scale(dot(f.normal, p) - f.offset())).collect();
+      projections.sort_by(|p1, p2| (*p1 - p).sqr_len().total_cmp(&(*p2 - p).sqr_len()));
+      projected[*v] = projections
+        .into_iter()
+        .filter(|&x| (x - p).len() <= params.cell_size)
+        .any(|x| move_to(&mut self.vertices, *v, x));
+      !projected[*v]
+    });
+    report.skipped = targets.len();
+
+    // grid edges still cut features off between moved vertices
+    let on_feature = |vertices: &[Point], v1: u32, v2: u32| {
+      let [p1, p2] = [v1, v2].map(|v| vertices[v as usize]);
+      let along = |feature: Option<Feature>, d: Point| match feature {
+        Some(Feature::Edge(dir)) => cross(dir, d).len() <= 0.01 * params.cell_size,
+        _ => false,
+      };
+      along(moved_to[v1 as usize], p2 - p1) || along(moved_to[v2 as usize], p1 - p2)
+    };
+    let mut edges = FxHashMap::<(u32, u32), usize>::default();
+    for (i, t) in self.triangles.iter().enumerate() {
+      for k in 0..3 {
+        edges.insert((t[k], t[(k + 1) % 3]), i);
+      }
+    }
+    // indices of both triangles and what they were
+    let mut flips: Vec<([usize; 2], [Triangle; 2])> = Vec::new();
+    let mut flipped = true;
+    while flipped {
+      flipped = false;
+      for i in 0..self.triangles.len() {
+        for k in 0..3 {
+          let t = self.triangles[i];
+          let (a, b, c) = (t[k], t[(k + 1) % 3], t[(k + 2) % 3]);
+          let Some(&j) = edges.get(&(b, a)) else {
+            continue;
+          };
+          let other = self.triangles[j];
+          let d = other.into_iter().find(|&v| v != a && v != b).unwrap();
+          if on_feature(&self.vertices, a, b)
+            || !on_feature(&self.vertices, c, d)
+            || edges.contains_key(&(c, d))
+            || edges.contains_key(&(d, c))
+          {
+            continue;
+          }
+          let n = normal_of(&self.vertices, t) + normal_of(&self.vertices, other);
+          let (t1, t2) = ([c, a, d], [d, b, c]);
+          if folds(&self.vertices, t1, n) || folds(&self.vertices, t2, n) {
+            continue;
+          }
+          for t in [t, other] {
+            for k in 0..3 {
+              edges.remove(&(t[k], t[(k + 1) % 3]));
+            }
+          }
+          for (t, index) in [(t1, i), (t2, j)] {
+            self.triangles[index] = t;
+            for k in 0..3 {
+              edges.insert((t[k], t[(k + 1) % 3]), index);
+            }
+          }
+          flips.push(([i, j], [t, other]));
+          flipped = true;
+        }
+      }
+    }
+
+    // moves are checked only against triangles around, near thin walls and gaps
+    // vertices may go through other parts of the surface
+    loop {
+      let crossing = self.validate().self_intersections;
+      let mut undone: FxHashSet<usize> =
+        crossing.iter().flat_map(|&(t1, t2)| [t1 as usize, t2 as usize]).collect();
+      // later flips of the same triangles are undone too
+      let mut grown = true;
+      while grown {
+        grown = false;
+        for ([i, j], _) in &flips {
+          if undone.contains(i) != undone.contains(j) {
+            undone.extend([*i, *j]);
+            grown = true;
+          }
+        }
+      }
+      let crossed: Vec<u32> = crossing
+        .iter()
+        .flat_map(|&(t1, t2)| [t1, t2])
+        .flat_map(|t| self.triangles[t as usize])
+        .collect();
+      let mut reverted = false;
+      while let Some(index) = flips.iter().rposition(|([i, _], _)| undone.contains(i)) {
+        let ([i, j], [t1, t2]) = flips.remove(index);
+        self.triangles[i] = t1;
+        self.triangles[j] = t2;
+        reverted = true;
+      }
+      for v in crossed {
+        let v = v as usize;
+        if moved_to[v].is_some() || projected[v] {
+          self.vertices[v] = original[v];
+          moved_to[v] = None;
+          projected[v] = false;
+          report.skipped += 1;
+          reverted = true;
+        }
+      }
+      if !reverted {
+        break;
+      }
+    }
+    report.flipped_edges = flips.len();
+    for v in 0..self.vertices.len() {
+      match moved_to[v] {
+        Some(Feature::Edge(_)) => report.edge_vertices += 1,
+        Some(Feature::Corner) => report.corner_vertices += 1,
+        None => report.face_vertices += projected[v] as usize,
+      }
+    }
+    let features = moved_to.iter().map(Option::is_some).collect();
+    (features, report)
+  }
+
+  /// `smooth_keeping` repeated `steps` times, free vertices pulled between fixed ones may go
+  /// through the surface, then fixed vertices around them are let go and smoothing starts over.
+  /// Returns the number of vertices let go
+  pub fn smooth_features(
+    &mut self,
+    delta: f32,
+    steps: usize,
+    fixed: &mut [bool],
+    progress: &dyn Progress,
+  ) -> usize {
+    let original = self.vertices.clone();
+    let mut released = 0;
+    loop {
+      let start = std::time::Instant::now();
+      for i in 0..steps {
+        self.smooth_keeping(delta, fixed);
+        progress.progress("smoothed", i + 1, steps, start.elapsed());
+      }
+      let crossing = self.validate().self_intersections;
+      let crossed: FxHashSet<u32> = crossing
+        .iter()
+        .flat_map(|&(t1, t2)| [t1, t2])
+        .flat_map(|t| self.triangles[t as usize])
+        .collect();
+      let mut let_go = 0;
+      for t in &self.triangles {
+        if t.iter().any(|v| crossed.contains(v)) {
+          for &v in t {
+            if fixed[v as usize] {
+              fixed[v as usize] = false;
+              let_go += 1;
+            }
+          }
+        }
+      }
+      if let_go == 0 {
+        return released;
+      }
+      released += let_go;
+      self.vertices.copy_from_slice(&original);
+    }
+  }
+}
+
+// plane through the surface points found along `normal` at `p` and at points around it,
+// `None` if the surface is not flat there or is too thin to cross
+fn tangent_plane(
+  inside: &dyn Fn(Point) -> bool,
+  p: Point,
+  normal: Point,
+  cell: f32,
+) -> Option<Plane> {
+  if normal.sqr_len() == 0.0 {
+    return None;
+  }
+  let normal = normal.norm();
+  let t1 = normal.any_perp().norm();
+  let t2 = cross(normal, t1);
+  let eps = cell * 0.1;
+  let surface = |q: Point| {
+    let (mut from, mut to) = (q - normal.scale(cell * 0.5), q + normal.scale(cell * 0.5));
+    if !inside(from) || inside(to) {
+      return None;
+    }
+    for _ in 0..20 {
+      let middle = (from + to).scale(0.5);
+      if inside(middle) {
+        from = middle;
+      } else {
+        to = middle;
+      }
+    }
+    Some((from + to).scale(0.5))
+  };
+  let q0 = surface(p)?;
+  let q1 = surface(p + t1.scale(eps))?;
+  let q2 = surface(p + t2.scale(eps))?;
+  let q3 = surface(p - (t1 + t2).scale(eps))?;
+  let n = cross(q1 - q0, q2 - q0);
+  if n.sqr_len() == 0.0 {
+    return None;
+  }
+  let n = n.norm();
+  // the fourth point is on the other side of an edge
+  (dot(n, q3 - q0).abs() <= eps * 0.05).then_some(Plane { point: q0, normal: n })
+}
+
+// intersection of the planes of two or three faces closest to `p`,
+// faces are groups of planes turning less than half of `params.angle`
+fn feature_target(p: Point, planes: &[Plane], params: &SharpParams) -> Option<Target> {
+  let cos_half = (params.angle.to_radians() * 0.5).cos();
+  let mut faces: Vec<(Point, Vec<Plane>)> = Vec::new();
+  for &plane in planes {
+    match faces.iter_mut().find(|(n, _)| dot(n.norm(), plane.normal) >= cos_half) {
+      Some((n, group)) => {
+        *n += plane.normal;
+        group.push(plane);
+      }
+      None => faces.push((plane.normal, vec![plane])),
+    }
+  }
+  // points of a face which are not on one plane are a curved surface
+  let mut faces: Vec<Plane> = faces
+    .into_iter()
+    .map(|(n, group)| {
+      let mut point = Point::ZERO;
+      for plane in &group {
+        point += plane.point;
+      }
+      let face = Plane { point: point.scale(1.0 / group.len() as f32), normal: n.norm() };
+      let flat = group
+        .iter()
+        .all(|g| dot(face.normal, g.point - face.point).abs() <= 0.05 * params.cell_size);
+      flat.then_some(face)
+    })
+    .collect::<Option<_>>()?;
+
+  let cos_feature = params.angle.to_radians().cos();
+  let mut corner: Option<(f32, Point)> = None;
+  for i in 0..faces.len() {
+    for j in i + 1..faces.len() {
+      for k in j + 1..faces.len() {
+        let [a, b, c] = [faces[i], faces[j], faces[k]];
+        let det = dot(a.normal, cross(b.normal, c.normal));
+        if det.abs() > 0.1 && corner.is_none_or(|(d, _)| det.abs() > d) {
+          let x = (cross(b.normal, c.normal).scale(a.offset())
+            + cross(c.normal, a.normal).scale(b.offset())
+            + cross(a.normal, b.normal).scale(c.offset()))
+          .scale(det.recip());
+          corner = Some((det.abs(), x));
+        }
+      }
+    }
+  }
+  let result = match corner {
+    Some((_, x)) => (x, Feature::Corner),
+    None => {
+      let mut edge: Option<(f32, Point, Point)> = None;
+      for i in 0..faces.len() {
+        for j in i + 1..faces.len() {
+          let [a, b] = [faces[i], faces[j]];
+          let c = dot(a.normal, b.normal);
+          if c > cos_feature || edge.is_some_and(|(e, _, _)| e <= c) {
+            continue;
+          }
+          // the closest to `p` point on both planes
+          let (r1, r2) = (a.offset() - dot(a.normal, p), b.offset() - dot(b.normal, p));
+          let det = 1.0 - c * c;
+          let x = p + a.normal.scale((r1 - c * r2) / det) + b.normal.scale((r2 - c * r1) / det);
+          edge = Some((c, x, cross(a.normal, b.normal).norm()));
+        }
+      }
+      let (_, x, dir) = edge?;
+      (x, Feature::Edge(dir))
+    }
+  };
+  let (point, feature) = result;
+  ((point - p).len() <= params.cell_size).then_some(Target { point, feature, faces })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::solid::ModelCreator;
+
+  #[test]
+  fn sharpen_turned_box() {
+    let half = Point { x: 4.3, y: 3.7, z: 2.9 };
+    let angle = 0.3f32;
+    let to_box = |p: Point| p.rotate(Point { x: 0.0, y: 0.0, z: 1.0 }, -angle);
+    let inside = |p: Point| {
+      let q = to_box(p);
+      q.x.abs() < half.x && q.y.abs() < half.y && q.z.abs() < half.z
+    };
+    let part_f = |p: Point| if inside(p) { 1 } else { 0 };
+    let mut mc = ModelCreator::new(16, 20.0, 20, 0, &part_f);
+    while !mc.finished() {
+      mc.fill_next_layer(&part_f, &SilentProgress);
+    }
+    let mut model = mc.get_models().remove(&1).unwrap();
+    let volume = 8.0 * half.x * half.y * half.z;
+    let rounded_error = (model.get_volume() - volume).abs();
+    let intersections = model.validate().self_intersections.len();
+    let params = SharpParams { angle: 30.0, cell_size: 20.0 / 14.5 };
+    let (mut features, report) = model.sharpen(&inside, &params, &SilentProgress);
+    let validation = model.validate();
+    // grid vertices on flat faces have small overlaps already
+    assert!(validation.is_manifold() && validation.degenerate_triangles.is_empty());
+    assert!(validation.self_intersections.len() <= intersections);
+    assert!(report.corner_vertices >= 8 && report.edge_vertices > 0);
+    assert!((model.get_volume() - volume).abs() < 0.2 * rounded_error);
+    model.smooth_features(0.1, 5, &mut features, &SilentProgress);
+    assert!(model.validate().is_valid());
+    // every corner of the box is still there
+    for k in 0..8 {
+      let sign = |bit: usize| if k & bit == 0 { -1.0 } else { 1.0 };
+      let corner = Point { x: sign(1) * half.x, y: sign(2) * half.y, z: sign(4) * half.z };
+      let corner = corner.rotate(Point { x: 0.0, y: 0.0, z: 1.0 }, angle);
+      let closest = model.vertices.iter().map(|&v| (v - corner).len()).fold(f32::MAX, f32::min);
+      assert!(closest < 0.01, "{closest}");
+    }
+  }
+}
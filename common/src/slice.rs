@@ -0,0 +1,144 @@
+use crate::contour::*;
+use crate::model::*;
+use crate::points2d;
+use crate::points3d::*;
+use crate::validation::Edge;
+use fxhash::FxHashMap;
+
+/// Cutting plane with 2d axes on it, the normal is `cross(x_axis, y_axis)`
+#[derive(Debug, Clone, Copy)]
+pub struct Plane {
+  pub origin: Point,
+  pub x_axis: Point,
+  pub y_axis: Point,
+}
+
+impl Plane {
+  pub fn new(origin: Point, normal: Point) -> Self {
+    let normal = normal.norm();
+    let x_axis = normal.any_perp().norm();
+    Self { origin, x_axis, y_axis: cross(normal, x_axis) }
+  }
+
+  /// Horizontal plane keeping x and y of points
+  pub fn at_z(z: f32) -> Self {
+    Self { origin: Point { x: 0.0, y: 0.0, z }, x_axis: Point::X, y_axis: Point::Y }
+  }
+
+  pub fn normal(&self) -> Point {
+    cross(self.x_axis, self.y_axis)
+  }
+
+  pub fn project(&self, p: Point) -> points2d::Point {
+    let d = p - self.origin;
+    points2d::Point { x: dot(d, self.x_axis), y: dot(d, self.y_axis) }
+  }
+}
+
+impl Model {
+  /// Cross section seen from the side the normal of the plane points to, holes are clockwise
+  /// if the model is closed and has positive volume. Vertices lying on the plane are counted
+  /// as being above it, so faces in the plane itself give nothing
+  pub fn slice(&self, plane: &Plane) -> FlatFigure {
+    let normal = plane.normal();
+    let above: Vec<f32> = self.vertices.iter().map(|&p| dot(p - plane.origin, normal)).collect();
+    let crossing = |(a, b): Edge| {
+      if a == b {
+        return plane.project(self.vertices[a as usize]);
+      }
+      let (da, db) = (above[a as usize], above[b as usize]);
+      let (pa, pb) = (self.vertices[a as usize], self.vertices[b as usize]);
+      plane.project(pa + (pb - pa).scale(da / (da - db)))
+    };
+
+    // segments go counterclockwise around the solid, so each crossed edge starts one segment
+    // and ends another one
+    let mut points = FxHashMap::<Edge, points2d::Point>::default();
+    let mut next = FxHashMap::<Edge, Edge>::default();
+    for t in &self.triangles {
+      let mut ends = Vec::with_capacity(2);
+      for k in 0..3 {
+        let (a, b) = (t[k], t[(k + 1) % 3]);
+        if (above[a as usize] >= 0.0) != (above[b as usize] >= 0.0) {
+          // all edges going down from a vertex on the plane cross it in this vertex
+          let e = match (above[a as usize] == 0.0, above[b as usize] == 0.0) {
+            (true, _) => (a, a),
+            (_, true) => (b, b),
+            _ => (a.min(b), a.max(b)),
+          };
+          points.entry(e).or_insert_with(|| crossing(e));
+          ends.push(e);
+        }
+      }
+      if let [e0, e1] = ends[..] {
+        if e0 == e1 {
+          continue;
+        }
+        let (p0, p1) = (points[&e0], points[&e1]);
+        let direction = cross(normal, self.get_perp(*t));
+        let d = plane.project(plane.origin + direction);
+        if points2d::dot(p1 - p0, d) >= 0.0 {
+          next.insert(e0, e1);
+        } else {
+          next.insert(e1, e0);
+        }
+      }
+    }
+
+    let mut starts: Vec<Edge> = next.keys().copied().collect();
+    starts.sort();
+    let mut contours = Vec::new();
+    for start in starts {
+      let Some(mut e) = next.remove(&start) else { continue };
+      let mut contour = Contour { points: vec![points[&start]] };
+      // open meshes give chains which are closed by a straight line
+      while e != start {
+        contour.points.push(points[&e]);
+        match next.remove(&e) {
+          Some(n) => e = n,
+          None => break,
+        }
+      }
+      if contour.points.len() >= 3 {
+        contours.push(contour);
+      }
+    }
+    FlatFigure::from_contours(contours)
+  }
+
+  /// Horizontal cross sections at every height
+  pub fn slice_at_heights(&self, heights: &[f32]) -> Vec<FlatFigure> {
+    heights.iter().map(|&z| self.slice(&Plane::at_z(z))).collect()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::boolean::BooleanOp;
+
+  #[test]
+  fn slice_cuboid_through_vertices() {
+    let test = Model::cuboid(2, 2, 2, 1.0);
+    for figure in test.slice_at_heights(&[0.5, 1.0]) {
+      assert_eq!(figure.contours().len(), 1);
+      assert!((figure.get_square() - 4.0).abs() < 1.0e-4);
+    }
+  }
+
+  #[test]
+  fn slice_through_coplanar_faces() {
+    // step with its lower top face at z = 1
+    let mut lower = Model::cuboid(2, 2, 2, 1.0);
+    lower.map_points(|p| Point { x: p.x + 2.0, y: p.y, z: p.z * 0.5 });
+    let test = Model::cuboid(2, 2, 2, 1.0).boolean(&lower, BooleanOp::Union);
+    assert!(test.validate().is_valid(), "{}", test.validate().summary());
+
+    // faces in the plane are above it, so the section is the one just below the plane
+    for (z, contours, square) in [(0.0, 0, 0.0), (1.0, 1, 8.0), (2.0, 1, 4.0)] {
+      let figure = test.slice(&Plane::at_z(z));
+      assert_eq!(figure.contours().len(), contours, "z = {z}");
+      assert!((figure.get_square() - square).abs() < 1.0e-4, "z = {z}");
+    }
+  }
+}
@@ -1,10 +1,21 @@
 use crate::model::*;
 use crate::points3d::*;
+use crate::progress::*;
 use fxhash::{FxHashMap, FxHashSet};
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
 
 pub type PartIndex = u32;
 const BAD_INDEX: PartIndex = 0xFFFFFFFF;
 
+/// Signed distance from a point to the surface of one part, negative inside of the part,
+/// `gradient` is the direction where the distance grows the fastest if it is known
+#[derive(Debug, Clone, Copy)]
+pub struct PartDistance {
+  pub distance: f32,
+  pub gradient: Option<Point>,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct SolidCell {
   index: PartIndex,
@@ -66,6 +77,20 @@ pub struct SolidCell {
   w_qpp: u32,
 }
 
+// passes names of all intermediate points of `SolidCell` to `$apply`
+macro_rules! cell_points {
+  ($apply: ident) => {
+    $apply!(
+      v_pzz, v_mzz, v_zpz, v_zmz, v_zzp, v_zzm, v_mmm, v_mmp, v_mpm, v_mpp, v_pmm, v_pmp, v_ppm,
+      v_ppp, w_mmz, w_mpz, w_pmz, w_ppz, w_mzm, w_mzp, w_pzm, w_pzp, w_zmm, w_zmp, w_zpm, w_zpp,
+      w_mmn, w_mnm, w_nmm, w_mmq, w_mnp, w_nmp, w_mpn, w_mqm, w_npm, w_mpq, w_mqp, w_npp, w_pmn,
+      w_pnm, w_qmm, w_pmq, w_pnp, w_qmp, w_ppn, w_pqm, w_qpm, w_ppq, w_pqp, w_qpp
+    )
+  };
+}
+
+const CELL_POINTS: usize = 50;
+
 impl SolidCell {
   pub fn new() -> Self {
     Self {
@@ -123,7 +148,188 @@ impl SolidCell {
       w_qpp: BAD_INDEX,
     }
   }
+
+  // intermediate points are indices of vertices in the model of `index`
+  fn points(&self) -> [u32; CELL_POINTS] {
+    macro_rules! list {
+      ($($field: ident),*) => { [$(self.$field),*] };
+    }
+    cell_points!(list)
+  }
+
+  fn map_points(&mut self, mut f: impl FnMut(u32) -> u32) {
+    macro_rules! map {
+      ($($field: ident),*) => {
+        $(if self.$field != BAD_INDEX {
+          self.$field = f(self.$field);
+        })*
+      };
+    }
+    cell_points!(map);
+  }
+}
+
+/// Box meshed by `ModelCreator` with its own number of grid points along every axis,
+/// layers of the grid are filled one after another along `sweep` axis
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MeshDomain {
+  pub min: Point,
+  pub max: Point,
+  /// grid points along x, y and z
+  pub points: [usize; 3],
+  /// 0, 1 or 2 for x, y or z
+  pub sweep: usize,
+}
+
+impl MeshDomain {
+  /// Cube around zero with `size` points along every side, layers go along z
+  pub fn cube(size: usize, solid_size: f32) -> Self {
+    let half = solid_size * 0.5;
+    Self {
+      min: Point { x: -half, y: -half, z: -half },
+      max: Point { x: half, y: half, z: half },
+      points: [size; 3],
+      sweep: 2,
+    }
+  }
+
+  /// Box around `min` and `max` with grid steps `cell` along x, y and z,
+  /// it grows to fit a whole number of steps, layers go along the longest side
+  pub fn with_cell_size(min: Point, max: Point, cell: Point) -> Self {
+    let [min, max, cell] = [min, max, cell].map(|p| [p.x, p.y, p.z]);
+    let mut points = [0; 3];
+    let mut bounds = [[0.0; 3]; 2];
+    for axis in 0..3 {
+      let extent = (max[axis] - min[axis]).max(0.0);
+      points[axis] = (extent / cell[axis]).ceil() as usize + 2;
+      // same spacing as `SolidLayer::get_coord`
+      let half = cell[axis] * (points[axis] as f32 - 1.5) * 0.5;
+      let center = (min[axis] + max[axis]) * 0.5;
+      bounds[0][axis] = center - half;
+      bounds[1][axis] = center + half;
+    }
+    let [min, max] = bounds.map(|[x, y, z]| Point { x, y, z });
+    let sweep = (0..3).max_by_key(|&axis| points[axis]).unwrap();
+    Self { min, max, points, sweep }
+  }
+
+  /// Same box with layers going along `sweep` axis
+  pub fn with_sweep(self, sweep: usize) -> Self {
+    Self { sweep: sweep.min(2), ..self }
+  }
+
+  /// Distance between grid points along `axis`
+  pub fn step(&self, axis: usize) -> f32 {
+    let (min, max) = self.bounds(axis);
+    (max - min) / (self.points[axis] as f32 - 1.5)
+  }
+
+  /// Boxes of parts found at every `coarse`-th point of the grid along every axis,
+  /// they are grown by the coarse step, so parts thinner than it may be missed
+  pub fn part_boxes(
+    &self,
+    coarse: usize,
+    part_f: &dyn Fn(Point) -> PartIndex,
+  ) -> FxHashMap<PartIndex, (Point, Point)> {
+    let coarse = coarse.max(1);
+    // the last point is always taken to see parts at the far side
+    let indices = |axis: usize| {
+      let last = self.points[axis].saturating_sub(1);
+      (0..last).step_by(coarse).chain([last]).collect::<Vec<_>>()
+    };
+    let [xs, ys, zs] = [0, 1, 2].map(indices);
+    let mut boxes = FxHashMap::<PartIndex, (Point, Point)>::default();
+    for &z in &zs {
+      for &y in &ys {
+        for &x in &xs {
+          let p = Point {
+            x: self.coord(0, x, true),
+            y: self.coord(1, y, true),
+            z: self.coord(2, z, true),
+          };
+          let m_index = part_f(p);
+          if m_index == 0 {
+            continue;
+          }
+          let (min, max) = boxes.entry(m_index).or_insert((p, p));
+          *min = Point { x: min.x.min(p.x), y: min.y.min(p.y), z: min.z.min(p.z) };
+          *max = Point { x: max.x.max(p.x), y: max.y.max(p.y), z: max.z.max(p.z) };
+        }
+      }
+    }
+    let [dx, dy, dz] = [0, 1, 2].map(|axis| self.step(axis) * coarse as f32);
+    let grow = Point { x: dx, y: dy, z: dz };
+    for (min, max) in boxes.values_mut() {
+      (*min, *max) = (*min - grow, *max + grow);
+    }
+    boxes
+  }
+
+  /// Smallest part of the grid covering the box from `min` to `max`, it has the same points
+  /// there and one more layer of them around, so parts inside of the box are meshed the same way
+  pub fn sub_domain(&self, min: Point, max: Point) -> Self {
+    let mut result = self.clone();
+    for axis in 0..3 {
+      let coord = |p: Point| [p.x, p.y, p.z][axis];
+      let step = self.step(axis);
+      let origin = self.bounds(axis).0;
+      let last = self.points[axis].saturating_sub(1);
+      let index = |c: f32| ((c - origin) / step).clamp(0.0, last as f32);
+      let from = (index(coord(min)).floor() as usize).saturating_sub(1);
+      let to = (index(coord(max)).ceil() as usize + 1).min(last).max(from + 1);
+      let points = to - from + 1;
+      let low = origin + step * from as f32;
+      let high = low + step * (points as f32 - 1.5);
+      set_coord(&mut result.min, axis, low);
+      set_coord(&mut result.max, axis, high);
+      result.points[axis] = points;
+    }
+    result
+  }
+
+  /// Steps and offsets of grid points from zero along x, y and z,
+  /// they are the same for all boxes of the same grid, e.g. for every `sub_domain`
+  pub fn lattice(&self) -> [(f32, f32); 3] {
+    [0, 1, 2].map(|axis| {
+      let step = self.step(axis);
+      let first = self.coord(axis, 0, false);
+      (step, first - step * (first / step).round())
+    })
+  }
+
+  // axes of rows and columns of layers, they go around after `sweep` to keep the orientation
+  fn layer_axes(&self) -> (usize, usize) {
+    ((self.sweep + 1) % 3, (self.sweep + 2) % 3)
+  }
+
+  fn layer_size(&self) -> (usize, usize) {
+    let (u, v) = self.layer_axes();
+    (self.points[u], self.points[v])
+  }
+
+  fn layers(&self) -> usize {
+    self.points[self.sweep]
+  }
+
+  fn bounds(&self, axis: usize) -> (f32, f32) {
+    let coord = |p: Point| [p.x, p.y, p.z][axis];
+    (coord(self.min), coord(self.max))
+  }
+
+  fn coord(&self, axis: usize, i: usize, odd: bool) -> f32 {
+    let (min, max) = self.bounds(axis);
+    (min + max) * 0.5 + SolidLayer::get_coord(self.points[axis], max - min, i, odd)
+  }
+}
+
+fn set_coord(p: &mut Point, axis: usize, value: f32) {
+  match axis {
+    0 => p.x = value,
+    1 => p.y = value,
+    _ => p.z = value,
+  }
 }
+
 #[derive(Default)]
 pub struct SolidLayer {
   cells: Vec<SolidCell>,
@@ -136,23 +342,24 @@ impl SolidLayer {
     x as f32 * scale + shift
   }
 
+  /// Layer `w` of the domain
   pub fn filled(
-    size: usize,
-    solid_size: f32,
-    z: f32,
+    domain: &MeshDomain,
+    w: usize,
     odd: bool,
     part_f: &dyn Fn(Point) -> PartIndex,
   ) -> Self {
-    let mut cells = vec![SolidCell::new(); size * size];
+    let (width, height) = domain.layer_size();
+    let (u, v) = domain.layer_axes();
+    let mut cells = vec![SolidCell::new(); width * height];
+    let mut pos = Point::ZERO;
+    set_coord(&mut pos, domain.sweep, domain.coord(domain.sweep, w, odd));
     let mut idx = 0;
 
-    for y in 0..size {
-      for x in 0..size {
-        let pos = Point {
-          x: Self::get_coord(size, solid_size, x, odd),
-          y: Self::get_coord(size, solid_size, y, odd),
-          z,
-        };
+    for y in 0..height {
+      for x in 0..width {
+        set_coord(&mut pos, u, domain.coord(u, x, odd));
+        set_coord(&mut pos, v, domain.coord(v, y, odd));
         cells[idx].index = part_f(pos);
         cells[idx].pos = pos;
         idx += 1;
@@ -162,33 +369,104 @@ impl SolidLayer {
     Self { cells }
   }
 
-  pub fn lift(&self, z: f32, part_f: &dyn Fn(Point) -> PartIndex) -> Self {
+  /// Same cells moved to `coord` along `sweep` axis
+  pub fn lift(&self, sweep: usize, coord: f32, part_f: &dyn Fn(Point) -> PartIndex) -> Self {
     let mut cells = vec![SolidCell::new(); self.cells.len()];
 
-    for i in 0..cells.len() {
-      cells[i].pos = self.cells[i].pos;
-      cells[i].pos.z = z;
-      cells[i].index = part_f(cells[i].pos);
+    for (cell, old) in cells.iter_mut().zip(&self.cells) {
+      cell.pos = old.pos;
+      set_coord(&mut cell.pos, sweep, coord);
+      cell.index = part_f(cell.pos);
     }
 
     Self { cells }
   }
+
+  pub fn lift_parallel(
+    &self,
+    sweep: usize,
+    coord: f32,
+    part_f: &(dyn Fn(Point) -> PartIndex + Sync),
+    threads: usize,
+  ) -> Self {
+    let mut cells = vec![SolidCell::new(); self.cells.len()];
+    let chunk = std::cmp::max(1, cells.len().div_ceil(threads));
+
+    std::thread::scope(|s| {
+      for (dst, src) in cells.chunks_mut(chunk).zip(self.cells.chunks(chunk)) {
+        s.spawn(move || {
+          for (d, s) in dst.iter_mut().zip(src) {
+            d.pos = s.pos;
+            set_coord(&mut d.pos, sweep, coord);
+            d.index = part_f(d.pos);
+          }
+        });
+      }
+    });
+
+    Self { cells }
+  }
 }
 
-pub struct ModelCreator {
-  size: usize,
-  solid_size: f32,
+#[derive(Clone, Copy)]
+struct LayerParams {
+  width: usize,
+  last_odd: bool,
+}
+
+// functions of parts and limits of root searches along edges and across faces of cells
+#[derive(Clone, Copy)]
+struct SurfaceSearch<'a> {
+  part_f: &'a dyn Fn(Point) -> PartIndex,
+  distance_f: &'a dyn Fn(Point, PartIndex) -> Option<PartDistance>,
+  tries: usize,
+  tries_t: usize,
+}
+
+#[derive(Default)]
+struct MeshState {
   got_points: usize,
   got_edges: usize,
   models: FxHashMap<PartIndex, Model>,
+  used_numbers: Vec<PartIndex>,
+  skipped: FxHashSet<PartIndex>,
+  // `None` fills all models
+  selected: Option<FxHashSet<PartIndex>>,
+}
+
+impl MeshState {
+  fn fills(&self, m_index: PartIndex) -> bool {
+    !self.skipped.contains(&m_index) && self.selected.as_ref().is_none_or(|s| s.contains(&m_index))
+  }
+}
+
+// rows of a layer meshed by one thread into its own models
+struct ChunkMesh {
+  // copies of layers starting from the first row of the chunk
+  pl: Vec<SolidCell>,
+  cl: Vec<SolidCell>,
+  nl: Vec<SolidCell>,
+  mesh: MeshState,
+  // models start with these vertices of the whole models, they are used by cells of the chunk
+  imported: FxHashMap<PartIndex, Vec<u32>>,
+  // numbers of vertices and triangles, points and edges after the first row
+  first_row: FxHashMap<PartIndex, (usize, usize)>,
+  first_row_counts: (usize, usize),
+  // the second row of layers after the first one
+  second_row: [Vec<SolidCell>; 3],
+}
+
+pub struct ModelCreator {
+  domain: MeshDomain,
+  mesh: MeshState,
   prev_layer: SolidLayer,
   cur_layer: SolidLayer,
   next_layer: SolidLayer,
-  used_numbers: Vec<PartIndex>,
   last_z: usize,
   last_odd: bool,
   tries: usize,
   tries_t: usize,
+  started: Instant,
 }
 
 impl ModelCreator {
@@ -198,21 +476,29 @@ impl ModelCreator {
     tries: usize,
     tries_t: usize,
     part_f: &dyn Fn(Point) -> PartIndex,
+  ) -> Self {
+    Self::with_domain(MeshDomain::cube(size, solid_size), tries, tries_t, part_f)
+  }
+
+  /// Meshes the box of `domain`, time and memory depend on numbers of its points,
+  /// layers hold points of the two axes other than `domain.sweep`
+  pub fn with_domain(
+    domain: MeshDomain,
+    tries: usize,
+    tries_t: usize,
+    part_f: &dyn Fn(Point) -> PartIndex,
   ) -> Self {
     let mut result = Self {
-      size,
-      solid_size,
-      got_points: 0,
-      got_edges: 0,
-      models: FxHashMap::default(),
+      domain,
+      mesh: MeshState::default(),
       prev_layer: SolidLayer::default(),
       cur_layer: SolidLayer::default(),
       next_layer: SolidLayer::default(),
-      used_numbers: Vec::new(),
       last_z: 0,
       last_odd: true,
       tries,
       tries_t,
+      started: Instant::now(),
     };
 
     result.cur_layer = result.filled_layer(0, false, part_f);
@@ -221,37 +507,53 @@ impl ModelCreator {
     result
   }
 
+  pub fn domain(&self) -> &MeshDomain {
+    &self.domain
+  }
+
   pub fn got_points(&self) -> usize {
-    self.got_points
+    self.mesh.got_points
   }
 
   pub fn got_edges(&self) -> usize {
-    self.got_edges
+    self.mesh.got_edges
+  }
+
+  /// Models which are not filled, e.g. because they are taken from cache
+  pub fn skip_models(&mut self, models: impl IntoIterator<Item = PartIndex>) {
+    self.mesh.skipped.extend(models);
+  }
+
+  pub fn skipped_models(&self) -> &FxHashSet<PartIndex> {
+    &self.mesh.skipped
+  }
+
+  /// Only these models are filled, surfaces of other parts are neither found nor stored,
+  /// `domain` may cover only them, see `MeshDomain::part_boxes`
+  pub fn select_models(&mut self, models: impl IntoIterator<Item = PartIndex>) {
+    self.mesh.selected.get_or_insert_with(FxHashSet::default).extend(models);
+  }
+
+  /// `None` if all models are filled
+  pub fn selected_models(&self) -> Option<&FxHashSet<PartIndex>> {
+    self.mesh.selected.as_ref()
   }
 
   pub fn get_models(self) -> FxHashMap<PartIndex, Model> {
-    self.models
+    self.mesh.models
   }
 
   fn filled_layer(&self, z: usize, odd: bool, part_f: &dyn Fn(Point) -> PartIndex) -> SolidLayer {
-    SolidLayer::filled(
-      self.size,
-      self.solid_size,
-      SolidLayer::get_coord(self.size, self.solid_size, z, odd),
-      odd,
-      part_f,
-    )
+    SolidLayer::filled(&self.domain, z, odd, part_f)
   }
 
   pub fn finished(&self) -> bool {
-    self.last_z == self.size - 1 && self.last_odd
+    self.last_z == self.domain.layers() - 1 && self.last_odd
   }
 
   fn fill_tetrahedron(
     model: &mut Model,
-    part_f: &dyn Fn(Point) -> PartIndex,
-    tries: usize,
-    tries_t: usize,
+    search: SurfaceSearch,
     model_index: PartIndex,
     (c0, c0_inside, e01, e02, e03, p012, p023, p031): (
       Point,
@@ -294,6 +596,9 @@ impl ModelCreator {
       &mut u32,
     ),
   ) {
+    let SurfaceSearch { part_f, distance_f, tries, tries_t } = search;
+    let edge_root =
+      |inside, outside| surface_root(part_f, distance_f, inside, outside, model_index, tries);
     let root_t = |mut p1b: Point,
                   mut p2b: Point,
                   mut mb: Point,
@@ -301,10 +606,13 @@ impl ModelCreator {
                   mut p2e: Point,
                   mut me: Point|
      -> Point {
+      if let Some(p) = face_root(distance_f, model_index, tries, [p1b, p2b, mb, p1e, p2e, me]) {
+        return p;
+      }
       let mut p1m = (p1b + p1e).scale(0.5);
       let mut p2m = (p2b + p2e).scale(0.5);
       if tries_t == 0 {
-        return find_root(part_f, p1m, p2m, model_index, tries);
+        return edge_root(p1m, p2m);
       }
 
       let mut i = 0;
@@ -312,10 +620,10 @@ impl ModelCreator {
         i += 1;
         let p1mb = (p1b + p1m).scale(0.5);
         let p2mb = (p2b + p2m).scale(0.5);
-        let mb1 = find_root(part_f, p1mb, p2mb, model_index, tries);
+        let mb1 = edge_root(p1mb, p2mb);
         let p1me = (p1m + p1e).scale(0.5);
         let p2me = (p2m + p2e).scale(0.5);
-        let me2 = find_root(part_f, p1me, p2me, model_index, tries);
+        let me2 = edge_root(p1me, p2me);
         if cross(mb1 - mb, mb1 - me).sqr_len() > cross(me2 - mb, me2 - me).sqr_len() {
           if i >= tries_t {
             return mb1;
@@ -352,13 +660,13 @@ impl ModelCreator {
               e2: &mut u32,
               p20: &mut u32| {
       if *e0 == BAD_INDEX {
-        *e0 = model.add_vertex(find_root(part_f, corner, c0, model_index, tries));
+        *e0 = model.add_vertex(edge_root(corner, c0));
       }
       if *e1 == BAD_INDEX {
-        *e1 = model.add_vertex(find_root(part_f, corner, c1, model_index, tries));
+        *e1 = model.add_vertex(edge_root(corner, c1));
       }
       if *e2 == BAD_INDEX {
-        *e2 = model.add_vertex(find_root(part_f, corner, c2, model_index, tries));
+        *e2 = model.add_vertex(edge_root(corner, c2));
       }
       if *p01 == BAD_INDEX {
         *p01 = model.add_vertex(root_t(
@@ -409,13 +717,13 @@ impl ModelCreator {
               e2: &mut u32,
               p20: &mut u32| {
       if *e0 == BAD_INDEX {
-        *e0 = model.add_vertex(find_root(part_f, c0, corner, model_index, tries));
+        *e0 = model.add_vertex(edge_root(c0, corner));
       }
       if *e1 == BAD_INDEX {
-        *e1 = model.add_vertex(find_root(part_f, c1, corner, model_index, tries));
+        *e1 = model.add_vertex(edge_root(c1, corner));
       }
       if *e2 == BAD_INDEX {
-        *e2 = model.add_vertex(find_root(part_f, c2, corner, model_index, tries));
+        *e2 = model.add_vertex(edge_root(c2, corner));
       }
       if *p01 == BAD_INDEX {
         *p01 = model.add_vertex(root_t(
@@ -468,16 +776,16 @@ impl ModelCreator {
              e3: &mut u32,
              p30: &mut u32| {
       if *e0 == BAD_INDEX {
-        *e0 = model.add_vertex(find_root(part_f, c0, c1, model_index, tries));
+        *e0 = model.add_vertex(edge_root(c0, c1));
       }
       if *e1 == BAD_INDEX {
-        *e1 = model.add_vertex(find_root(part_f, c2, c1, model_index, tries));
+        *e1 = model.add_vertex(edge_root(c2, c1));
       }
       if *e2 == BAD_INDEX {
-        *e2 = model.add_vertex(find_root(part_f, c2, c3, model_index, tries));
+        *e2 = model.add_vertex(edge_root(c2, c3));
       }
       if *e3 == BAD_INDEX {
-        *e3 = model.add_vertex(find_root(part_f, c0, c3, model_index, tries));
+        *e3 = model.add_vertex(edge_root(c0, c3));
       }
       if *p01 == BAD_INDEX {
         *p01 = model.add_vertex(root_t(
@@ -650,38 +958,75 @@ impl ModelCreator {
     }
   }
 
-  fn use_layers(&mut self, part_f: &dyn Fn(Point) -> PartIndex) {
-    if self.size == 0 {
+  fn layer_params(&self) -> LayerParams {
+    LayerParams { width: self.domain.layer_size().0, last_odd: self.last_odd }
+  }
+
+  fn surface_search<'a>(
+    &self,
+    part_f: &'a dyn Fn(Point) -> PartIndex,
+    distance_f: &'a dyn Fn(Point, PartIndex) -> Option<PartDistance>,
+  ) -> SurfaceSearch<'a> {
+    SurfaceSearch { part_f, distance_f, tries: self.tries, tries_t: self.tries_t }
+  }
+
+  fn use_layers(
+    &mut self,
+    part_f: &dyn Fn(Point) -> PartIndex,
+    distance_f: &dyn Fn(Point, PartIndex) -> Option<PartDistance>,
+  ) {
+    let (width, height) = self.domain.layer_size();
+    if width == 0 || height == 0 {
       return;
     }
-    let pl = self.prev_layer.cells.as_mut_slice();
-    let cl = self.cur_layer.cells.as_mut_slice();
-    let nl = self.next_layer.cells.as_mut_slice();
+    let search = self.surface_search(part_f, distance_f);
+    Self::use_rows(
+      self.layer_params(),
+      &mut self.mesh,
+      &mut self.prev_layer.cells,
+      &mut self.cur_layer.cells,
+      &mut self.next_layer.cells,
+      height - 1,
+      search,
+    );
+  }
 
-    let next_shift = self.last_odd as usize;
+  // layers contain `rows + 1` rows of cells starting from the first processed one
+  fn use_rows(
+    params: LayerParams,
+    mesh: &mut MeshState,
+    pl: &mut [SolidCell],
+    cl: &mut [SolidCell],
+    nl: &mut [SolidCell],
+    rows: usize,
+    search: SurfaceSearch,
+  ) {
+    let width = params.width;
+    let last_odd = params.last_odd;
+    let next_shift = last_odd as usize;
 
-    for y in 0..self.size - 1 {
-      for x in 0..self.size - 1 {
-        self.got_points += 1;
-        let c = y * self.size + x;
+    for y in 0..rows {
+      for x in 0..width - 1 {
+        mesh.got_points += 1;
+        let c = y * width + x;
         let cx = c + 1;
-        let cy = c + self.size;
-        let cxy = c + self.size + 1;
-        let npc = if self.last_odd { c } else { cxy };
+        let cy = c + width;
+        let cxy = c + width + 1;
+        let npc = if last_odd { c } else { cxy };
 
-        let h1cur = if self.last_odd { cy } else { c };
+        let h1cur = if last_odd { cy } else { c };
         let h2cur = h1cur + 1;
-        let v1next = if self.last_odd { c } else { cx };
-        let v2next = v1next + self.size;
+        let v1next = if last_odd { c } else { cx };
+        let v2next = v1next + width;
 
-        let v1cur = if self.last_odd { cx } else { c };
-        let v2cur = v1cur + self.size;
-        let h1next = if self.last_odd { c } else { cy };
+        let v1cur = if last_odd { cx } else { c };
+        let v2cur = v1cur + width;
+        let h1next = if last_odd { c } else { cy };
         let h2next = h1next + 1;
 
         let corner_index = pl[npc].index;
 
-        self.got_edges += (corner_index != cl[c].index) as usize
+        mesh.got_edges += (corner_index != cl[c].index) as usize
           + (corner_index != cl[cx].index    ) as usize
           + (corner_index != cl[cy].index    ) as usize
           + (corner_index != cl[cxy].index   ) as usize
@@ -709,10 +1054,10 @@ impl ModelCreator {
           || corner_index != nl[v1next].index
           || corner_index != nl[v2next].index
         {
-          self.used_numbers.clear();
+          mesh.used_numbers.clear();
           let mut use_number = |i| {
-            if i != 0 && !self.used_numbers.contains(&i) {
-              self.used_numbers.push(i);
+            if i != 0 && !mesh.used_numbers.contains(&i) {
+              mesh.used_numbers.push(i);
             }
           };
 
@@ -731,8 +1076,11 @@ impl ModelCreator {
           use_number(nl[v1next].index);
           use_number(nl[v2next].index);
 
-          for &model_index in &self.used_numbers {
-            let model = self.models.entry(model_index).or_insert(Model::new());
+          for &model_index in &mesh.used_numbers {
+            if !mesh.fills(model_index) {
+              continue;
+            }
+            let model = mesh.models.entry(model_index).or_default();
 
             macro_rules! vertex {
               ($l: expr, $c: expr, $e0: ident, $e1: ident, $e2: ident, $p01: ident, $p12: ident, $p20: ident) => {
@@ -751,9 +1099,7 @@ impl ModelCreator {
 
             Self::fill_tetrahedron(
               model,
-              part_f,
-              self.tries,
-              self.tries_t,
+              search,
               model_index,
               vertex!(pl, npc, v_mmp, v_pmp, v_zzp, w_zmp, w_pmq, w_mmq),
               vertex!(cl, c, v_ppm, v_pzz, v_ppp, w_qpm, w_qpp, w_ppz),
@@ -763,9 +1109,7 @@ impl ModelCreator {
 
             Self::fill_tetrahedron(
               model,
-              part_f,
-              self.tries,
-              self.tries_t,
+              search,
               model_index,
               vertex!(pl, npc, v_pmp, v_ppp, v_zzp, w_pzp, w_ppq, w_pmq),
               vertex!(cl, cx, v_mpm, v_zpz, v_mpp, w_mqm, w_mqp, w_mpz),
@@ -775,9 +1119,7 @@ impl ModelCreator {
 
             Self::fill_tetrahedron(
               model,
-              part_f,
-              self.tries,
-              self.tries_t,
+              search,
               model_index,
               vertex!(pl, npc, v_ppp, v_mpp, v_zzp, w_zpp, w_mpq, w_ppq),
               vertex!(cl, cxy, v_mmm, v_mzz, v_mmp, w_nmm, w_nmp, w_mmz),
@@ -787,9 +1129,7 @@ impl ModelCreator {
 
             Self::fill_tetrahedron(
               model,
-              part_f,
-              self.tries,
-              self.tries_t,
+              search,
               model_index,
               vertex!(pl, npc, v_mpp, v_mmp, v_zzp, w_mzp, w_mmq, w_mpq),
               vertex!(cl, cy, v_pmm, v_zmz, v_pmp, w_pnm, w_pnp, w_pmz),
@@ -799,9 +1139,7 @@ impl ModelCreator {
 
             Self::fill_tetrahedron(
               model,
-              part_f,
-              self.tries,
-              self.tries_t,
+              search,
               model_index,
               vertex!(cl, h1cur, v_pzz, v_ppp, v_pmp, w_qpp, w_pzp, w_qmp),
               vertex!(cl, h2cur, v_mzz, v_mpp, v_mmp, w_npp, w_mzp, w_nmp),
@@ -811,9 +1149,7 @@ impl ModelCreator {
 
             Self::fill_tetrahedron(
               model,
-              part_f,
-              self.tries,
-              self.tries_t,
+              search,
               model_index,
               vertex!(cl, v1cur, v_zpz, v_mpp, v_ppp, w_mqp, w_zpp, w_pqp),
               vertex!(cl, v2cur, v_zmz, v_mmp, v_pmp, w_mnp, w_zmp, w_pnp),
@@ -826,7 +1162,137 @@ impl ModelCreator {
     }
   }
 
-  pub fn fill_next_layer(&mut self, part_f: &dyn Fn(Point) -> PartIndex) {
+  // meshes the rows `y..y + rows` of copied layers into models of the chunk, the first row
+  // is filled without points which the previous chunk adds to its cells, so it is only used
+  // to find points of the second row and is filled again by `merge_chunk`
+  fn mesh_chunk(
+    &self,
+    y: usize,
+    rows: usize,
+    part_f: &dyn Fn(Point) -> PartIndex,
+    distance_f: &dyn Fn(Point, PartIndex) -> Option<PartDistance>,
+  ) -> ChunkMesh {
+    let params = self.layer_params();
+    let search = self.surface_search(part_f, distance_f);
+    let width = params.width;
+    let cells = y * width..(y + rows + 1) * width;
+    let mut mesh = MeshState {
+      skipped: self.mesh.skipped.clone(),
+      selected: self.mesh.selected.clone(),
+      ..Default::default()
+    };
+
+    let mut imported: FxHashMap<PartIndex, Vec<u32>> = FxHashMap::default();
+    let mut local = FxHashMap::default();
+    let mut copy = |layer: &SolidLayer| -> Vec<SolidCell> {
+      let mut cells = layer.cells[cells.clone()].to_vec();
+      for c in &mut cells {
+        let m_index = c.index;
+        c.map_points(|v| {
+          *local.entry((m_index, v)).or_insert_with(|| {
+            let model = mesh.models.entry(m_index).or_default();
+            imported.entry(m_index).or_default().push(v);
+            model.add_vertex(self.mesh.models[&m_index].vertices[v as usize])
+          })
+        });
+      }
+      cells
+    };
+    let mut pl = copy(&self.prev_layer);
+    let mut cl = copy(&self.cur_layer);
+    let mut nl = copy(&self.next_layer);
+
+    let two_rows = ..2 * width;
+    let (p, c, n) = (&mut pl[two_rows], &mut cl[two_rows], &mut nl[two_rows]);
+    Self::use_rows(params, &mut mesh, p, c, n, 1, search);
+    let first_row =
+      mesh.models.iter().map(|(&i, m)| (i, (m.vertices.len(), m.triangles.len()))).collect();
+    let first_row_counts = (mesh.got_points, mesh.got_edges);
+    let second_row = [&pl, &cl, &nl].map(|l| l[width..2 * width].to_vec());
+
+    let rest = width..;
+    let (p, c, n) = (&mut pl[rest.clone()], &mut cl[rest.clone()], &mut nl[rest]);
+    Self::use_rows(params, &mut mesh, p, c, n, rows - 1, search);
+
+    ChunkMesh { pl, cl, nl, mesh, imported, first_row, first_row_counts, second_row }
+  }
+
+  // fills the first row of the chunk `y..` with the points of the previous chunk,
+  // then adds the other rows of the chunk with vertices of its models renumbered
+  fn merge_chunk(
+    &mut self,
+    y: usize,
+    chunk: ChunkMesh,
+    part_f: &dyn Fn(Point) -> PartIndex,
+    distance_f: &dyn Fn(Point, PartIndex) -> Option<PartDistance>,
+  ) {
+    let params = self.layer_params();
+    let search = self.surface_search(part_f, distance_f);
+    let width = params.width;
+    let two_rows = y * width..(y + 2) * width;
+    Self::use_rows(
+      params,
+      &mut self.mesh,
+      &mut self.prev_layer.cells[two_rows.clone()],
+      &mut self.cur_layer.cells[two_rows.clone()],
+      &mut self.next_layer.cells[two_rows],
+      1,
+      search,
+    );
+
+    // vertices of the first row in the chunk are the same as just found ones
+    // if they are used by the second row, others are not used by next rows
+    let mut ids: FxHashMap<PartIndex, Vec<u32>> = FxHashMap::default();
+    for (&m_index, model) in &chunk.mesh.models {
+      let mut m_ids = vec![BAD_INDEX; model.vertices.len()];
+      if let Some(imported) = chunk.imported.get(&m_index) {
+        m_ids[..imported.len()].copy_from_slice(imported);
+      }
+      ids.insert(m_index, m_ids);
+    }
+    let layers = [&self.prev_layer, &self.cur_layer, &self.next_layer];
+    for (copied, layer) in chunk.second_row.iter().zip(layers) {
+      let row = &layer.cells[(y + 1) * width..(y + 2) * width];
+      for (c, found) in copied.iter().zip(row) {
+        for (v, found_v) in c.points().into_iter().zip(found.points()) {
+          if v != BAD_INDEX {
+            ids.get_mut(&c.index).unwrap()[v as usize] = found_v;
+          }
+        }
+      }
+    }
+
+    for (m_index, model) in chunk.mesh.models {
+      let (first_vertices, first_triangles) =
+        chunk.first_row.get(&m_index).copied().unwrap_or((0, 0));
+      let m_ids = ids.get_mut(&m_index).unwrap();
+      let merged = self.mesh.models.entry(m_index).or_default();
+      for (i, v) in model.vertices.into_iter().enumerate().skip(first_vertices) {
+        m_ids[i] = merged.add_vertex(v);
+      }
+      for t in &model.triangles[first_triangles..] {
+        merged.triangles.push(t.map(|v| m_ids[v as usize]));
+      }
+    }
+
+    let rows = chunk.pl.len() / width - 1;
+    let cells = (y + 1) * width..(y + rows + 1) * width;
+    let layers = [&mut self.prev_layer, &mut self.cur_layer, &mut self.next_layer];
+    for (copied, layer) in [chunk.pl, chunk.cl, chunk.nl].into_iter().zip(layers) {
+      let dst = &mut layer.cells[cells.clone()];
+      for (dst, mut c) in dst.iter_mut().zip(copied.into_iter().skip(width)) {
+        if let Some(m_ids) = ids.get(&c.index) {
+          c.map_points(|v| m_ids[v as usize]);
+        }
+        *dst = c;
+      }
+    }
+
+    self.mesh.got_points += chunk.mesh.got_points - chunk.first_row_counts.0;
+    self.mesh.got_edges += chunk.mesh.got_edges - chunk.first_row_counts.1;
+  }
+
+  fn lift_next_layer(&mut self) -> f32 {
     self.prev_layer = std::mem::take(&mut self.cur_layer);
     self.cur_layer = std::mem::take(&mut self.next_layer);
     if self.last_odd {
@@ -836,10 +1302,602 @@ impl ModelCreator {
       self.last_odd = true;
     }
 
-    let z = SolidLayer::get_coord(self.size, self.solid_size, self.last_z, self.last_odd);
-    self.next_layer = self.prev_layer.lift(z, part_f);
-    self.use_layers(part_f);
+    self.domain.coord(self.domain.sweep, self.last_z, self.last_odd)
+  }
+
+  fn report_layer(&self, progress: &dyn Progress) {
+    let total = self.domain.layers().saturating_sub(1);
+    progress.progress("processed layers", self.last_z, total, self.started.elapsed());
+  }
+
+  pub fn fill_next_layer(&mut self, part_f: &dyn Fn(Point) -> PartIndex, progress: &dyn Progress) {
+    self.fill_next_layer_exact(part_f, &|_, _| None, progress);
+  }
+
+  /// Same as `fill_next_layer`, but vertices are placed by `distance_f` of the part they belong to
+  /// where it is known: on edges of cells in a few steps instead of `tries` calls of `part_f`,
+  /// and inside of faces where tangents of the surface meet, so curved surfaces are followed
+  /// closer with the same cells. `distance_f` should change its sign where `part_f` changes the part
+  pub fn fill_next_layer_exact(
+    &mut self,
+    part_f: &dyn Fn(Point) -> PartIndex,
+    distance_f: &dyn Fn(Point, PartIndex) -> Option<PartDistance>,
+    progress: &dyn Progress,
+  ) {
+    let z = self.lift_next_layer();
+    self.next_layer = self.prev_layer.lift(self.domain.sweep, z, part_f);
+    self.use_layers(part_f, distance_f);
+
+    self.report_layer(progress);
+  }
+
+  /// Same as `fill_next_layer` with rows of the layer split between `threads` threads,
+  /// every thread meshes its rows into its own models, which are merged in order of rows.
+  /// Models are exactly the same as serial ones if `part_f` always returns the same index
+  /// for the same point, only the first row of every thread is filled twice.
+  pub fn fill_next_layer_parallel(
+    &mut self,
+    part_f: &(dyn Fn(Point) -> PartIndex + Sync),
+    threads: usize,
+    progress: &dyn Progress,
+  ) {
+    self.fill_next_layer_parallel_exact(part_f, &|_, _| None, threads, progress);
+  }
+
+  /// `fill_next_layer_exact` with rows filled by `threads` threads
+  pub fn fill_next_layer_parallel_exact(
+    &mut self,
+    part_f: &(dyn Fn(Point) -> PartIndex + Sync),
+    distance_f: &(dyn Fn(Point, PartIndex) -> Option<PartDistance> + Sync),
+    threads: usize,
+    progress: &dyn Progress,
+  ) {
+    let z = self.lift_next_layer();
+    self.next_layer = self.prev_layer.lift_parallel(self.domain.sweep, z, part_f, threads);
+    let (width, height) = self.domain.layer_size();
+    if width > 0 && height > 0 {
+      let rows = height - 1;
+      let chunk = std::cmp::max(1, rows.div_ceil(threads));
+      let chunks = std::thread::scope(|s| {
+        let this = &*self;
+        let handles: Vec<_> = (0..rows)
+          .step_by(chunk)
+          .map(|y| {
+            let chunk_rows = std::cmp::min(chunk, rows - y);
+            (y, s.spawn(move || this.mesh_chunk(y, chunk_rows, part_f, distance_f)))
+          })
+          .collect();
+        handles.into_iter().map(|(y, h)| (y, h.join().unwrap())).collect::<Vec<_>>()
+      });
+      for (y, chunk) in chunks {
+        self.merge_chunk(y, chunk, part_f, distance_f);
+      }
+    }
+
+    self.report_layer(progress);
+  }
+}
+
+// vertex of `target` surface between `inside` and `outside` points, found by regula falsi on
+// the distance if it is known and by bisection with `part_f` otherwise
+pub(crate) fn surface_root(
+  part_f: &dyn Fn(Point) -> PartIndex,
+  distance_f: &dyn Fn(Point, PartIndex) -> Option<PartDistance>,
+  inside: Point,
+  outside: Point,
+  target: PartIndex,
+  tries: usize,
+) -> Point {
+  let bisection = || find_root(part_f, inside, outside, target, tries);
+  let (Some(d1), Some(d2)) = (distance_f(inside, target), distance_f(outside, target)) else {
+    return bisection();
+  };
+  let (mut p1, mut d1, mut p2, mut d2) = (inside, d1.distance, outside, d2.distance);
+  if !(d1 <= 0.0 && d2 > 0.0) {
+    return bisection();
+  }
+  let tolerance = (outside - inside).len() * 1.0e-6;
+  // weight of the end kept twice in a row is halved (Illinois method), so both ends move
+  let mut last_inside = None;
+  for _ in 0..tries {
+    let p = p1 + (p2 - p1).scale(d1 / (d1 - d2));
+    let Some(d) = distance_f(p, target) else {
+      return bisection();
+    };
+    if d.distance.abs() <= tolerance {
+      return p;
+    }
+    let inside = d.distance <= 0.0;
+    if inside {
+      (p1, d1) = (p, d.distance);
+      if last_inside == Some(true) {
+        d2 *= 0.5;
+      }
+    } else {
+      (p2, d2) = (p, d.distance);
+      if last_inside == Some(false) {
+        d1 *= 0.5;
+      }
+    }
+    last_inside = Some(inside);
+  }
+  p1 + (p2 - p1).scale(d1 / (d1 - d2))
+}
+
+// vertex of `target` surface inside of a face between segments `p1b p2b` and `p1e p2e`
+// from inside to outside points with vertices `mb` and `me` on them. It is where tangents
+// of the surface at `mb` and `me` meet moved onto the surface, `None` without gradients
+// or if the point leaves the face
+fn face_root(
+  distance_f: &dyn Fn(Point, PartIndex) -> Option<PartDistance>,
+  target: PartIndex,
+  tries: usize,
+  [p1b, p2b, mb, p1e, p2e, me]: [Point; 6],
+) -> Option<Point> {
+  let n = cross(p2b - p1b, p2e - p1e).norm();
+  let gb = distance_f(mb, target)?.gradient?;
+  let ge = distance_f(me, target)?.gradient?;
+  let (tb, te) = (cross(gb, n), cross(ge, n));
+  let chord = me - mb;
+  let middle = (mb + me).scale(0.5);
+  let denom = dot(cross(tb, te), n);
+  let mut p = if denom.abs() > tb.len() * te.len() * 1.0e-3 {
+    mb + tb.scale(dot(cross(chord, te), n) / denom)
+  } else {
+    middle
+  };
+  // tangents of nearly flat surfaces meet too far, parallel ones give NaN
+  let offset = (p - middle).len();
+  if offset.is_nan() || offset > chord.len() {
+    p = middle;
+  }
+
+  let tolerance = chord.len() * 1.0e-6;
+  let mut distance = f32::MAX;
+  for _ in 0..tries {
+    let d = distance_f(p, target)?;
+    distance = d.distance;
+    if distance.abs() <= tolerance {
+      break;
+    }
+    let g = d.gradient?;
+    let g = g - n.scale(dot(g, n));
+    if g.sqr_len() == 0.0 {
+      return None;
+    }
+    p -= g.scale(distance / g.sqr_len());
+  }
+
+  // the face is a triangle, both segments start or end at the same point
+  let triangle = if (p1b - p1e).sqr_len() == 0.0 { [p1b, p2b, p2e] } else { [p2b, p1b, p1e] };
+  let side = |a: Point, b: Point| dot(cross(b - a, p - a), n);
+  let sides = [
+    side(triangle[0], triangle[1]),
+    side(triangle[1], triangle[2]),
+    side(triangle[2], triangle[0]),
+  ];
+  let inside_face = sides.iter().all(|&s| s >= 0.0) || sides.iter().all(|&s| s <= 0.0);
+  (distance.abs() <= chord.len() * 1.0e-3 && inside_face).then_some(p)
+}
+
+const CHECKPOINT_MAGIC: &[u8; 4] = b"MCK3";
+
+// little endian data of checkpoints
+#[derive(Default)]
+struct CheckpointData {
+  data: Vec<u8>,
+  pos: usize,
+}
+
+impl CheckpointData {
+  fn put_u32(&mut self, v: u32) {
+    self.data.extend_from_slice(&v.to_le_bytes());
+  }
+
+  fn put_u64(&mut self, v: u64) {
+    self.data.extend_from_slice(&v.to_le_bytes());
+  }
+
+  fn put_point(&mut self, p: Point) {
+    self.put_u32(p.x.to_bits());
+    self.put_u32(p.y.to_bits());
+    self.put_u32(p.z.to_bits());
+  }
+
+  fn put_str(&mut self, s: &str) {
+    self.put_u64(s.len() as u64);
+    self.data.extend_from_slice(s.as_bytes());
+  }
+
+  fn put_cell(&mut self, c: &SolidCell) {
+    self.put_u32(c.index);
+    self.put_point(c.pos);
+    macro_rules! put {
+      ($($field: ident),*) => { $(self.put_u32(c.$field);)* };
+    }
+    cell_points!(put);
+  }
+
+  fn put_layer(&mut self, layer: &SolidLayer) {
+    self.put_u64(layer.cells.len() as u64);
+    for c in &layer.cells {
+      self.put_cell(c);
+    }
+  }
+
+  fn get_bytes(&mut self, len: usize) -> Result<&[u8], String> {
+    let bytes = self.data.get(self.pos..self.pos + len).ok_or("checkpoint is truncated")?;
+    self.pos += len;
+    Ok(bytes)
+  }
+
+  fn get_u32(&mut self) -> Result<u32, String> {
+    Ok(u32::from_le_bytes(self.get_bytes(4)?.try_into().unwrap()))
+  }
+
+  fn get_u64(&mut self) -> Result<u64, String> {
+    Ok(u64::from_le_bytes(self.get_bytes(8)?.try_into().unwrap()))
+  }
+
+  fn get_len(&mut self) -> Result<usize, String> {
+    let len = self.get_u64()? as usize;
+    // every item takes at least a byte
+    if len > self.data.len() - self.pos {
+      return Err("checkpoint is truncated".to_string());
+    }
+    Ok(len)
+  }
+
+  fn get_point(&mut self) -> Result<Point, String> {
+    let x = f32::from_bits(self.get_u32()?);
+    let y = f32::from_bits(self.get_u32()?);
+    let z = f32::from_bits(self.get_u32()?);
+    Ok(Point { x, y, z })
+  }
+
+  fn get_str(&mut self) -> Result<String, String> {
+    let len = self.get_len()?;
+    String::from_utf8(self.get_bytes(len)?.to_vec()).map_err(|e| e.to_string())
+  }
+
+  fn get_cell(&mut self) -> Result<SolidCell, String> {
+    let mut c = SolidCell::new();
+    c.index = self.get_u32()?;
+    c.pos = self.get_point()?;
+    macro_rules! get {
+      ($($field: ident),*) => { $(c.$field = self.get_u32()?;)* };
+    }
+    cell_points!(get);
+    Ok(c)
+  }
 
-    print!("\rprocessed [{}/{}] layers", self.last_z, self.size);
+  fn get_layer(&mut self) -> Result<SolidLayer, String> {
+    let len = self.get_len()?;
+    let cells = (0..len).map(|_| self.get_cell()).collect::<Result<_, _>>()?;
+    Ok(SolidLayer { cells })
+  }
+}
+
+impl ModelCreator {
+  /// Saves the whole state to continue meshing from the current layer with `resume`,
+  /// `key` identifies inputs of the run, the checkpoint is not resumed with other ones.
+  /// The file is replaced only after the new state is written completely.
+  pub fn save_checkpoint(&self, path: &std::path::Path, key: &str) -> Result<(), String> {
+    let mut d = CheckpointData::default();
+    d.data.extend_from_slice(CHECKPOINT_MAGIC);
+    d.put_str(key);
+    d.put_point(self.domain.min);
+    d.put_point(self.domain.max);
+    self.domain.points.iter().for_each(|&n| d.put_u64(n as u64));
+    d.put_u64(self.domain.sweep as u64);
+    d.put_u64(self.tries as u64);
+    d.put_u64(self.tries_t as u64);
+    d.put_u64(self.last_z as u64);
+    d.put_u32(self.last_odd as u32);
+    d.put_u64(self.mesh.got_points as u64);
+    d.put_u64(self.mesh.got_edges as u64);
+
+    // sorted to write the same file for the same state
+    let mut indices: Vec<_> = self.mesh.models.keys().copied().collect();
+    indices.sort();
+    d.put_u64(indices.len() as u64);
+    for m_index in indices {
+      let m = &self.mesh.models[&m_index];
+      d.put_u32(m_index);
+      d.put_u64(m.vertices.len() as u64);
+      for v in &m.vertices {
+        d.put_point(*v);
+      }
+      d.put_u64(m.triangles.len() as u64);
+      for t in &m.triangles {
+        t.iter().for_each(|&v| d.put_u32(v));
+      }
+    }
+
+    let mut skipped: Vec<_> = self.mesh.skipped.iter().copied().collect();
+    skipped.sort();
+    d.put_u64(skipped.len() as u64);
+    skipped.into_iter().for_each(|m_index| d.put_u32(m_index));
+    d.put_u32(self.mesh.selected.is_some() as u32);
+    let mut selected: Vec<_> = self.mesh.selected.iter().flatten().copied().collect();
+    selected.sort();
+    d.put_u64(selected.len() as u64);
+    selected.into_iter().for_each(|m_index| d.put_u32(m_index));
+
+    d.put_layer(&self.prev_layer);
+    d.put_layer(&self.cur_layer);
+    d.put_layer(&self.next_layer);
+
+    let tmp_path = path.with_extension("tmp");
+    std::fs::write(&tmp_path, &d.data)
+      .and_then(|_| std::fs::rename(&tmp_path, path))
+      .map_err(|e| format!("Unable to write checkpoint to {}: {}", path.to_string_lossy(), e))
+  }
+
+  /// Restores meshing saved by `save_checkpoint` with the same `key`
+  pub fn resume(path: &std::path::Path, key: &str) -> Result<Self, String> {
+    let data = std::fs::read(path)
+      .map_err(|e| format!("Unable to read checkpoint {}: {}", path.to_string_lossy(), e))?;
+    let mut d = CheckpointData { data, pos: 0 };
+    let map_err = |e: String| format!("Bad checkpoint {}: {}", path.to_string_lossy(), e);
+
+    if d.get_bytes(CHECKPOINT_MAGIC.len()).map_err(map_err)? != CHECKPOINT_MAGIC {
+      return Err(map_err("unknown format".to_string()));
+    }
+    if d.get_str().map_err(map_err)? != key {
+      return Err(format!("Checkpoint {} is made for other inputs", path.to_string_lossy()));
+    }
+    Self::read_checkpoint(&mut d).map_err(map_err)
+  }
+
+  fn read_checkpoint(d: &mut CheckpointData) -> Result<Self, String> {
+    let (min, max) = (d.get_point()?, d.get_point()?);
+    let points = [d.get_u64()? as usize, d.get_u64()? as usize, d.get_u64()? as usize];
+    let sweep = d.get_u64()? as usize;
+    if sweep > 2 {
+      return Err("wrong sweep axis".to_string());
+    }
+    let domain = MeshDomain { min, max, points, sweep };
+    let (width, height) = domain.layer_size();
+    let tries = d.get_u64()? as usize;
+    let tries_t = d.get_u64()? as usize;
+    let last_z = d.get_u64()? as usize;
+    let last_odd = d.get_u32()? != 0;
+
+    let got_points = d.get_u64()? as usize;
+    let got_edges = d.get_u64()? as usize;
+    let mut models = FxHashMap::default();
+    for _ in 0..d.get_len()? {
+      let m_index = d.get_u32()?;
+      let mut m = Model::new();
+      for _ in 0..d.get_len()? {
+        m.vertices.push(d.get_point()?);
+      }
+      for _ in 0..d.get_len()? {
+        m.triangles.push([d.get_u32()?, d.get_u32()?, d.get_u32()?]);
+      }
+      models.insert(m_index, m);
+    }
+    let skipped = (0..d.get_len()?).map(|_| d.get_u32()).collect::<Result<_, _>>()?;
+    let has_selection = d.get_u32()? != 0;
+    let selected = (0..d.get_len()?).map(|_| d.get_u32()).collect::<Result<_, _>>()?;
+    let mesh = MeshState {
+      got_points,
+      got_edges,
+      models,
+      used_numbers: Vec::new(),
+      skipped,
+      selected: has_selection.then_some(selected),
+    };
+
+    let prev_layer = d.get_layer()?;
+    let cur_layer = d.get_layer()?;
+    let next_layer = d.get_layer()?;
+    // previous layer is empty until the first `fill_next_layer`
+    let layers = [&prev_layer, &cur_layer, &next_layer];
+    if layers.iter().any(|l| !l.cells.is_empty() && l.cells.len() != width * height) {
+      return Err("layers have wrong size".to_string());
+    }
+
+    Ok(Self {
+      domain,
+      mesh,
+      prev_layer,
+      cur_layer,
+      next_layer,
+      last_z,
+      last_odd,
+      tries,
+      tries_t,
+      started: Instant::now(),
+    })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::test_dir::TestDir;
+
+  fn bits(models: &FxHashMap<PartIndex, Model>) -> Vec<(PartIndex, Vec<[u32; 3]>, Vec<Triangle>)> {
+    let mut result: Vec<_> = models
+      .iter()
+      .map(|(&i, m)| {
+        let vertices = m.vertices.iter().map(|v| [v.x, v.y, v.z].map(f32::to_bits)).collect();
+        (i, vertices, m.triangles.clone())
+      })
+      .collect();
+    result.sort_by_key(|m| m.0);
+    result
+  }
+
+  // a ball in a wavy slab and a rod through both of them
+  fn ball_in_slab(p: Point) -> PartIndex {
+    if p.x * p.x + p.z * p.z < 2.2 {
+      3
+    } else if (p - Point { x: 1.3, y: -0.7, z: 0.4 }).len() < 5.5 {
+      1
+    } else if (p.y - (p.x * 0.4).sin() * 2.0).abs() < 3.0 && p.x.abs() + p.z.abs() < 15.0 {
+      2
+    } else {
+      0
+    }
+  }
+
+  #[test]
+  fn parallel_layers_are_exact() {
+    let part_f = ball_in_slab;
+    let distance_f = |p: Point, m_index| {
+      let center = Point { x: 1.3, y: -0.7, z: 0.4 };
+      let gradient = Some((p - center).norm());
+      (m_index == 1).then_some(PartDistance { distance: (p - center).len() - 5.5, gradient })
+    };
+
+    let mesh = |threads: usize, exact: bool| {
+      let mut mc = ModelCreator::new(24, 20.0, 20, 2, &part_f);
+      while !mc.finished() {
+        match (threads, exact) {
+          (0, false) => mc.fill_next_layer(&part_f, &SilentProgress),
+          (0, true) => mc.fill_next_layer_exact(&part_f, &distance_f, &SilentProgress),
+          (_, false) => mc.fill_next_layer_parallel(&part_f, threads, &SilentProgress),
+          (_, true) => {
+            mc.fill_next_layer_parallel_exact(&part_f, &distance_f, threads, &SilentProgress)
+          }
+        }
+      }
+      (mc.got_points(), mc.got_edges(), bits(&mc.get_models()))
+    };
+
+    for exact in [false, true] {
+      let serial = mesh(0, exact);
+      assert_eq!(serial.2.len(), 3);
+      for threads in [1, 2, 5, 40] {
+        assert!(mesh(threads, exact) == serial, "{threads} threads, exact {exact}");
+      }
+    }
+  }
+
+  #[test]
+  fn resumed_meshing_is_exact() {
+    let part_f = ball_in_slab;
+    let mut mc = ModelCreator::new(20, 20.0, 20, 0, &part_f);
+    while !mc.finished() {
+      mc.fill_next_layer(&part_f, &SilentProgress);
+    }
+    let expected = (mc.got_points(), mc.got_edges(), bits(&mc.get_models()));
+
+    let dir = TestDir::new("checkpoint");
+    let path = dir.join("checkpoint.bin");
+    let mut mc = ModelCreator::new(20, 20.0, 20, 0, &part_f);
+    mc.select_models([1, 2, 3]);
+    for _ in 0..13 {
+      mc.fill_next_layer(&part_f, &SilentProgress);
+    }
+    mc.save_checkpoint(&path, "key").unwrap();
+    drop(mc);
+    assert!(ModelCreator::resume(&path, "other key").is_err());
+    let mut mc = ModelCreator::resume(&path, "key").unwrap();
+    assert_eq!(mc.selected_models().map(|s| s.len()), Some(3));
+    while !mc.finished() {
+      mc.fill_next_layer(&part_f, &SilentProgress);
+    }
+    assert!((mc.got_points(), mc.got_edges(), bits(&mc.get_models())) == expected);
+  }
+
+  #[test]
+  fn sphere_by_signed_distance() {
+    let radius = 6.0;
+    let part_f = |p: Point| if p.len() <= radius { 1 } else { 0 };
+    let distance_f =
+      |p: Point, _| Some(PartDistance { distance: p.len() - radius, gradient: Some(p.norm()) });
+    let mesh = |exact: bool| {
+      let mut mc = ModelCreator::new(16, 20.0, 20, 0, &part_f);
+      while !mc.finished() {
+        match exact {
+          true => mc.fill_next_layer_exact(&part_f, &distance_f, &SilentProgress),
+          false => mc.fill_next_layer(&part_f, &SilentProgress),
+        }
+      }
+      let model = mc.get_models().remove(&1).unwrap();
+      // the farthest from the sphere of triangle centers and edge middles
+      let deviation = model.triangles.iter().fold(0.0f32, |dev, t| {
+        let [a, b, c] = t.map(|v| model.vertices[v as usize]);
+        [(a + b + c).scale(1.0 / 3.0), (a + b).scale(0.5), (b + c).scale(0.5), (c + a).scale(0.5)]
+          .iter()
+          .fold(dev, |dev, p| dev.max((p.len() - radius).abs()))
+      });
+      (model, deviation)
+    };
+    let (bisected, bisected_deviation) = mesh(false);
+    let (exact, exact_deviation) = mesh(true);
+    assert_eq!(exact.triangles.len(), bisected.triangles.len());
+    let report = exact.validate();
+    assert!(report.is_valid(), "{}", report.summary());
+    assert!(exact_deviation < 0.6 * bisected_deviation);
+    let volume = 4.0 / 3.0 * std::f32::consts::PI * radius.powi(3);
+    assert!((exact.get_volume() - volume).abs() < (bisected.get_volume() - volume).abs());
+  }
+
+  #[test]
+  fn plate_in_box_with_own_steps() {
+    let (min, max) = (Point { x: 10.0, y: 2.0, z: -1.0 }, Point { x: 50.0, y: 6.0, z: 1.0 });
+    let part_f = |p: Point| {
+      let inside = p.x > min.x && p.y > min.y && p.z > min.z;
+      if inside && p.x < max.x && p.y < max.y && p.z < max.z { 1 } else { 0 }
+    };
+    let cell = Point { x: 1.0, y: 0.5, z: 0.25 };
+    let domain = MeshDomain::with_cell_size(min, max, cell);
+    assert_eq!(domain.sweep, 0);
+    assert_eq!(domain.points, [42, 10, 10]);
+    for sweep in [0, 2] {
+      let mut mc = ModelCreator::with_domain(domain.clone().with_sweep(sweep), 20, 0, &part_f);
+      while !mc.finished() {
+        mc.fill_next_layer(&part_f, &SilentProgress);
+      }
+      let model = mc.get_models().remove(&1).unwrap();
+      let report = model.validate();
+      assert!(report.is_valid(), "{}", report.summary());
+      assert!((model.get_volume() - 320.0).abs() < 1.0, "{}", model.get_volume());
+      let (a, b) = model.get_aabb();
+      assert!((a - min).len() < 0.01 && (b - max).len() < 0.01);
+    }
+  }
+
+  #[test]
+  fn mesh_selected_part() {
+    let part_f = |p: Point| {
+      let inside = p.y.abs() < 3.1 && p.z.abs() < 2.7;
+      match p.x {
+        x if inside && x > -8.3 && x < -2.3 => 1,
+        x if inside && x > 2.3 && x < 8.3 => 2,
+        _ => 0,
+      }
+    };
+    let mesh = |domain: MeshDomain, selected: Option<PartIndex>| {
+      let mut mc = ModelCreator::with_domain(domain, 20, 0, &part_f);
+      if let Some(m_index) = selected {
+        mc.select_models([m_index]);
+      }
+      while !mc.finished() {
+        mc.fill_next_layer(&part_f, &SilentProgress);
+      }
+      mc.get_models()
+    };
+    let domain = MeshDomain::cube(40, 20.0);
+    let all = mesh(domain.clone(), None);
+    let boxes = domain.part_boxes(4, &part_f);
+    assert_eq!(boxes.len(), 2);
+    let (min, max) = boxes[&2];
+    let selected = domain.sub_domain(min, max);
+    let product = |d: &MeshDomain| d.points.iter().product::<usize>();
+    assert!(product(&selected) * 2 < product(&domain), "{:?}", selected.points);
+    let models = mesh(selected, Some(2));
+    assert_eq!(models.keys().collect::<Vec<_>>(), [&2]);
+    // the same grid points give the same surface
+    let (model, expected) = (&models[&2], &all[&2]);
+    assert_eq!(model.triangles.len(), expected.triangles.len());
+    assert!((model.get_volume() - expected.get_volume()).abs() < 1.0e-3 * expected.get_volume());
+    let ((a1, b1), (a2, b2)) = (model.get_aabb(), expected.get_aabb());
+    assert!((a1 - a2).len() < 1.0e-3 && (b1 - b2).len() < 1.0e-3);
   }
 }
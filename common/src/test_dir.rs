@@ -0,0 +1,29 @@
+use std::ops::Deref;
+use std::path::{Path, PathBuf};
+
+/// Temporary directory of one test, removed with its files when the test ends or panics
+pub struct TestDir(PathBuf);
+
+impl TestDir {
+  /// `name` should be unique among tests, they run in parallel
+  pub fn new(name: &str) -> Self {
+    let path = std::env::temp_dir().join(format!("{name}_{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&path);
+    std::fs::create_dir_all(&path).unwrap();
+    Self(path)
+  }
+}
+
+impl Deref for TestDir {
+  type Target = Path;
+
+  fn deref(&self) -> &Path {
+    &self.0
+  }
+}
+
+impl Drop for TestDir {
+  fn drop(&mut self) {
+    let _ = std::fs::remove_dir_all(&self.0);
+  }
+}
@@ -0,0 +1,274 @@
+use crate::bvh::Bvh;
+use crate::model::*;
+use crate::points3d::*;
+use fxhash::{FxHashMap, FxHashSet};
+use serde::Serialize;
+use std::cmp::Reverse;
+
+/// Edge as a pair of vertex indices, the smaller one is the first
+pub type Edge = (u32, u32);
+
+/// Connected by edges set of triangles
+#[derive(Debug, Clone, Serialize)]
+pub struct ShellInfo {
+  pub triangles: usize,
+  /// signed, negative for inside out shells
+  pub volume: f32,
+}
+
+/// Problems of the mesh found by `Model::validate`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ValidationReport {
+  /// edges used by only one triangle
+  pub open_edges: Vec<Edge>,
+  /// edges used by more than two triangles
+  pub non_manifold_edges: Vec<Edge>,
+  /// edges passed in the same direction by both their triangles
+  pub inconsistent_edges: Vec<Edge>,
+  /// indices of triangles with vertex indices out of range, they are skipped by other checks
+  pub invalid_indices: Vec<u32>,
+  /// indices of triangles with repeated vertices or zero area
+  pub degenerate_triangles: Vec<u32>,
+  /// the biggest shell is the first
+  pub shells: Vec<ShellInfo>,
+  /// pairs of triangles without common vertices crossing each other
+  pub self_intersections: Vec<(u32, u32)>,
+}
+
+impl ValidationReport {
+  /// Every edge has two triangles going through it in opposite directions,
+  /// topology based algorithms like `Model::optimize` need it
+  pub fn is_manifold(&self) -> bool {
+    self.invalid_indices.is_empty()
+      && self.open_edges.is_empty()
+      && self.non_manifold_edges.is_empty()
+      && self.inconsistent_edges.is_empty()
+  }
+
+  pub fn is_valid(&self) -> bool {
+    self.is_manifold()
+      && self.degenerate_triangles.is_empty()
+      && self.self_intersections.is_empty()
+      && self.shells.len() <= 1
+  }
+
+  /// One line with numbers of problems
+  pub fn summary(&self) -> String {
+    let volumes: Vec<_> = self.shells.iter().map(|s| s.volume.to_string()).collect();
+    format!(
+      "{} invalid triangles, {} open edges, {} non manifold edges, {} inconsistent edges, \
+       {} degenerate triangles, {} self intersections, {} shells with volumes [{}]",
+      self.invalid_indices.len(),
+      self.open_edges.len(),
+      self.non_manifold_edges.len(),
+      self.inconsistent_edges.len(),
+      self.degenerate_triangles.len(),
+      self.self_intersections.len(),
+      self.shells.len(),
+      volumes.join(", ")
+    )
+  }
+}
+
+impl Model {
+  /// Checks the mesh without changing it, unlike `validate_and_delete_small_groups` never panics
+  pub fn validate(&self) -> ValidationReport {
+    let mut report = ValidationReport::default();
+    let size = self.vertices.len();
+    let valid = |t: &Triangle| t.iter().all(|&v| (v as usize) < size);
+    for (i, t) in self.triangles.iter().enumerate() {
+      if !valid(t) {
+        report.invalid_indices.push(i as u32);
+      }
+    }
+
+    // triangles of every edge with `true` if the triangle goes from the smaller vertex
+    let mut edges = FxHashMap::<Edge, Vec<(u32, bool)>>::default();
+    for (i, t) in self.triangles.iter().enumerate().filter(|(_, t)| valid(t)) {
+      for k in 0..3 {
+        let (v0, v1) = (t[k], t[(k + 1) % 3]);
+        if v0 != v1 {
+          edges.entry((v0.min(v1), v0.max(v1))).or_default().push((i as u32, v0 < v1));
+        }
+      }
+    }
+
+    let mut shells = DisjointSets::new(self.triangles.len());
+    for (&edge, faces) in &edges {
+      match faces.as_slice() {
+        [_] => report.open_edges.push(edge),
+        [(t0, d0), (t1, d1)] => {
+          if d0 == d1 {
+            report.inconsistent_edges.push(edge);
+          }
+          shells.union(*t0 as usize, *t1 as usize);
+        }
+        _ => report.non_manifold_edges.push(edge),
+      }
+    }
+    report.open_edges.sort();
+    report.non_manifold_edges.sort();
+    report.inconsistent_edges.sort();
+
+    for (i, &t) in self.triangles.iter().enumerate().filter(|(_, t)| valid(t)) {
+      if self.is_degenerate(t) {
+        report.degenerate_triangles.push(i as u32);
+      }
+    }
+
+    let mut shell_of_root = FxHashMap::<usize, usize>::default();
+    for (i, t) in self.triangles.iter().enumerate().filter(|(_, t)| valid(t)) {
+      let root = shells.find(i);
+      let next = shell_of_root.len();
+      let shell = *shell_of_root.entry(root).or_insert(next);
+      if shell == report.shells.len() {
+        report.shells.push(ShellInfo { triangles: 0, volume: 0.0 });
+      }
+      let [v0, v1, v2] = t.map(|v| self.vertices[v as usize]);
+      report.shells[shell].triangles += 1;
+      report.shells[shell].volume += dot(v0, cross(v1, v2)) / 6.0;
+    }
+    report.shells.sort_by_key(|s| Reverse(s.triangles));
+
+    let mut skipped = report.invalid_indices.clone();
+    skipped.extend(&report.degenerate_triangles);
+    report.self_intersections = self.find_self_intersections(&skipped);
+    report
+  }
+
+  /// Triangle has repeated vertices or its area is negligible comparing to its size
+  pub fn is_degenerate(&self, t: Triangle) -> bool {
+    if t[0] == t[1] || t[1] == t[2] || t[2] == t[0] {
+      return true;
+    }
+    let [v0, v1, v2] = t.map(|v| self.vertices[v as usize]);
+    let max_edge = (v1 - v0).sqr_len().max((v2 - v1).sqr_len()).max((v0 - v2).sqr_len());
+    cross(v1 - v0, v2 - v0).len() <= max_edge * 1.0e-6
+  }
+
+  // candidate pairs are the ones with overlapping boxes found by a hierarchy of boxes,
+  // `skipped` triangles are not checked
+  fn find_self_intersections(&self, skipped: &[u32]) -> Vec<(u32, u32)> {
+    let skipped: FxHashSet<u32> = skipped.iter().copied().collect();
+    let triangles: Vec<[Point; 3]> = self
+      .triangles
+      .iter()
+      .enumerate()
+      .map(|(i, t)| match skipped.contains(&(i as u32)) {
+        true => [Point::default(); 3],
+        false => t.map(|v| self.vertices[v as usize]),
+      })
+      .collect();
+    let order = (0..self.triangles.len() as u32).filter(|i| !skipped.contains(i)).collect();
+    let bvh = Bvh::with_order(triangles, order);
+
+    let mut result = Vec::new();
+    for (i, j) in bvh.overlapping_pairs() {
+      let (ti, tj) = (self.triangles[i as usize], self.triangles[j as usize]);
+      if !ti.iter().any(|v| tj.contains(v)) && triangles_cross(bvh.triangle(i), bvh.triangle(j)) {
+        result.push((i, j));
+      }
+    }
+    result.sort();
+    result
+  }
+}
+
+// some edge of one triangle passes through inside of the other one, coplanar overlaps are not found
+fn triangles_cross(a: [Point; 3], b: [Point; 3]) -> bool {
+  (0..3).any(|k| segment_crosses(a[k], a[(k + 1) % 3], b))
+    || (0..3).any(|k| segment_crosses(b[k], b[(k + 1) % 3], a))
+}
+
+fn segment_crosses(p0: Point, p1: Point, t: [Point; 3]) -> bool {
+  let dir = p1 - p0;
+  let e1 = t[1] - t[0];
+  let e2 = t[2] - t[0];
+  let h = cross(dir, e2);
+  let det = dot(e1, h);
+  if det.abs() <= f32::EPSILON * e1.len() * h.len() {
+    return false;
+  }
+  let s = p0 - t[0];
+  let u = dot(s, h) / det;
+  let q = cross(s, e1);
+  let v = dot(dir, q) / det;
+  let along = dot(e2, q) / det;
+  // touching in a single point is not a crossing
+  const EPS: f32 = 1.0e-4;
+  u > EPS && v > EPS && u + v < 1.0 - EPS && along > EPS && along < 1.0 - EPS
+}
+
+/// Union-find over indices
+pub(crate) struct DisjointSets {
+  parent: Vec<usize>,
+}
+
+impl DisjointSets {
+  pub fn new(size: usize) -> Self {
+    Self { parent: (0..size).collect() }
+  }
+
+  pub fn find(&mut self, mut i: usize) -> usize {
+    while self.parent[i] != i {
+      self.parent[i] = self.parent[self.parent[i]];
+      i = self.parent[i];
+    }
+    i
+  }
+
+  pub fn union(&mut self, i: usize, j: usize) {
+    let (i, j) = (self.find(i), self.find(j));
+    self.parent[i] = j;
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn validate_open_cuboid() {
+    let mut test = Model::cuboid(2, 2, 2, 1.0);
+    let report = test.validate();
+    assert!(report.is_valid());
+    assert!((report.shells[0].volume - 8.0).abs() < 1.0e-4);
+
+    test.triangles.pop();
+    let report = test.validate();
+    assert!(!report.is_manifold());
+    assert_eq!(report.open_edges.len(), 3);
+  }
+
+  #[test]
+  fn indices_out_of_range() {
+    let mut test = Model::cuboid(2, 2, 2, 1.0);
+    let (size, first) = (test.vertices.len() as u32, test.triangles.len() as u32);
+    test.triangles.push([0, 1, size]);
+    test.triangles.push([size + 7, size + 7, size + 7]);
+    let report = test.validate();
+    assert_eq!(report.invalid_indices, [first, first + 1]);
+    assert!(!report.is_manifold());
+    assert!(report.open_edges.is_empty());
+    assert!(report.degenerate_triangles.is_empty());
+    assert!(report.self_intersections.is_empty());
+    assert!((report.shells[0].volume - 8.0).abs() < 1.0e-4);
+  }
+
+  #[test]
+  fn big_triangle_through_cuboid() {
+    let mut test = Model::cuboid(2, 2, 2, 1.0);
+    let first = test.vertices.len() as u32;
+    test.vertices.extend([
+      Point { x: -100.0, y: -100.0, z: 0.5 },
+      Point { x: 100.0, y: -100.0, z: 0.5 },
+      Point { x: 0.0, y: 100.0, z: 0.5 },
+    ]);
+    test.triangles.push([first, first + 1, first + 2]);
+    let report = test.validate();
+    let big = test.triangles.len() as u32 - 1;
+    // every side has two cells crossed by the plane with two triangles each
+    assert_eq!(report.self_intersections.len(), 16);
+    assert!(report.self_intersections.iter().all(|&(_, j)| j == big));
+  }
+}
@@ -0,0 +1,138 @@
+use std::io::{Error, ErrorKind, Write};
+
+// 1980-01-01 00:00
+const DOS_DATE: u16 = 0x21;
+
+const LOCAL_HEADER: u32 = 0x04034b50;
+const CENTRAL_HEADER: u32 = 0x02014b50;
+const END_OF_CENTRAL: u32 = 0x06054b50;
+
+// sizes and offsets of plain zip, archives which need zip64 are refused
+fn fits<T: TryFrom<usize>>(value: usize, what: &str) -> std::io::Result<T> {
+  T::try_from(value)
+    .map_err(|_| Error::new(ErrorKind::InvalidInput, format!("{what} {value} is too big for zip")))
+}
+
+/// Zip archive with deflated `files` given by names and contents
+pub fn write_zip(files: &[(&str, &[u8])]) -> std::io::Result<Vec<u8>> {
+  let mut out = Vec::new();
+  let mut central = Vec::new();
+  for (name, data) in files {
+    let mut encoder =
+      flate2::write::DeflateEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(data)?;
+    let compressed = encoder.finish()?;
+    let crc = crc32fast::hash(data);
+    let offset: u32 = fits(out.len(), "offset")?;
+
+    // fields shared by the local and central headers from version needed to name length
+    let mut common = Vec::new();
+    common.extend_from_slice(&20u16.to_le_bytes());
+    common.extend_from_slice(&0u16.to_le_bytes());
+    common.extend_from_slice(&8u16.to_le_bytes());
+    common.extend_from_slice(&0u16.to_le_bytes());
+    common.extend_from_slice(&DOS_DATE.to_le_bytes());
+    common.extend_from_slice(&crc.to_le_bytes());
+    common.extend_from_slice(&fits::<u32>(compressed.len(), "compressed size")?.to_le_bytes());
+    common.extend_from_slice(&fits::<u32>(data.len(), "size")?.to_le_bytes());
+    common.extend_from_slice(&fits::<u16>(name.len(), "name length")?.to_le_bytes());
+
+    out.extend_from_slice(&LOCAL_HEADER.to_le_bytes());
+    out.extend_from_slice(&common);
+    out.extend_from_slice(&0u16.to_le_bytes());
+    out.extend_from_slice(name.as_bytes());
+    out.extend_from_slice(&compressed);
+
+    central.extend_from_slice(&CENTRAL_HEADER.to_le_bytes());
+    central.extend_from_slice(&20u16.to_le_bytes());
+    central.extend_from_slice(&common);
+    // extra, comment, disk, internal and external attributes
+    central.extend_from_slice(&[0; 12]);
+    central.extend_from_slice(&offset.to_le_bytes());
+    central.extend_from_slice(name.as_bytes());
+  }
+
+  let count: u16 = fits(files.len(), "number of files")?;
+  let central_offset: u32 = fits(out.len(), "offset")?;
+  let central_size: u32 = fits(central.len(), "central directory size")?;
+  out.extend_from_slice(&central);
+  out.extend_from_slice(&END_OF_CENTRAL.to_le_bytes());
+  out.extend_from_slice(&[0; 4]);
+  out.extend_from_slice(&count.to_le_bytes());
+  out.extend_from_slice(&count.to_le_bytes());
+  out.extend_from_slice(&central_size.to_le_bytes());
+  out.extend_from_slice(&central_offset.to_le_bytes());
+  out.extend_from_slice(&0u16.to_le_bytes());
+  Ok(out)
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+  use super::*;
+  use std::io::Read;
+
+  fn u16_at(data: &[u8], pos: usize) -> usize {
+    u16::from_le_bytes([data[pos], data[pos + 1]]) as usize
+  }
+
+  fn u32_at(data: &[u8], pos: usize) -> usize {
+    u32::from_le_bytes(data[pos..pos + 4].try_into().unwrap()) as usize
+  }
+
+  // files found through the central directory like unzip does
+  pub(crate) fn read_zip(zip: &[u8]) -> Vec<(String, Vec<u8>)> {
+    let end = zip.len() - 22;
+    assert_eq!(u32_at(zip, end), END_OF_CENTRAL as usize);
+    let count = u16_at(zip, end + 10);
+    let mut pos = u32_at(zip, end + 16);
+    assert_eq!(pos + u32_at(zip, end + 12), end);
+
+    let mut files = Vec::new();
+    for _ in 0..count {
+      assert_eq!(u32_at(zip, pos), CENTRAL_HEADER as usize);
+      let (crc, compressed, size) =
+        (u32_at(zip, pos + 16), u32_at(zip, pos + 20), u32_at(zip, pos + 24));
+      let name_len = u16_at(zip, pos + 28);
+      let name = String::from_utf8(zip[pos + 46..pos + 46 + name_len].to_vec()).unwrap();
+      let local = u32_at(zip, pos + 42);
+      pos += 46 + name_len;
+
+      assert_eq!(u32_at(zip, local), LOCAL_HEADER as usize);
+      assert_eq!(u16_at(zip, local + 8), 8);
+      assert_eq!(u32_at(zip, local + 14), crc);
+      assert_eq!(&zip[local + 30..local + 30 + name_len], name.as_bytes());
+      let start = local + 30 + name_len + u16_at(zip, local + 28);
+      let mut data = Vec::new();
+      flate2::read::DeflateDecoder::new(&zip[start..start + compressed])
+        .read_to_end(&mut data)
+        .unwrap();
+      assert_eq!(data.len(), size);
+      assert_eq!(crc32fast::hash(&data) as usize, crc);
+      files.push((name, data));
+    }
+    files
+  }
+
+  #[test]
+  fn read_back() {
+    let text = "<model>\n".repeat(1000);
+    let bytes: Vec<u8> = (0..5000u32).map(|i| (i * i % 251) as u8).collect();
+    let files: [(&str, &[u8]); 3] =
+      [("3D/model.xml", text.as_bytes()), ("empty", b""), ("_rels/bytes.bin", &bytes)];
+    let zip = write_zip(&files).unwrap();
+    assert!(zip.len() < text.len());
+    let read = read_zip(&zip);
+    assert_eq!(read.len(), files.len());
+    for ((name, data), (read_name, read_data)) in files.iter().zip(&read) {
+      assert_eq!(name, read_name);
+      assert_eq!(data, read_data);
+    }
+  }
+
+  #[test]
+  fn too_long_name() {
+    let name = "a".repeat(70000);
+    let error = write_zip(&[(&name, b"data")]).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidInput);
+  }
+}
@@ -1,3 +1,4 @@
+use common::part_creator::*;
 use common::points2d::*;
 use common::solid::*;
 
@@ -269,32 +270,34 @@ impl AxleToolCreator {
 
     Self { rolls, g1, g2 }
   }
+}
 
-  pub fn get_quality() -> usize {
+impl PartCreator for AxleToolCreator {
+  fn get_quality(&self) -> usize {
     1
   }
 
-  pub fn get_size() -> f32 {
+  fn get_size(&self) -> f32 {
     1.0
   }
 
-  pub fn faces(&self) -> usize {
+  fn faces(&self) -> usize {
     10
   }
 
-  pub fn get_height(&self, part_index: usize) -> f32 {
+  fn get_height(&self, part_index: usize) -> f32 {
     3.0
   }
 
-  pub fn get_name(&self, part_index: usize) -> Option<&str> {
+  fn get_name(&self, part_index: usize) -> Option<String> {
     match part_index {
-      8 => Some("gear1"),
-      9 => Some("gear2"),
+      8 => Some("gear1".to_string()),
+      9 => Some("gear2".to_string()),
       _ => None,
     }
   }
 
-  pub fn get_count(&self, part_index: usize) -> usize {
+  fn get_count(&self, part_index: usize) -> usize {
     if part_index == 0 {
       2
     } else {
@@ -302,14 +305,14 @@ impl AxleToolCreator {
     }
   }
 
-  pub fn aabb(&self, part_index: usize) -> Option<AABB> {
+  fn aabb(&self, part_index: usize) -> Option<AABB> {
     if part_index < 8 {
       return Some(AABB::empty());
     }
     None
   }
 
-  pub fn get_sticker_index(&self, pos: Point, part_index: usize) -> PartIndex {
+  fn get_sticker_index(&self, pos: Point, part_index: usize) -> PartIndex {
     match part_index {
       /*
       0 => {
@@ -1,3 +1,4 @@
+use common::part_creator::*;
 use common::points2d::*;
 use common::solid::*;
 use num::Float;
@@ -10,36 +11,30 @@ impl BevelCreator {
   pub fn new() -> Self {
     Self {}
   }
+}
 
-  pub fn get_count(&self, part_index: usize) -> usize {
-    1
-  }
-
-  pub fn aabb(&self, part_index: usize) -> Option<AABB> {
-    None
-  }
-
-  pub fn faces(&self) -> usize {
+impl PartCreator for BevelCreator {
+  fn faces(&self) -> usize {
     1
   }
 
-  pub fn get_height(&self, part_index: usize) -> f32 {
+  fn get_height(&self, part_index: usize) -> f32 {
     2.0
   }
 
-  pub fn get_quality() -> usize {
+  fn get_quality(&self) -> usize {
     128
   }
 
-  pub fn get_size() -> f32 {
+  fn get_size(&self) -> f32 {
     120.0
   }
 
-  pub fn get_name(&self, part_index: usize) -> Option<&str> {
-    Some("bevel")
+  fn get_name(&self, part_index: usize) -> Option<String> {
+    Some("bevel".to_string())
   }
 
-  pub fn get_sticker_index(&self, pos: Point, part_index: usize) -> PartIndex {
+  fn get_sticker_index(&self, pos: Point, part_index: usize) -> PartIndex {
     if pos.y < -38.0 || pos.x < -46.0 || pos.y > 9.0 || pos.x > 60.0 {
       return 0;
     }
@@ -74,8 +69,4 @@ impl BevelCreator {
 
     return 1;
   }
-
-  pub fn get_part_index(&self, pos: common::points3d::Point) -> PartIndex {
-    0
-  }
 }
@@ -1,3 +1,4 @@
+use crate::part_creator::*;
 use crate::points2d::*;
 use crate::solid::*;
 
@@ -298,12 +299,14 @@ impl BrakeHandleCreator {
 
     Self { figures, connectors }
   }
+}
 
-  pub fn faces(&self) -> usize {
+impl PartCreator for BrakeHandleCreator {
+  fn faces(&self) -> usize {
     self.figures.len() + self.connectors.len()
   }
 
-  pub fn get_sticker_index(&self, pos: Point, current_normal: usize) -> PartIndex {
+  fn get_sticker_index(&self, pos: Point, current_normal: usize) -> PartIndex {
     let mut r;
     if current_normal < self.figures.len() {
       r = self.figures[current_normal].contains(pos);
@@ -333,8 +336,4 @@ impl BrakeHandleCreator {
     }
     r as PartIndex
   }
-
-  pub fn get_part_index(&self, pos: crate::points3d::Point) -> PartIndex {
-    return 0;
-  }
 }
@@ -1,3 +1,4 @@
+use common::part_creator::*;
 use common::points2d::*;
 use common::solid::*;
 
@@ -59,28 +60,30 @@ impl BrakeToolCreator {
 
     Self { builder }
   }
+}
 
-  pub fn aabb(&self, part_index: usize) -> Option<AABB> {
+impl PartCreator for BrakeToolCreator {
+  fn aabb(&self, part_index: usize) -> Option<AABB> {
     Some(self.builder.aabb(part_index))
   }
 
-  pub fn faces(&self) -> usize {
+  fn faces(&self) -> usize {
     self.builder.contour_count()
   }
 
-  pub fn get_height(&self, current_normal: usize) -> f32 {
+  fn get_height(&self, current_normal: usize) -> f32 {
     self.builder.get_material_thickness(current_normal)
   }
 
-  pub fn get_name(&self, current_normal: usize) -> Option<&str> {
-    self.builder.get_name(current_normal)
+  fn get_name(&self, current_normal: usize) -> Option<String> {
+    self.builder.get_name(current_normal).map(String::from)
   }
 
-  pub fn get_count(&self, current_normal: usize) -> usize {
+  fn get_count(&self, current_normal: usize) -> usize {
     self.builder.get_count(current_normal)
   }
 
-  pub fn get_sticker_index(&self, pos: Point, current_normal: usize) -> PartIndex {
+  fn get_sticker_index(&self, pos: Point, current_normal: usize) -> PartIndex {
     self.builder.contains(pos, current_normal) as PartIndex
   }
 }
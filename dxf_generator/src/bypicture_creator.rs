@@ -1,3 +1,4 @@
+use common::part_creator::*;
 use common::points2d::*;
 use common::solid::*;
 use num::Float;
@@ -51,36 +52,26 @@ impl ByPictureCreator {
 
     Self { mat }
   }
+}
 
-  pub fn get_count(&self, part_index: usize) -> usize {
-    1
-  }
-
-  pub fn aabb(&self, part_index: usize) -> Option<AABB> {
-    None
-  }
-
-  pub fn faces(&self) -> usize {
+impl PartCreator for ByPictureCreator {
+  fn faces(&self) -> usize {
     1
   }
 
-  pub fn get_height(&self, part_index: usize) -> f32 {
+  fn get_height(&self, part_index: usize) -> f32 {
     2.0
   }
 
-  pub fn get_quality() -> usize {
+  fn get_quality(&self) -> usize {
     128
   }
 
-  pub fn get_size() -> f32 {
+  fn get_size(&self) -> f32 {
     100.0
   }
 
-  pub fn get_name(&self, part_index: usize) -> Option<&str> {
-    None
-  }
-
-  pub fn get_sticker_index(&self, pos: Point, part_index: usize) -> PartIndex {
+  fn get_sticker_index(&self, pos: Point, part_index: usize) -> PartIndex {
     let x = (pos.x * 56.0 + 10.0) * 0.5;
     let y = (pos.y * 56.0 + 10.0) * 0.5;
     if y >= 0.0 && (y as usize) < self.mat.len() {
@@ -92,8 +83,4 @@ impl ByPictureCreator {
 
     return 0;
   }
-
-  pub fn get_part_index(&self, pos: common::points3d::Point) -> PartIndex {
-    0
-  }
 }
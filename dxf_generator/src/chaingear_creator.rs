@@ -1,3 +1,4 @@
+use common::part_creator::*;
 use common::points2d::*;
 use common::solid::*;
 use num::Float;
@@ -411,25 +412,23 @@ impl ChaingearCreator {
 
     Self { gears, h3e, h6, a7b }
   }
+}
 
-  pub fn get_count(&self, part_index: usize) -> usize {
-    1
-  }
-
-  pub fn aabb(&self, part_index: usize) -> Option<AABB> {
+impl PartCreator for ChaingearCreator {
+  fn aabb(&self, part_index: usize) -> Option<AABB> {
     let gear = &self.gears[part_index];
     Some(AABB::around_zero(gear.pin_c_r + gear.pin_r + 0.01))
   }
 
-  pub fn faces(&self) -> usize {
+  fn faces(&self) -> usize {
     self.gears.len()
   }
 
-  pub fn get_height(&self, part_index: usize) -> f32 {
+  fn get_height(&self, part_index: usize) -> f32 {
     3.0
   }
 
-  pub fn get_name(&self, part_index: usize) -> Option<String> {
+  fn get_name(&self, part_index: usize) -> Option<String> {
     let g = &self.gears[part_index];
     let desc = match g.couple {
       GearCouple::Inner3Euro => format!("{}-rear-3pins-euro", g.tc),
@@ -439,7 +438,7 @@ impl ChaingearCreator {
     Some(desc)
   }
 
-  pub fn get_sticker_index(&self, pos: Point, part_index: usize) -> PartIndex {
+  fn get_sticker_index(&self, pos: Point, part_index: usize) -> PartIndex {
     let r = pos.len();
     if part_index < self.gears.len() {
       let g = &self.gears[part_index];
@@ -470,8 +469,4 @@ impl ChaingearCreator {
 
     0
   }
-
-  pub fn get_part_index(&self, pos: common::points3d::Point) -> PartIndex {
-    0
-  }
 }
@@ -1,11 +1,14 @@
+use common::part_creator::*;
 use common::points2d::*;
 use common::solid::*;
 use num::*;
+use serde::{Deserialize, Serialize};
 
 use common::slots_and_holes::*;
 
-#[derive(Default)]
-struct ClickboxParams {
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct ClickboxParams {
   error: f32,
 
   y_out: f32,
@@ -13,6 +16,7 @@ struct ClickboxParams {
   drum_radius: f32,
   depth: f32,
 
+  #[serde(skip)]
   drum_pos: Point,
   roll_radius: f32,
   step_out: f32,
@@ -24,16 +28,55 @@ struct ClickboxParams {
   pipe_r: f32,
   nut_y: f32,
 
+  #[serde(skip)]
   cable_slot_1_y: f32,
+  #[serde(skip)]
   cable_slot_2_y: f32,
 
   height: f32,
 
+  // calculated in `init`
+  #[serde(skip)]
   f_in: (Point, Vec<Point>),
+  #[serde(skip)]
   f_out: (Point, Vec<Point>),
+  #[serde(skip)]
   angle_between: f32,
 }
 
+impl Default for ClickboxParams {
+  fn default() -> Self {
+    Self {
+      error: 0.05,
+
+      y_out: 27.5,
+      y_in: 43.2,
+      drum_radius: 14.5,
+      depth: 2.0,
+
+      drum_pos: Point::ZERO,
+      roll_radius: 3.5,
+      step_out: 5.5,
+      step_in: 6.0,
+
+      axle_r: 4.6,
+      braid_r: 2.9,
+      axle_end: 22.0,
+      pipe_r: 2.6,
+      nut_y: 7.0,
+
+      cable_slot_1_y: 0.0,
+      cable_slot_2_y: 0.0,
+
+      height: 17.2,
+
+      f_in: Default::default(),
+      f_out: Default::default(),
+      angle_between: 0.0,
+    }
+  }
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct MainPoint {
   center: Point,
@@ -1313,31 +1356,13 @@ impl ClickboxParams {
     );
   }
 
-  fn init(&mut self, error: f32) {
-    self.error = error;
-
-    self.y_out = 27.5;
-    self.y_in = 43.2;
-    self.drum_radius = 14.5;
-    self.depth = 2.0;
-
+  fn init(&mut self) {
     self.drum_pos =
       Point { x: self.drum_radius + self.depth + 7.1, y: self.drum_radius - self.depth + 11.0 };
-    self.roll_radius = 3.5;
-    self.step_out = 5.5;
-    self.step_in = 6.0;
-
-    self.axle_r = 4.6;
-    self.braid_r = 2.9;
-    self.axle_end = 22.0;
-    self.pipe_r = 2.6;
-    self.nut_y = 7.0;
 
     self.cable_slot_1_y = self.nut_y + 6.75;
     self.cable_slot_2_y = self.drum_pos.y + (self.drum_radius - self.depth - 1.0);
 
-    self.height = 17.2;
-
     self.f_in = self.find_crank_center(self.y_in, self.step_in, 0.1);
     self.f_out = self.find_crank_center(self.y_out, self.step_out, 2.0);
     self.angle_between =
@@ -1672,24 +1697,7 @@ pub struct ClickboxCreator {
 
 impl ClickboxCreator {
   pub fn new() -> Self {
-    let error = 0.05;
-
-    let mut params = ClickboxParams::default();
-    params.init(error);
-
-    let mut builder2 = Builder::new(1.0, 2.0, error);
-    let mut builder15 = Builder::new(2.0, 1.5, error);
-    let mut drum = DrumCreator::default();
-
-    params.make_clickbox(&mut builder2);
-    params.make_handle(&mut builder15);
-    params.make_drum(&mut drum);
-
-    Self { builder15, builder2, drum }
-  }
-
-  pub fn faces(&self) -> usize {
-    self.builder2.contour_count() + self.builder15.contour_count() + self.drum.faces()
+    Self::with_params(ClickboxParams::default())
   }
 
   fn match_index<R>(
@@ -1712,8 +1720,32 @@ impl ClickboxCreator {
     }
     panic!("Wrong index!");
   }
+}
 
-  pub fn get_height(&self, part_index: usize) -> f32 {
+impl ParamsCreator for ClickboxCreator {
+  type Params = ClickboxParams;
+
+  fn with_params(mut params: ClickboxParams) -> Self {
+    params.init();
+
+    let mut builder2 = Builder::new(1.0, 2.0, params.error);
+    let mut builder15 = Builder::new(2.0, 1.5, params.error);
+    let mut drum = DrumCreator::default();
+
+    params.make_clickbox(&mut builder2);
+    params.make_handle(&mut builder15);
+    params.make_drum(&mut drum);
+
+    Self { builder15, builder2, drum }
+  }
+}
+
+impl PartCreator for ClickboxCreator {
+  fn faces(&self) -> usize {
+    self.builder2.contour_count() + self.builder15.contour_count() + self.drum.faces()
+  }
+
+  fn get_height(&self, part_index: usize) -> f32 {
     self.match_index(
       part_index,
       |i| self.builder15.get_material_thickness(i),
@@ -1722,16 +1754,17 @@ impl ClickboxCreator {
     )
   }
 
-  pub fn get_name(&self, part_index: usize) -> Option<&str> {
-    self.match_index(
+  fn get_name(&self, part_index: usize) -> Option<String> {
+    let name = self.match_index(
       part_index,
       |i| self.builder15.get_name(i),
       |i| self.builder2.get_name(i),
       |i| self.drum.get_name(i),
-    )
+    );
+    name.map(String::from)
   }
 
-  pub fn get_count(&self, part_index: usize) -> usize {
+  fn get_count(&self, part_index: usize) -> usize {
     self.match_index(
       part_index,
       |i| self.builder15.get_count(i),
@@ -1740,7 +1773,7 @@ impl ClickboxCreator {
     )
   }
 
-  pub fn get_sticker_index(&self, pos: Point, part_index: usize) -> PartIndex {
+  fn get_sticker_index(&self, pos: Point, part_index: usize) -> PartIndex {
     self.match_index(
       part_index,
       |i| self.builder15.contains(pos, i) as PartIndex,
@@ -1749,7 +1782,7 @@ impl ClickboxCreator {
     )
   }
 
-  pub fn aabb(&self, part_index: usize) -> Option<AABB> {
+  fn aabb(&self, part_index: usize) -> Option<AABB> {
     Some(self.match_index(
       part_index,
       |i| self.builder15.aabb(i),
@@ -1,3 +1,4 @@
+use crate::part_creator::*;
 use crate::points2d::*;
 use crate::solid::*;
 
@@ -999,20 +1000,22 @@ impl ClickboxCreator {
    // result.make_clickbox();
     result
   }
+}
 
-  pub fn get_quality() -> usize {
+impl PartCreator for ClickboxCreator {
+  fn get_quality(&self) -> usize {
     1
   }
 
-  pub fn get_size() -> f32 {
+  fn get_size(&self) -> f32 {
     1.0
   }
 
-  pub fn faces(&self) -> usize {
+  fn faces(&self) -> usize {
     self.builder_1_5.contour_count() + self.builder_2.contour_count()
   }
 
-  pub fn get_height(&self, current_normal: usize) -> f32 {
+  fn get_height(&self, current_normal: usize) -> f32 {
     let c1 = self.builder_1_5.contour_count();
     if current_normal < c1 {
       self.builder_1_5.get_material_thickness(current_normal)
@@ -1021,16 +1024,16 @@ impl ClickboxCreator {
     }
   }
 
-  pub fn get_name(&self, current_normal: usize) -> Option<&str> {
+  fn get_name(&self, current_normal: usize) -> Option<String> {
     let c1 = self.builder_1_5.contour_count();
     if current_normal < c1 {
-      self.builder_1_5.get_name(current_normal)
+      self.builder_1_5.get_name(current_normal).map(String::from)
     } else {
-      self.builder_2.get_name(current_normal - c1)
+      self.builder_2.get_name(current_normal - c1).map(String::from)
     }
   }
 
-  pub fn get_count(&self, current_normal: usize) -> usize {
+  fn get_count(&self, current_normal: usize) -> usize {
     let c1 = self.builder_1_5.contour_count();
     if current_normal < c1 {
       self.builder_1_5.get_count(current_normal)
@@ -1039,7 +1042,7 @@ impl ClickboxCreator {
     }
   }
 
-  pub fn get_sticker_index(&self, pos: Point, current_normal: usize) -> PartIndex {
+  fn get_sticker_index(&self, pos: Point, current_normal: usize) -> PartIndex {
     let c1 = self.builder_1_5.contour_count();
     if current_normal < c1 {
       self.builder_1_5.contains(current_normal, pos) as PartIndex
@@ -1047,8 +1050,4 @@ impl ClickboxCreator {
       self.builder_2.contains(current_normal - c1, pos) as PartIndex
     }
   }
-
-  pub fn get_part_index(&self, pos: crate::points3d::Point) -> PartIndex {
-    return 0;
-  }
 }
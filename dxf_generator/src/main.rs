@@ -1,20 +1,96 @@
 #![allow(unused)]
 
+use clap::{Parser, Subcommand};
 use common::contour::*;
+use common::material::*;
+use common::part_creator::*;
 use common::points2d::*;
+use common::progress::*;
+use common::report::*;
 use rand::Rng;
 use rand::SeedableRng;
 use std::io::Write;
 use std::ops::DerefMut;
+use std::path::{Path, PathBuf};
 use std::time::Instant;
 
 use fxhash::FxHashMap;
 
+mod axle_tool_creator;
+mod bevel_creator;
+mod brake_handle_creator;
+mod brake_tool_creator;
+mod bypicture_creator;
 mod chaingear_creator;
-type PartCreator = chaingear_creator::ChaingearCreator;
+mod clickbox2_creator;
+mod small_watches_creator;
+mod watches_creator;
+
+// outdated, need to be restored before use
+//mod clickbox_creator;
+//mod ribbon_creator;
+
+const CREATORS: &[RegisteredCreator] = common::creator_registry![
+  "axle_tool" => axle_tool_creator::AxleToolCreator,
+  "bevel" => bevel_creator::BevelCreator,
+  "brake_handle" => brake_handle_creator::BrakeHandleCreator,
+  "brake_tool" => brake_tool_creator::BrakeToolCreator,
+  "bypicture" => bypicture_creator::ByPictureCreator,
+  "chaingear" => chaingear_creator::ChaingearCreator,
+  "clickbox2" => clickbox2_creator::ClickboxCreator [params],
+  "small_watches" => small_watches_creator::WatchesCreator,
+  "watches" => watches_creator::WatchesCreator,
+];
+
+// steps of 0.2 mm in the area of 200 mm
+const DEFAULT_QUALITY: usize = 1000;
+const DEFAULT_SIZE: f32 = 200.0;
+
+#[derive(Parser)]
+#[command(about = "Generates dxf contours of flat parts by creator name")]
+struct Cli {
+  #[command(subcommand)]
+  command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+  /// Print names of all known creators
+  List,
+  /// Generate contours of all faces of the creator
+  Generate {
+    name: String,
+    /// Override grid resolution, contours are traced with steps of `size / quality`
+    #[arg(long, default_value_t = DEFAULT_QUALITY)]
+    quality: usize,
+    /// Override size of the area where faces without their own box are searched
+    #[arg(long, default_value_t = DEFAULT_SIZE)]
+    size: f32,
+    /// Json file overriding creator parameters, effective ones are saved to the output dir
+    #[arg(long)]
+    params: Option<PathBuf>,
+    /// Material of all parts instead of the ones assigned by the creator
+    #[arg(long, value_parser = parse_material)]
+    material: Option<Material>,
+    /// Directory where `contours` and `extruded` folders are placed
+    #[arg(long, default_value = ".")]
+    output_dir: PathBuf,
+  },
+}
 
-//mod clickbox2_creator;
-//type PartCreator = clickbox2_creator::ClickboxCreator;
+fn parse_material(s: &str) -> Result<Material, String> {
+  find_material(s).ok_or_else(|| {
+    let names: Vec<_> = MATERIALS.iter().map(|m| m.name).collect();
+    format!("unknown material, available are {}", names.join(", "))
+  })
+}
+
+fn create_dir(path: PathBuf) -> PathBuf {
+  if let Err(e) = std::fs::create_dir_all(&path) {
+    println!("Unable to create directory {}: {}", path.to_string_lossy(), e);
+  }
+  path
+}
 
 /*
 pub struct ImgBuffer {
@@ -439,27 +515,37 @@ fn create_mockup() {
 }
 */
 
-fn main() {
+fn generate(
+  creator_name: &str,
+  part_creator: &dyn PartCreator,
+  quality: usize,
+  size: f32,
+  material: Option<Material>,
+  output_dir: &Path,
+) {
   let start = Instant::now();
-  let part_creator = PartCreator::new();
+  let contours_dir = create_dir(output_dir.join("contours"));
+  let extruded_dir = create_dir(output_dir.join("extruded"));
 
   let mut total_length = 0.0;
   let mut total_square = 0.0;
+  let mut report = BuildReport::new(creator_name);
+  let progress = ConsoleProgress::new();
 
   // let mut look_together = ContourSet::new();
 
   for i in 0..part_creator.faces() {
-    let aabb = part_creator.aabb(i).unwrap_or(AABB::around_zero(200.0));
+    let aabb = part_creator.aabb(i).unwrap_or(AABB::around_zero(size));
 
     let name = part_creator.get_name(i).map(|s| s.to_string()).unwrap_or(format!("part_{i}"));
-    print!("generate {name} in aabb {:?}...", aabb);
-    std::io::stdout().flush().unwrap();
+    progress.log(&format!("generate {name} in aabb {:?}...", aabb));
 
-    let mut cc = ContourCreator::new(aabb, 0.2, 10);
-    let mut topologys = cc.make_topology(&|p| part_creator.get_sticker_index(p, i));
+    let mut cc = ContourCreator::new(aabb, size / quality.max(1) as f32, 10);
+    let mut topologys = cc.make_topology(&|p| part_creator.get_sticker_index(p, i), &progress);
 
     let h = part_creator.get_height(i);
     let count = part_creator.get_count(i);
+    let material = material.or(part_creator.get_face_material(i)).unwrap_or(DEFAULT_MATERIAL);
     let full_name = format!("(THICK={h}, AMOUNT={count}) {name}");
     let single_i = topologys.len() == 1;
     for (k, mut topology) in topologys {
@@ -468,6 +554,7 @@ fn main() {
 
       let figure = topology.to_flat_figure();
       let full_name = if single_i { full_name.clone() } else { format!("{full_name}_{k}") };
+      let part_name = if single_i { name.clone() } else { format!("{name}_{k}") };
       let square = figure.get_square();
       let length = figure.get_length();
 
@@ -479,28 +566,60 @@ fn main() {
         figure.points_count()
       );
 
-      if let Err(msg) =
-        figure.save_to_dxf(&std::path::Path::new("contours").join(format!("{full_name}.dxf")))
-      {
+      if let Err(msg) = figure.save_to_dxf(&contours_dir.join(format!("{full_name}.dxf"))) {
         println!("{}", msg);
       }
 
       let ex = figure.extrude(h);
-      if let Err(msg) =
-        ex.save_to_stl(&std::path::Path::new("extruded").join(format!("{full_name}.stl")))
-      {
+      if let Err(msg) = ex.save_to_stl(&extruded_dir.join(format!("{full_name}.stl"))) {
         println!("{}", msg);
       }
 
       let cc = figure.generate_triangle_contours();
-      if let Err(msg) =
-        cc.save_to_dxf(&std::path::Path::new("contours").join(format!("{full_name}_TR.dxf")))
-      {
+      if let Err(msg) = cc.save_to_dxf(&contours_dir.join(format!("{full_name}_TR.dxf"))) {
         println!("{}", msg);
       }
+
+      let file = format!("contours/{full_name}.dxf");
+      let face = FlatFace { index: i as u32, name: part_name, file, count, thickness: h, material };
+      report.add(PartReport::from_flat_figure(face, &figure, &ex));
     }
   }
 
+  if let Err(msg) = report.save(output_dir) {
+    println!("{}", msg);
+  }
   println!("total {total_length} length, {total_square} square");
+  println!("total {} mass, {} cost", report.totals.mass, report.totals.cost);
   println!("time {}", start.elapsed().as_millis() as f32 / 1000.0);
 }
+
+fn main() {
+  match Cli::parse().command {
+    Command::List => {
+      for c in CREATORS {
+        println!("{}", c.name);
+      }
+    }
+    Command::Generate { name, quality, size, params, material, output_dir } => {
+      let Some(registered) = find_creator(CREATORS, &name) else {
+        println!("Unknown creator {name}, see `list` for available ones");
+        std::process::exit(1);
+      };
+      if params.is_some() && !registered.has_params {
+        let names = creators_with_params(CREATORS).join(", ");
+        println!("Creator {name} has no parameters, `--params` works only for {names}");
+        std::process::exit(1);
+      }
+      match registered.instantiate(params.as_deref(), &output_dir) {
+        Ok(instance) => {
+          generate(&name, instance.creator.as_ref(), quality, size, material, &output_dir)
+        }
+        Err(msg) => {
+          println!("{}", msg);
+          std::process::exit(1);
+        }
+      }
+    }
+  }
+}
@@ -0,0 +1,36 @@
+use std::path::{Path, PathBuf};
+
+// generated models depend only on sources of the generator and of the libraries it is built
+// from, cached models are reused until any of them is changed
+const SOURCES: [&str; 3] = ["src", "../common/src", "../gears/src"];
+
+fn collect(dir: &Path, files: &mut Vec<PathBuf>) {
+  for entry in std::fs::read_dir(dir).unwrap() {
+    let path = entry.unwrap().path();
+    if path.is_dir() {
+      collect(&path, files);
+    } else {
+      files.push(path);
+    }
+  }
+}
+
+fn main() {
+  let root = PathBuf::from(std::env::var_os("CARGO_MANIFEST_DIR").unwrap());
+  let mut files = Vec::new();
+  for dir in SOURCES {
+    println!("cargo:rerun-if-changed={dir}");
+    collect(&root.join(dir), &mut files);
+  }
+  files.sort();
+
+  // 64-bit FNV-1a of relative paths and contents of files
+  let mut hash = 0xcbf29ce484222325u64;
+  for path in files {
+    let name = path.strip_prefix(&root).unwrap().to_string_lossy().replace('\\', "/");
+    for byte in name.bytes().chain(std::fs::read(&path).unwrap()) {
+      hash = (hash ^ byte as u64).wrapping_mul(0x100000001b3);
+    }
+  }
+  println!("cargo:rustc-env=SOURCE_HASH={hash:016x}");
+}
@@ -1,5 +1,6 @@
 use common::common_for_twisty_puzzles::*;
 use common::model::*;
+use common::part_creator::*;
 use common::points3d::*;
 use common::solid::*;
 use fxhash::{FxHashMap, FxHashSet};
@@ -167,44 +168,6 @@ impl ArbCreator {
     }
   }
 
-  pub fn faces(&self) -> usize {
-    self.normals.len()
-  }
-
-  pub fn get_part_index(&self, pos: Point) -> PartIndex {
-    self.get_part_index_impl(pos, self.faces())
-  }
-
-  pub fn get_height(&self, current_normal: usize) -> f32 {
-    0.6
-  }
-
-  pub fn get_count(&self, current_normal: usize) -> usize {
-    1
-  }
-
-  pub fn get_name(&self, current_normal: usize) -> Option<String> {
-    None
-  }
-
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
-    if current_normal == 0 {
-      self.get_part_index_impl(Point { x: pos.x, y: pos.y, z: 0.0 }, self.normals.len())
-    } else if current_normal == 1 {
-      self.get_part_index_impl(Point { x: pos.x, y: pos.y, z: self.sz - 9.5 }, self.normals.len())
-    } else {
-      0
-    }
-  }
-
-  pub fn get_quality() -> usize {
-    200
-  }
-
-  pub fn get_size() -> f32 {
-    100.0
-  }
-
   pub fn get_part_index_impl(&self, mut pos: Point, current_normal: usize) -> PartIndex {
     let r = pos.len();
     if pos.x.abs() > 49.999 || pos.y.abs() > 49.999 || pos.z.abs() > 49.999 {
@@ -455,3 +418,31 @@ impl ArbCreator {
     return index;
   }
 }
+
+impl PartCreator for ArbCreator {
+  fn faces(&self) -> usize {
+    self.normals.len()
+  }
+
+  fn get_part_index(&self, pos: Point) -> PartIndex {
+    self.get_part_index_impl(pos, self.faces())
+  }
+
+  fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
+    if current_normal == 0 {
+      self.get_part_index_impl(Point { x: pos.x, y: pos.y, z: 0.0 }, self.normals.len())
+    } else if current_normal == 1 {
+      self.get_part_index_impl(Point { x: pos.x, y: pos.y, z: self.sz - 9.5 }, self.normals.len())
+    } else {
+      0
+    }
+  }
+
+  fn get_quality(&self) -> usize {
+    200
+  }
+
+  fn get_size(&self) -> f32 {
+    100.0
+  }
+}
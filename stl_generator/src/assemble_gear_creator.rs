@@ -1,10 +1,12 @@
 use common::common_for_twisty_puzzles::*;
 use common::model::*;
+use common::part_creator::*;
 use common::points2d;
 use common::points3d::*;
 use common::solid::*;
 use lazy_static::*;
 use num::Float;
+use serde::{Deserialize, Serialize};
 
 use std::cell::RefCell;
 
@@ -27,8 +29,6 @@ const COS0: f32 = 0.9396926;
 const TAN0: f32 = 0.36397022;
 const T0: f32 = 0.01490438;
 
-const GEAR_ERR: f32 = 0.06;
-
 #[derive(Debug)]
 struct Profile {
   z: f32,
@@ -176,7 +176,7 @@ const TASK_BRUTAL: Task = Task {
 };
 
 impl Task {
-  fn get_part_index(&self, pos: Point) -> PartIndex {
+  fn get_part_index(&self, pos: Point, gear_err: f32) -> PartIndex {
     if pos.z < self.z0 || pos.z > self.z0 + 16.0 {
       return 0;
     }
@@ -185,7 +185,7 @@ impl Task {
     let wall2 = pos.z > self.z0 + 13.0;
     let wall = wall1 || wall2;
     let proj = xy(pos);
-    let ge = GEAR_ERR / self.scale;
+    let ge = gear_err / self.scale;
     let r = proj.len() / self.scale;
     let a = f32::atan2(proj.y, proj.x);
 
@@ -278,40 +278,65 @@ impl Task {
   }
 }
 
-pub struct AssembleGearCreator {}
+#[derive(Serialize, Deserialize)]
+#[serde(default)]
+pub struct AssembleGearParams {
+  gear_err: f32,
+}
+
+impl Default for AssembleGearParams {
+  fn default() -> Self {
+    Self { gear_err: 0.06 }
+  }
+}
+
+pub struct AssembleGearCreator {
+  params: AssembleGearParams,
+}
 
 impl AssembleGearCreator {
   pub fn new() -> Self {
-    Self {}
+    Self::with_params(AssembleGearParams::default())
   }
+}
 
-  pub fn get_part_index(&self, pos: Point) -> PartIndex {
+impl ParamsCreator for AssembleGearCreator {
+  type Params = AssembleGearParams;
+
+  fn with_params(params: AssembleGearParams) -> Self {
+    Self { params }
+  }
+}
+
+impl PartCreator for AssembleGearCreator {
+  fn get_part_index(&self, pos: Point) -> PartIndex {
     if pos.x.abs() > 69.0 || pos.y.abs() > 69.0 || pos.z.abs() > 69.0 {
       return 0;
     }
 
-    let index = TASK_EASY.get_part_index(pos);
+    let gear_err = self.params.gear_err;
+    let index = TASK_EASY.get_part_index(pos, gear_err);
     if index != 0 {
       return index;
     }
 
 /*
-    let index = TASK_MEDIUM.get_part_index(pos);
+    let index = TASK_MEDIUM.get_part_index(pos, gear_err);
     if index != 0 {
       return index;
     }
 
-    let index = TASK_HARD.get_part_index(pos);
+    let index = TASK_HARD.get_part_index(pos, gear_err);
     if index != 0 {
       return index;
     }
 
-    let index = TASK_NIHTMARE.get_part_index(pos);
+    let index = TASK_NIHTMARE.get_part_index(pos, gear_err);
     if index != 0 {
       return index;
     }
 
-    let index = TASK_BRUTAL.get_part_index(pos);
+    let index = TASK_BRUTAL.get_part_index(pos, gear_err);
     if index != 0 {
       return index;
     }*/
@@ -319,32 +344,20 @@ impl AssembleGearCreator {
     return 0;
   }
 
-  pub fn get_height(&self, current_normal: usize) -> f32 {
-    0.6
-  }
-
-  pub fn get_count(&self, current_normal: usize) -> usize {
+  fn faces(&self) -> usize {
     1
   }
 
-  pub fn faces(&self) -> usize {
-    1
-  }
-
-  pub fn get_name(&self, current_normal: usize) -> Option<String> {
-    None
-  }
-
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
+  fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
     let pos = Point { x: pos.x, y: 0.0, z: pos.y };
     self.get_part_index(pos)
   }
 
-  pub fn get_quality() -> usize {
+  fn get_quality(&self) -> usize {
     384
   }
 
-  pub fn get_size() -> f32 {
+  fn get_size(&self) -> f32 {
     140.0
   }
 }
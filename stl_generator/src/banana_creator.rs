@@ -1,4 +1,5 @@
 use common::model::*;
+use common::part_creator::*;
 use common::points3d::*;
 use common::solid::*;
 
@@ -45,34 +46,6 @@ impl BananaCreator {
     }
   }
 
-  pub fn faces(&self) -> usize {
-    0
-  }
-
-  pub fn get_height(&self, current_normal: usize) -> f32 {
-    0.6
-  }
-
-  pub fn get_count(&self, current_normal: usize) -> usize {
-    1
-  }
-
-  pub fn get_name(&self, current_normal: usize) -> Option<String> {
-    None
-  }
-
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
-    0
-  }
-
-  pub fn get_quality() -> usize {
-    120
-  }
-
-  pub fn get_size() -> f32 {
-    120.0
-  }
-
   pub fn get_part_index_impl(&self, pos: Point, current_normal: usize) -> PartIndex {
     let r = pos.len();
     let depth = self.ball_radius - r;
@@ -124,8 +97,18 @@ impl BananaCreator {
     }
     return index;
   }
+}
+
+impl PartCreator for BananaCreator {
+  fn get_quality(&self) -> usize {
+    120
+  }
+
+  fn get_size(&self) -> f32 {
+    120.0
+  }
 
-  pub fn get_part_index(&self, pos: Point) -> PartIndex {
+  fn get_part_index(&self, pos: Point) -> PartIndex {
     self.get_part_index_impl(pos, self.axis.len())
   }
 }
@@ -1,5 +1,6 @@
 use common::common_for_twisty_puzzles::*;
 use common::model::*;
+use common::part_creator::*;
 use common::points3d::*;
 use common::solid::*;
 use fxhash::FxHashMap;
@@ -37,38 +38,6 @@ impl BathCreator {
     Self { tiles }
   }
 
-  pub fn faces(&self) -> usize {
-    0
-  }
-
-  pub fn get_part_index(&self, pos: Point) -> PartIndex {
-    self.get_part_index_impl(pos, self.faces())
-  }
-
-  pub fn get_height(&self, current_normal: usize) -> f32 {
-    0.6
-  }
-
-  pub fn get_count(&self, current_normal: usize) -> usize {
-    1
-  }
-
-  pub fn get_name(&self, current_normal: usize) -> Option<String> {
-    None
-  }
-
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
-    0
-  }
-
-  pub fn get_quality() -> usize {
-    150
-  }
-
-  pub fn get_size() -> f32 {
-    35.0
-  }
-
   fn get_tile(pos: (f32, f32), tile_size: (f32, f32), stride: usize) -> usize {
     let tile_x = f32::max(pos.0 / tile_size.0, 0.0);
     let tile_y = f32::max(pos.1 / tile_size.1, 0.0);
@@ -346,3 +315,17 @@ impl BathCreator {
     0
   }
 }
+
+impl PartCreator for BathCreator {
+  fn get_part_index(&self, pos: Point) -> PartIndex {
+    self.get_part_index_impl(pos, self.faces())
+  }
+
+  fn get_quality(&self) -> usize {
+    150
+  }
+
+  fn get_size(&self) -> f32 {
+    35.0
+  }
+}
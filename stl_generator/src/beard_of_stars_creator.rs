@@ -1,4 +1,5 @@
 use crate::model::*;
+use crate::part_creator::*;
 use crate::points3d::*;
 use crate::solid::*;
 use num::Float;
@@ -77,18 +78,6 @@ impl BeardOfStarsCreator {
     Self { axis, axis_add, normals, split_angle, split_angle2 }
   }
 
-  pub fn faces(&self) -> usize {
-    self.normals.len()
-  }
-
-  pub fn get_part_index(&self, pos: Point) -> PartIndex {
-    self.get_part_index_impl(pos, self.normals.len())
-  }
-
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
-    0
-  }
-
   pub fn get_part_index_impl(&self, pos: Point, current_normal: usize) -> PartIndex {
     let r = pos.len();
     if pos.x.abs() > 64.999 || pos.y.abs() > 64.999 || pos.z.abs() > 64.999 {
@@ -192,3 +181,13 @@ impl BeardOfStarsCreator {
     return index;
   }
 }
+
+impl PartCreator for BeardOfStarsCreator {
+  fn faces(&self) -> usize {
+    self.normals.len()
+  }
+
+  fn get_part_index(&self, pos: Point) -> PartIndex {
+    self.get_part_index_impl(pos, self.normals.len())
+  }
+}
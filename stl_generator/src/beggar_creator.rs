@@ -1,5 +1,6 @@
 use common::common_for_twisty_puzzles::*;
 use common::model::*;
+use common::part_creator::*;
 use common::points3d::*;
 use common::solid::*;
 use num::Float;
@@ -15,38 +16,6 @@ impl BeggarCreator {
     Self {}
   }
 
-  pub fn faces(&self) -> usize {
-    0
-  }
-
-  pub fn get_part_index(&self, pos: Point) -> PartIndex {
-    self.get_part_index_impl(pos, 1)
-  }
-
-  pub fn get_height(&self, current_normal: usize) -> f32 {
-    0.6
-  }
-
-  pub fn get_count(&self, current_normal: usize) -> usize {
-    1
-  }
-
-  pub fn get_name(&self, current_normal: usize) -> Option<&str> {
-    None
-  }
-
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
-    0
-  }
-
-  pub fn get_quality() -> usize {
-    128
-  }
-
-  pub fn get_size() -> f32 {
-    120.0
-  }
-
   pub fn get_part_index_impl(&self, pos: Point, current_normal: usize) -> PartIndex {
     if pos.x.abs() > 44.999 || pos.y.abs() > 44.999 || pos.z.abs() > 44.999 {
       return 0;
@@ -285,3 +254,17 @@ impl BeggarCreator {
     return 0;
   }
 }
+
+impl PartCreator for BeggarCreator {
+  fn get_part_index(&self, pos: Point) -> PartIndex {
+    self.get_part_index_impl(pos, 1)
+  }
+
+  fn get_quality(&self) -> usize {
+    128
+  }
+
+  fn get_size(&self) -> f32 {
+    120.0
+  }
+}
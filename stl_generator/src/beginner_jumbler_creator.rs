@@ -1,5 +1,6 @@
 use common::common_for_twisty_puzzles::*;
 use common::model::*;
+use common::part_creator::*;
 use common::points3d::*;
 use common::solid::*;
 use fxhash::FxHashMap;
@@ -243,47 +244,10 @@ impl BeginnerJumblerCreator {
     result
   }
 
-  pub fn faces(&self) -> usize {
-    self.normals.len() + 1
-  }
-
-  pub fn get_part_index(&self, pos: Point) -> PartIndex {
-    self.get_part_index_impl(pos, self.faces(), false)
-  }
-
   pub fn get_part_index_for_check(&self, pos: Point, current_normal: usize) -> PartIndex {
     self.get_part_index_impl(pos, current_normal, true)
   }
 
-  pub fn get_height(&self, current_normal: usize) -> f32 {
-    0.6
-  }
-
-  pub fn get_count(&self, current_normal: usize) -> usize {
-    1
-  }
-
-  pub fn get_name(&self, current_normal: usize) -> Option<String> {
-    None
-  }
-
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
-    if current_normal < self.normals.len() {
-      0
-    } else {
-      let pos = Point { x: pos.x, y: 0.0, z: pos.y };
-      self.get_part_index_impl(pos, current_normal, false)
-    }
-  }
-
-  pub fn get_quality() -> usize {
-    320
-  }
-
-  pub fn get_size() -> f32 {
-    100.0
-  }
-
   fn get_part_index_impl(&self, pos: Point, current_normal: usize, side_check: bool) -> PartIndex {
     let r = pos.len();
     if pos.x.abs() > 49.999 || pos.y.abs() > 49.999 || pos.z.abs() > 49.999 {
@@ -500,3 +464,30 @@ impl BeginnerJumblerCreator {
     return index;
   }
 }
+
+impl PartCreator for BeginnerJumblerCreator {
+  fn faces(&self) -> usize {
+    self.normals.len() + 1
+  }
+
+  fn get_part_index(&self, pos: Point) -> PartIndex {
+    self.get_part_index_impl(pos, self.faces(), false)
+  }
+
+  fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
+    if current_normal < self.normals.len() {
+      0
+    } else {
+      let pos = Point { x: pos.x, y: 0.0, z: pos.y };
+      self.get_part_index_impl(pos, current_normal, false)
+    }
+  }
+
+  fn get_quality(&self) -> usize {
+    320
+  }
+
+  fn get_size(&self) -> f32 {
+    100.0
+  }
+}
@@ -1,5 +1,6 @@
 use common::common_for_twisty_puzzles::*;
 use common::model::*;
+use common::part_creator::*;
 use common::points3d::*;
 use common::solid::*;
 use num::Float;
@@ -195,45 +196,6 @@ impl PrismCreator {
     Self { groove, normals, axis_pos, axis_neg }
   }
 
-  pub fn faces(&self) -> usize {
-    self.normals.len()
-  }
-
-  pub fn get_part_index(&self, pos: Point) -> PartIndex {
-    self.get_part_index_impl(pos, self.faces())
-  }
-
-  pub fn get_height(&self, current_normal: usize) -> f32 {
-    0.6
-  }
-
-  pub fn get_count(&self, current_normal: usize) -> usize {
-    1
-  }
-
-  pub fn get_name(&self, current_normal: usize) -> Option<String> {
-    None
-  }
-
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
-    let n0 = self.normals[current_normal];
-    let n1 = n0.any_perp().norm();
-    let n2 = cross(n0, n1);
-
-    let last_groove = self.groove[self.groove.len() - 2];
-    let sz = last_groove + 2.2;
-    let p = n0.scale(sz) + n1.scale(pos.x) + n2.scale(pos.y);
-    (self.get_part_index_impl(p, current_normal) > 0) as PartIndex
-  }
-
-  pub fn get_quality() -> usize {
-    128
-  }
-
-  pub fn get_size() -> f32 {
-    80.0
-  }
-
   pub fn get_part_index_impl(&self, pos: Point, current_normal: usize) -> PartIndex {
     let r = pos.len();
 
@@ -442,3 +404,32 @@ impl PrismCreator {
     return index;
   }
 }
+
+impl PartCreator for PrismCreator {
+  fn faces(&self) -> usize {
+    self.normals.len()
+  }
+
+  fn get_part_index(&self, pos: Point) -> PartIndex {
+    self.get_part_index_impl(pos, self.faces())
+  }
+
+  fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
+    let n0 = self.normals[current_normal];
+    let n1 = n0.any_perp().norm();
+    let n2 = cross(n0, n1);
+
+    let last_groove = self.groove[self.groove.len() - 2];
+    let sz = last_groove + 2.2;
+    let p = n0.scale(sz) + n1.scale(pos.x) + n2.scale(pos.y);
+    (self.get_part_index_impl(p, current_normal) > 0) as PartIndex
+  }
+
+  fn get_quality(&self) -> usize {
+    128
+  }
+
+  fn get_size(&self) -> f32 {
+    80.0
+  }
+}
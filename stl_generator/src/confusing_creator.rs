@@ -1,5 +1,6 @@
 use common::common_for_twisty_puzzles::*;
 use common::model::*;
+use common::part_creator::*;
 use common::points3d::*;
 use common::solid::*;
 use num::Float;
@@ -77,43 +78,6 @@ impl ConfusingCreator {
     Self { axis, axis1, axis2, mid_r, groove, axis_pos, axis_neg }
   }
 
-  pub fn faces(&self) -> usize {
-    0
-  }
-
-  pub fn get_part_index(&self, pos: Point) -> PartIndex {
-    self.get_part_index_impl(pos, 6)
-  }
-
-  pub fn get_height(&self, current_normal: usize) -> f32 {
-    0.6
-  }
-
-  pub fn get_count(&self, current_normal: usize) -> usize {
-    1
-  }
-
-  pub fn get_name(&self, current_normal: usize) -> Option<String> {
-    None
-  }
-
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
-    let n = self.axis[current_normal];
-    let n1 = n.any_perp();
-    let n2 = cross(n, n1);
-    let pos = n.scale(29.9) + n1.scale(pos.x) + n2.scale(pos.y);
-    let result = self.get_part_index_impl(pos, current_normal);
-    (result > 0) as PartIndex
-  }
-
-  pub fn get_quality() -> usize {
-    384
-  }
-
-  pub fn get_size() -> f32 {
-    90.0
-  }
-
   pub fn get_part_index_impl(&self, pos: Point, current_normal: usize) -> PartIndex {
     if pos.x.abs() > 44.999 || pos.y.abs() > 44.999 || pos.z.abs() > 44.999 {
       return 0;
@@ -390,3 +354,26 @@ impl ConfusingCreator {
     return index;
   }
 }
+
+impl PartCreator for ConfusingCreator {
+  fn get_part_index(&self, pos: Point) -> PartIndex {
+    self.get_part_index_impl(pos, 6)
+  }
+
+  fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
+    let n = self.axis[current_normal];
+    let n1 = n.any_perp();
+    let n2 = cross(n, n1);
+    let pos = n.scale(29.9) + n1.scale(pos.x) + n2.scale(pos.y);
+    let result = self.get_part_index_impl(pos, current_normal);
+    (result > 0) as PartIndex
+  }
+
+  fn get_quality(&self) -> usize {
+    384
+  }
+
+  fn get_size(&self) -> f32 {
+    90.0
+  }
+}
@@ -1,4 +1,5 @@
 use common::model::*;
+use common::part_creator::*;
 use common::points3d::*;
 use common::solid::*;
 use num::Float;
@@ -83,47 +84,6 @@ impl CubeCreator {
     Self { a, n, hs }
   }
 
-  pub fn faces(&self) -> usize {
-    2
-  }
-
-  pub fn get_height(&self, current_normal: usize) -> f32 {
-    0.6
-  }
-
-  pub fn get_count(&self, current_normal: usize) -> usize {
-    1
-  }
-
-  pub fn get_name(&self, current_normal: usize) -> Option<String> {
-    None
-  }
-
-  pub fn get_quality() -> usize {
-    120
-  }
-
-  pub fn get_size() -> f32 {
-    100.0
-  }
-
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
-    if current_normal == 0 {
-      let n = self.n[0];
-      let n1 = n.any_perp().norm();
-      let n2 = cross(n, n1);
-      self.get_part_index_impl(n1.scale(pos.x) + n2.scale(pos.y), true)
-    } else if current_normal == 1 {
-      self.get_part_index_impl(Point { x: pos.x, y: 0.0, z: pos.y }, true)
-    } else {
-      0
-    }
-  }
-
-  pub fn get_part_index(&self, pos: Point) -> PartIndex {
-    self.get_part_index_impl(pos, false)
-  }
-
   fn get_part_index_impl(&self, mut pos: Point, for_section: bool) -> PartIndex {
     let r = pos.len();
     if r < 22.0 {
@@ -256,3 +216,34 @@ impl CubeCreator {
     return index;
   }
 }
+
+impl PartCreator for CubeCreator {
+  fn faces(&self) -> usize {
+    2
+  }
+
+  fn get_quality(&self) -> usize {
+    120
+  }
+
+  fn get_size(&self) -> f32 {
+    100.0
+  }
+
+  fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
+    if current_normal == 0 {
+      let n = self.n[0];
+      let n1 = n.any_perp().norm();
+      let n2 = cross(n, n1);
+      self.get_part_index_impl(n1.scale(pos.x) + n2.scale(pos.y), true)
+    } else if current_normal == 1 {
+      self.get_part_index_impl(Point { x: pos.x, y: 0.0, z: pos.y }, true)
+    } else {
+      0
+    }
+  }
+
+  fn get_part_index(&self, pos: Point) -> PartIndex {
+    self.get_part_index_impl(pos, false)
+  }
+}
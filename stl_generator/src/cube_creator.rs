@@ -1,4 +1,5 @@
 use crate::model::*;
+use crate::part_creator::*;
 use crate::points3d::*;
 use crate::solid::*;
 
@@ -56,22 +57,6 @@ impl CubeCreator {
     Self { axis, normals, n_basis, l, screw_diam, head_diam, thread_diam, axis_dst }
   }
 
-  pub fn faces(&self) -> usize {
-    self.normals.len()
-  }
-
-  pub fn get_part_index(&self, pos: Point) -> PartIndex {
-    self.get_part_index_impl(pos, self.normals.len())
-  }
-
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
-    let n = self.normals[current_normal];
-    let (n1, n2) = self.n_basis[current_normal];
-    let pos = n.scale(35.0 / n.sqr_len()) + n1.scale(pos.x) + n2.scale(pos.y);
-    let result = self.get_part_index_impl(pos, current_normal);
-    (result > 0) as PartIndex
-  }
-
   pub fn get_part_index_impl(&self, pos: Point, current_normal: usize) -> PartIndex {
     let r = pos.len();
     if pos.x.abs() > 64.999 || pos.y.abs() > 64.999 || pos.z.abs() > 64.999 {
@@ -204,3 +189,21 @@ impl CubeCreator {
     return index;
   }
 }
+
+impl PartCreator for CubeCreator {
+  fn faces(&self) -> usize {
+    self.normals.len()
+  }
+
+  fn get_part_index(&self, pos: Point) -> PartIndex {
+    self.get_part_index_impl(pos, self.normals.len())
+  }
+
+  fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
+    let n = self.normals[current_normal];
+    let (n1, n2) = self.n_basis[current_normal];
+    let pos = n.scale(35.0 / n.sqr_len()) + n1.scale(pos.x) + n2.scale(pos.y);
+    let result = self.get_part_index_impl(pos, current_normal);
+    (result > 0) as PartIndex
+  }
+}
@@ -1,5 +1,6 @@
 use common::common_for_twisty_puzzles::*;
 use common::model::*;
+use common::part_creator::*;
 use common::points3d::*;
 use common::solid::*;
 use num::Float;
@@ -80,43 +81,6 @@ impl CubeCreator {
     Self { axis, axis1, axis2, groove, groove_s, l, axis_pos, axis_neg }
   }
 
-  pub fn faces(&self) -> usize {
-    6
-  }
-
-  pub fn get_part_index(&self, pos: Point) -> PartIndex {
-    self.get_part_index_impl(pos, 6)
-  }
-
-  pub fn get_height(&self, current_normal: usize) -> f32 {
-    0.6
-  }
-
-  pub fn get_count(&self, current_normal: usize) -> usize {
-    1
-  }
-
-  pub fn get_name(&self, current_normal: usize) -> Option<String> {
-    None
-  }
-
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
-    let n = self.axis[current_normal];
-    let n1 = n.any_perp();
-    let n2 = cross(n, n1);
-    let pos = n.scale(self.l[current_normal] - 0.01) + n1.scale(pos.x) + n2.scale(pos.y);
-    let result = self.get_part_index_impl(pos, current_normal);
-    (result > 0) as PartIndex
-  }
-
-  pub fn get_quality() -> usize {
-    128
-  }
-
-  pub fn get_size() -> f32 {
-    60.0
-  }
-
   pub fn get_part_index_impl(&self, pos: Point, current_normal: usize) -> PartIndex {
     let r = pos.len();
     if pos.x.abs() > 29.999 || pos.y.abs() > 29.999 || pos.z.abs() > 29.999 {
@@ -298,3 +262,30 @@ impl CubeCreator {
     return index;
   }
 }
+
+impl PartCreator for CubeCreator {
+  fn faces(&self) -> usize {
+    6
+  }
+
+  fn get_part_index(&self, pos: Point) -> PartIndex {
+    self.get_part_index_impl(pos, 6)
+  }
+
+  fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
+    let n = self.axis[current_normal];
+    let n1 = n.any_perp();
+    let n2 = cross(n, n1);
+    let pos = n.scale(self.l[current_normal] - 0.01) + n1.scale(pos.x) + n2.scale(pos.y);
+    let result = self.get_part_index_impl(pos, current_normal);
+    (result > 0) as PartIndex
+  }
+
+  fn get_quality(&self) -> usize {
+    128
+  }
+
+  fn get_size(&self) -> f32 {
+    60.0
+  }
+}
@@ -1,5 +1,6 @@
 use common::common_for_twisty_puzzles::*;
 use common::model::*;
+use common::part_creator::*;
 use common::points3d::*;
 use common::solid::*;
 
@@ -86,41 +87,6 @@ impl DecaminxCreator {
     }
   }
 
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
-    let n = self.normals[current_normal];
-    let (n1, n2) = self.n_basis[current_normal];
-
-    let maxd = 35.0;
-    let pos = n.scale(maxd / n.sqr_len()) + n1.scale(pos.x) + n2.scale(pos.y);
-    let result = self.get_part_index_impl(pos, current_normal);
-
-    (result > 0) as PartIndex
-  }
-
-  pub fn faces(&self) -> usize {
-    self.axis.len()
-  }
-
-  pub fn get_height(&self, current_normal: usize) -> f32 {
-    0.6
-  }
-
-  pub fn get_quality() -> usize {
-    128
-  }
-
-  pub fn get_size() -> f32 {
-    90.0
-  }
-
-  pub fn get_count(&self, current_normal: usize) -> usize {
-    1
-  }
-
-  pub fn get_name(&self, current_normal: usize) -> Option<String> {
-    None
-  }
-
   pub fn get_part_index_impl(&self, pos: Point, current_normal: usize) -> PartIndex {
     let r = pos.len();
     let ball_r = self.groove[1] - 10.0;
@@ -319,8 +285,33 @@ impl DecaminxCreator {
 
     return index;
   }
+}
+
+impl PartCreator for DecaminxCreator {
+  fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
+    let n = self.normals[current_normal];
+    let (n1, n2) = self.n_basis[current_normal];
+
+    let maxd = 35.0;
+    let pos = n.scale(maxd / n.sqr_len()) + n1.scale(pos.x) + n2.scale(pos.y);
+    let result = self.get_part_index_impl(pos, current_normal);
+
+    (result > 0) as PartIndex
+  }
+
+  fn faces(&self) -> usize {
+    self.axis.len()
+  }
+
+  fn get_quality(&self) -> usize {
+    128
+  }
+
+  fn get_size(&self) -> f32 {
+    90.0
+  }
 
-  pub fn get_part_index(&self, pos: Point) -> PartIndex {
+  fn get_part_index(&self, pos: Point) -> PartIndex {
     self.get_part_index_impl(pos, self.axis.len())
   }
 }
@@ -1,4 +1,5 @@
 use crate::model::*;
+use crate::part_creator::*;
 use crate::points3d::*;
 use crate::solid::*;
 
@@ -74,32 +75,6 @@ impl DecaminxCreator {
     }
   }
 
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
-    let n = self.normals[current_normal];
-    let (n1, n2) = self.n_basis[current_normal];
-
-    let pos = n.scale(28.6 / n.sqr_len()) + n1.scale(pos.x) + n2.scale(pos.y);
-    let result = self.get_part_index_impl(pos, current_normal);
-
-    (result > 0) as PartIndex
-  }
-
-  pub fn faces(&self) -> usize {
-    self.axis.len()
-  }
-
-  pub fn get_height(current_normal: usize) -> f32 {
-    0.6
-  }
-
-  pub fn get_quality() -> usize {
-    128
-  }
-
-  pub fn get_size() -> f32 {
-    70.0
-  }
-
   pub fn get_part_index_impl(&self, pos: Point, current_normal: usize) -> PartIndex {
     let r = pos.len();
     let depth = self.ball_radius - r;
@@ -278,8 +253,32 @@ impl DecaminxCreator {
 
     return index;
   }
+}
+
+impl PartCreator for DecaminxCreator {
+  fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
+    let n = self.normals[current_normal];
+    let (n1, n2) = self.n_basis[current_normal];
+
+    let pos = n.scale(28.6 / n.sqr_len()) + n1.scale(pos.x) + n2.scale(pos.y);
+    let result = self.get_part_index_impl(pos, current_normal);
+
+    (result > 0) as PartIndex
+  }
+
+  fn faces(&self) -> usize {
+    self.axis.len()
+  }
+
+  fn get_quality(&self) -> usize {
+    128
+  }
+
+  fn get_size(&self) -> f32 {
+    70.0
+  }
 
-  pub fn get_part_index(&self, pos: Point) -> PartIndex {
+  fn get_part_index(&self, pos: Point) -> PartIndex {
     self.get_part_index_impl(pos, self.axis.len())
   }
 }
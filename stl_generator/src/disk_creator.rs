@@ -1,3 +1,4 @@
+use crate::part_creator::*;
 use crate::points3d::*;
 use crate::solid::*;
 
@@ -55,12 +56,14 @@ impl DiskCreator {
 
     Self { disk_centers, min_p, max_p, mag_deltas, deltas, disk_states_b, disk_states_e }
   }
+}
 
-  pub fn faces(&self) -> usize {
+impl PartCreator for DiskCreator {
+  fn faces(&self) -> usize {
     2
   }
 
-  pub fn get_sticker_index(
+  fn get_sticker_index(
     &self,
     mut pos: crate::points2d::Point,
     current_normal: usize,
@@ -100,7 +103,7 @@ impl DiskCreator {
     return 0;
   }
 
-  pub fn get_part_index(&self, pos: Point) -> PartIndex {
+  fn get_part_index(&self, pos: Point) -> PartIndex {
     if pos.z < 0.0 {
       return 0;
       if pos.z > -4.0 {
@@ -1,5 +1,6 @@
 use common::common_for_twisty_puzzles::*;
 use common::model::*;
+use common::part_creator::*;
 use common::points3d::*;
 use common::solid::*;
 use fxhash::FxHashSet;
@@ -107,49 +108,6 @@ impl HouseCreator {
     Self { axis, axis1, axis2, sz, closed_axes, normals, groove, axis_pos, axis_neg }
   }
 
-  pub fn faces(&self) -> usize {
-    self.normals.len() + 1
-  }
-
-  pub fn get_part_index(&self, pos: Point) -> PartIndex {
-    self.get_part_index_impl(pos, self.normals.len() + 1)
-  }
-
-  pub fn get_height(&self, current_normal: usize) -> f32 {
-    0.6
-  }
-
-  pub fn get_count(&self, current_normal: usize) -> usize {
-    1
-  }
-
-  pub fn get_name(&self, current_normal: usize) -> Option<String> {
-    None
-  }
-
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
-    if current_normal < self.normals.len() {
-      let n = self.normals[current_normal];
-      let n1 = n.any_perp();
-      let n2 = cross(n, n1);
-      let sz = self.sz;
-      let pos = n.scale(sz - 0.5) + n1.scale(pos.x) + n2.scale(pos.y);
-      let result = self.get_part_index_impl(pos, current_normal);
-      (result > 0) as PartIndex
-    } else {
-      let pos = Point { x: pos.x, y: pos.y, z: 0.0 };
-      self.get_part_index_impl(pos, current_normal)
-    }
-  }
-
-  pub fn get_quality() -> usize {
-    384
-  }
-
-  pub fn get_size() -> f32 {
-    120.0
-  }
-
   pub fn get_part_index_impl(&self, pos: Point, current_normal: usize) -> PartIndex {
     if pos.x.abs() > 59.999 || pos.y.abs() > 59.999 || pos.z.abs() > 59.999 {
       return 0;
@@ -347,3 +305,36 @@ impl HouseCreator {
     index
   }
 }
+
+impl PartCreator for HouseCreator {
+  fn faces(&self) -> usize {
+    self.normals.len() + 1
+  }
+
+  fn get_part_index(&self, pos: Point) -> PartIndex {
+    self.get_part_index_impl(pos, self.normals.len() + 1)
+  }
+
+  fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
+    if current_normal < self.normals.len() {
+      let n = self.normals[current_normal];
+      let n1 = n.any_perp();
+      let n2 = cross(n, n1);
+      let sz = self.sz;
+      let pos = n.scale(sz - 0.5) + n1.scale(pos.x) + n2.scale(pos.y);
+      let result = self.get_part_index_impl(pos, current_normal);
+      (result > 0) as PartIndex
+    } else {
+      let pos = Point { x: pos.x, y: pos.y, z: 0.0 };
+      self.get_part_index_impl(pos, current_normal)
+    }
+  }
+
+  fn get_quality(&self) -> usize {
+    384
+  }
+
+  fn get_size(&self) -> f32 {
+    120.0
+  }
+}
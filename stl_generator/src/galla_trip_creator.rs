@@ -1,5 +1,6 @@
 use crate::common_for_twisty_puzzles::*;
 use crate::model::*;
+use crate::part_creator::*;
 use crate::points3d::*;
 use crate::solid::*;
 use fxhash::*;
@@ -111,67 +112,6 @@ impl GallaTripCreator {
     Self { axis, long_edges, groove, axis_pos, axis_neg, k: 0.006 }
   }
 
-  pub fn faces(&self) -> usize {
-    1
-  }
-
-  pub fn get_part_index(&self, pos: Point) -> PartIndex {
-    self.get_part_index_impl(pos, usize::MAX)
-  }
-
-  pub fn get_height(current_normal: usize) -> f32 {
-    0.6
-  }
-
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
-    let n = &self.axis[current_normal];
-
-    fn sinc(x: f32) -> f32 {
-      if x == 0.0 {
-        1.0
-      } else {
-        x.sin() / x
-      }
-    }
-
-    fn versinc(x: f32) -> f32 {
-      if x == 0.0 {
-        0.0
-      } else {
-        (1.0 - x.cos()) / x
-      }
-    }
-
-    let k = self.k;
-    let k2 = k * 2.0;
-    let last_groove = self.groove[self.groove.len() - 2];
-    let max = last_groove + 2.2;
-
-    let r = pos.len();
-    let a = r * k2;
-
-    let pos = pos.scale(sinc(a));
-    let pos = n.0.scale(max - r * versinc(a)) + n.1.scale(pos.x) + n.2.scale(pos.y);
-
-    let r = pos.len();
-    let d = dot(pos, n.0);
-
-    let control_c = n.0.scale(max - k2.recip());
-    let delta = d + (max * max + r * r) * k - (2.0 * d * k + 1.0) * max;
-
-    let result = self.get_part_index_impl(pos, current_normal);
-
-    (result > 0) as PartIndex
-  }
-
-  pub fn get_quality() -> usize {
-    256
-  }
-
-  pub fn get_size() -> f32 {
-    150.0
-  }
-
   pub fn get_part_index_impl(&self, pos: Point, current_normal: usize) -> PartIndex {
     let r = pos.len();
     if pos.x.abs() > 49.999 || pos.y.abs() > 49.999 || pos.z.abs() > 49.999 {
@@ -417,3 +357,62 @@ impl GallaTripCreator {
     return index;
   }
 }
+
+impl PartCreator for GallaTripCreator {
+  fn faces(&self) -> usize {
+    1
+  }
+
+  fn get_part_index(&self, pos: Point) -> PartIndex {
+    self.get_part_index_impl(pos, usize::MAX)
+  }
+
+  fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
+    let n = &self.axis[current_normal];
+
+    fn sinc(x: f32) -> f32 {
+      if x == 0.0 {
+        1.0
+      } else {
+        x.sin() / x
+      }
+    }
+
+    fn versinc(x: f32) -> f32 {
+      if x == 0.0 {
+        0.0
+      } else {
+        (1.0 - x.cos()) / x
+      }
+    }
+
+    let k = self.k;
+    let k2 = k * 2.0;
+    let last_groove = self.groove[self.groove.len() - 2];
+    let max = last_groove + 2.2;
+
+    let r = pos.len();
+    let a = r * k2;
+
+    let pos = pos.scale(sinc(a));
+    let pos = n.0.scale(max - r * versinc(a)) + n.1.scale(pos.x) + n.2.scale(pos.y);
+
+    let r = pos.len();
+    let d = dot(pos, n.0);
+
+    let control_c = n.0.scale(max - k2.recip());
+    let delta = d + (max * max + r * r) * k - (2.0 * d * k + 1.0) * max;
+
+    let result = self.get_part_index_impl(pos, current_normal);
+
+    (result > 0) as PartIndex
+  }
+
+  fn get_quality(&self) -> usize {
+    256
+  }
+
+  fn get_size(&self) -> f32 {
+    150.0
+  }
+}
@@ -1,5 +1,6 @@
 use common::common_for_twisty_puzzles::*;
 use common::model::*;
+use common::part_creator::*;
 use common::points3d::*;
 use common::solid::*;
 use num::Float;
@@ -97,44 +98,6 @@ impl GhostPyraCreator {
     Self { axis, trivials, normals, groove, axis_pos, axis_neg, n_dists }
   }
 
-  pub fn get_height(&self, current_normal: usize) -> f32 {
-    1.5
-  }
-
-  pub fn get_count(&self, current_normal: usize) -> usize {
-    1
-  }
-
-  pub fn get_name(&self, current_normal: usize) -> Option<String> {
-    None
-  }
-
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
-    if pos.x.abs() > 99.0 || pos.y.abs() > 99.0 {
-      return 0;
-    }
-    let n = self.normals[current_normal];
-    let sz = n.1;
-    let n = n.0;
-    let n1 = n.any_perp().norm();
-    let n2 = cross(n, n1);
-
-    let pos = n.scale(sz - 0.01) + n1.scale(pos.x) + n2.scale(pos.y);
-    self.get_part_index_impl(pos, current_normal)
-  }
-
-  pub fn faces(&self) -> usize {
-    self.normals.len()
-  }
-
-  pub fn get_quality() -> usize {
-    320
-  }
-
-  pub fn get_size() -> f32 {
-    120.0
-  }
-
   pub fn get_part_index_impl(&self, pos: Point, current_normal: usize) -> PartIndex {
     if pos.x.abs() > 59.0 || pos.y.abs() > 59.0 || pos.z.abs() > 59.0 {
       return 0;
@@ -296,8 +259,40 @@ impl GhostPyraCreator {
 
     return index;
   }
+}
+
+impl PartCreator for GhostPyraCreator {
+  fn get_height(&self, current_normal: usize) -> f32 {
+    1.5
+  }
+
+  fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
+    if pos.x.abs() > 99.0 || pos.y.abs() > 99.0 {
+      return 0;
+    }
+    let n = self.normals[current_normal];
+    let sz = n.1;
+    let n = n.0;
+    let n1 = n.any_perp().norm();
+    let n2 = cross(n, n1);
+
+    let pos = n.scale(sz - 0.01) + n1.scale(pos.x) + n2.scale(pos.y);
+    self.get_part_index_impl(pos, current_normal)
+  }
+
+  fn faces(&self) -> usize {
+    self.normals.len()
+  }
+
+  fn get_quality(&self) -> usize {
+    320
+  }
+
+  fn get_size(&self) -> f32 {
+    120.0
+  }
 
-  pub fn get_part_index(&self, pos: Point) -> PartIndex {
+  fn get_part_index(&self, pos: Point) -> PartIndex {
     self.get_part_index_impl(pos, self.axis.len())
   }
 }
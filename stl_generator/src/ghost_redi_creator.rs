@@ -1,5 +1,6 @@
 use common::common_for_twisty_puzzles::*;
 use common::model::*;
+use common::part_creator::*;
 use common::points3d::*;
 use common::solid::*;
 use num::Float;
@@ -121,50 +122,6 @@ impl RediCreator {
     Self { axis, axis1, axis2, normals, groove0, groove1, axis_pos, axis_neg, axis_seq }
   }
 
-  pub fn faces(&self) -> usize {
-    self.normals.len()
-  }
-
-  pub fn get_part_index(&self, pos: Point) -> PartIndex {
-    self.get_part_index_impl(pos, self.faces())
-  }
-
-  pub fn get_height(&self, current_normal: usize) -> f32 {
-    2.0
-  }
-
-  pub fn get_count(&self, current_normal: usize) -> usize {
-    1
-  }
-
-  pub fn get_name(&self, current_normal: usize) -> Option<String> {
-    None
-  }
-
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
-    let last_groove = self.groove0[self.groove0.len() - 2];
-    let sz = last_groove + 2.19;
-    if pos.x.abs() > sz + 4.0 || pos.y.abs() > sz + 4.0 {
-      return 0;
-    }
-
-    let n = self.normals[current_normal];
-    let n1 = n.any_perp().norm();
-    let n2 = cross(n, n1);
-
-    let pos = n.scale(sz) + n1.scale(pos.x) + n2.scale(pos.y);
-    let result = self.get_part_index_impl(pos, current_normal);
-    (result == 0) as PartIndex
-  }
-
-  pub fn get_quality() -> usize {
-    120
-  }
-
-  pub fn get_size() -> f32 {
-    100.0
-  }
-
   pub fn get_part_index_impl(&self, pos: Point, current_normal: usize) -> PartIndex {
 
     /*
@@ -445,3 +402,41 @@ impl RediCreator {
     return index;
   }
 }
+
+impl PartCreator for RediCreator {
+  fn faces(&self) -> usize {
+    self.normals.len()
+  }
+
+  fn get_part_index(&self, pos: Point) -> PartIndex {
+    self.get_part_index_impl(pos, self.faces())
+  }
+
+  fn get_height(&self, current_normal: usize) -> f32 {
+    2.0
+  }
+
+  fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
+    let last_groove = self.groove0[self.groove0.len() - 2];
+    let sz = last_groove + 2.19;
+    if pos.x.abs() > sz + 4.0 || pos.y.abs() > sz + 4.0 {
+      return 0;
+    }
+
+    let n = self.normals[current_normal];
+    let n1 = n.any_perp().norm();
+    let n2 = cross(n, n1);
+
+    let pos = n.scale(sz) + n1.scale(pos.x) + n2.scale(pos.y);
+    let result = self.get_part_index_impl(pos, current_normal);
+    (result == 0) as PartIndex
+  }
+
+  fn get_quality(&self) -> usize {
+    120
+  }
+
+  fn get_size(&self) -> f32 {
+    100.0
+  }
+}
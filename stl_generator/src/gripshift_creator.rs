@@ -1,5 +1,6 @@
 use common::common_for_twisty_puzzles::*;
 use common::model::*;
+use common::part_creator::*;
 use common::points3d::*;
 use common::solid::*;
 use num::Float;
@@ -33,42 +34,6 @@ impl GripshiftCreator {
     Self {}
   }
 
-  pub fn faces(&self) -> usize {
-    1
-  }
-
-  pub fn get_part_index(&self, pos: Point) -> PartIndex {
-    let result = self.get_part_index_impl(pos, self.faces());
-    // if result != 20 && result != 21 { return 0; }
-    result
-  }
-
-  pub fn get_height(&self, current_normal: usize) -> f32 {
-    0.6
-  }
-
-  pub fn get_count(&self, current_normal: usize) -> usize {
-    1
-  }
-
-  pub fn get_name(&self, current_normal: usize) -> Option<String> {
-    None
-  }
-
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
-    let pos = Point { x: 15.0, y: pos.x, z: pos.y };
-    let part = self.get_part_index_impl(pos, current_normal);
-    part
-  }
-
-  pub fn get_quality() -> usize {
-    512
-  }
-
-  pub fn get_size() -> f32 {
-    150.0
-  }
-
   pub fn get_part_index_impl(&self, pos: Point, current_normal: usize) -> PartIndex {
     let r = (sqr(pos.y) + sqr(pos.z)).sqrt();
     let x = pos.x;
@@ -351,3 +316,29 @@ impl GripshiftCreator {
     return 0;
   }
 }
+
+impl PartCreator for GripshiftCreator {
+  fn faces(&self) -> usize {
+    1
+  }
+
+  fn get_part_index(&self, pos: Point) -> PartIndex {
+    let result = self.get_part_index_impl(pos, self.faces());
+    // if result != 20 && result != 21 { return 0; }
+    result
+  }
+
+  fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
+    let pos = Point { x: 15.0, y: pos.x, z: pos.y };
+    let part = self.get_part_index_impl(pos, current_normal);
+    part
+  }
+
+  fn get_quality(&self) -> usize {
+    512
+  }
+
+  fn get_size(&self) -> f32 {
+    150.0
+  }
+}
@@ -1,5 +1,6 @@
 use crate::common_for_twisty_puzzles::get_groove;
 use crate::model::*;
+use crate::part_creator::*;
 use crate::points3d::*;
 use crate::solid::*;
 use num::Float;
@@ -78,33 +79,6 @@ impl HoodCubeCreator {
     }
   }
 
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
-    return 0;
-    let n = self.normals[current_normal];
-    let (n1, n2) = self.n_basis[current_normal];
-
-    let pos = n.scale(28.6 / n.sqr_len()) + n1.scale(pos.x) + n2.scale(pos.y);
-    let result = self.get_part_index_impl(pos, current_normal);
-
-    (result > 0) as PartIndex
-  }
-
-  pub fn faces(&self) -> usize {
-    self.normals.len()
-  }
-
-  pub fn get_height(current_normal: usize) -> f32 {
-    0.6
-  }
-
-  pub fn get_quality() -> usize {
-    128
-  }
-
-  pub fn get_size() -> f32 {
-    90.0
-  }
-
   pub fn get_part_index_impl(&self, pos: Point, current_normal: usize) -> PartIndex {
     if pos.x > 0.0 {
       return 0;
@@ -243,8 +217,33 @@ impl HoodCubeCreator {
 
     return index;
   }
+}
+
+impl PartCreator for HoodCubeCreator {
+  fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
+    return 0;
+    let n = self.normals[current_normal];
+    let (n1, n2) = self.n_basis[current_normal];
+
+    let pos = n.scale(28.6 / n.sqr_len()) + n1.scale(pos.x) + n2.scale(pos.y);
+    let result = self.get_part_index_impl(pos, current_normal);
+
+    (result > 0) as PartIndex
+  }
+
+  fn faces(&self) -> usize {
+    self.normals.len()
+  }
+
+  fn get_quality(&self) -> usize {
+    128
+  }
+
+  fn get_size(&self) -> f32 {
+    90.0
+  }
 
-  pub fn get_part_index(&self, pos: Point) -> PartIndex {
+  fn get_part_index(&self, pos: Point) -> PartIndex {
     self.get_part_index_impl(pos, self.faces())
   }
 }
@@ -1,4 +1,5 @@
 use crate::model::*;
+use crate::part_creator::*;
 use crate::points3d::*;
 use crate::solid::*;
 
@@ -54,16 +55,10 @@ impl HouseCreator {
   pub fn new() -> Self {
     Self {}
   }
+}
 
-  pub fn faces(&self) -> usize {
-    0
-  }
-
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
-    0
-  }
-
-  pub fn get_part_index(&self, pos: Point) -> PartIndex {
+impl PartCreator for HouseCreator {
+  fn get_part_index(&self, pos: Point) -> PartIndex {
     let wall = 3.5;
     let win = 8.0;
 
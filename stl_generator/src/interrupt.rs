@@ -0,0 +1,57 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+// set by Ctrl-C while `InterruptGuard` exists
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+#[cfg(windows)]
+mod console {
+  use super::INTERRUPTED;
+  use std::sync::atomic::Ordering;
+  use winapi::shared::minwindef::*;
+  use winapi::um::consoleapi::SetConsoleCtrlHandler;
+  use winapi::um::wincon::CTRL_C_EVENT;
+
+  unsafe extern "system" fn on_ctrl(event: DWORD) -> BOOL {
+    if event != CTRL_C_EVENT {
+      return FALSE;
+    }
+    // the second Ctrl-C ends the process without waiting
+    if INTERRUPTED.swap(true, Ordering::SeqCst) {
+      std::process::exit(130);
+    }
+    TRUE
+  }
+
+  pub fn catch_ctrl_c(catch: bool) {
+    unsafe { SetConsoleCtrlHandler(Some(on_ctrl), if catch { TRUE } else { FALSE }) };
+  }
+}
+
+#[cfg(not(windows))]
+mod console {
+  // Ctrl-C keeps ending the process at once, checkpoints saved before it can still be resumed
+  pub fn catch_ctrl_c(_catch: bool) {}
+}
+
+/// Ctrl-C does not end the process while the guard exists, it is only remembered,
+/// so long operations can save their state and exit themselves.
+/// Only Windows consoles are handled, elsewhere `requested` is always false
+pub struct InterruptGuard;
+
+impl InterruptGuard {
+  pub fn new() -> Self {
+    INTERRUPTED.store(false, Ordering::SeqCst);
+    console::catch_ctrl_c(true);
+    Self
+  }
+
+  pub fn requested(&self) -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+  }
+}
+
+impl Drop for InterruptGuard {
+  fn drop(&mut self) {
+    console::catch_ctrl_c(false);
+  }
+}
@@ -1,4 +1,5 @@
 use common::model::*;
+use common::part_creator::*;
 use common::points3d::*;
 use common::solid::*;
 use num::Float;
@@ -62,36 +63,18 @@ impl JigsawCreator {
     .collect();
     Self { a, n, c, ra }
   }
+}
 
-  pub fn faces(&self) -> usize {
-    0
-  }
-
-  pub fn get_height(&self, current_normal: usize) -> f32 {
-    0.6
-  }
-
-  pub fn get_count(&self, current_normal: usize) -> usize {
-    1
-  }
-
-  pub fn get_name(&self, current_normal: usize) -> Option<String> {
-    None
-  }
-
-  pub fn get_quality() -> usize {
+impl PartCreator for JigsawCreator {
+  fn get_quality(&self) -> usize {
     302
   }
 
-  pub fn get_size() -> f32 {
+  fn get_size(&self) -> f32 {
     100.0
   }
 
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
-    0
-  }
-
-  pub fn get_part_index(&self, pos: Point) -> PartIndex {
+  fn get_part_index(&self, pos: Point) -> PartIndex {
     //  let pos=Point { x: 14.530849, y: 14.530849, z: 14.530849 };
 
     let near_b = pos.x.abs() > 25.3 || pos.y.abs() > 25.3 || pos.z.abs() > 25.3;
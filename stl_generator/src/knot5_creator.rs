@@ -1,4 +1,5 @@
 use common::model::*;
+use common::part_creator::*;
 use common::points2d::complex_mul;
 use common::points3d::*;
 use common::solid::*;
@@ -56,32 +57,22 @@ impl Knot5Creator {
 
     Self { lines }
   }
+}
 
-  pub fn faces(&self) -> usize {
+impl PartCreator for Knot5Creator {
+  fn faces(&self) -> usize {
     20
   }
 
-  pub fn get_height(&self, current_normal: usize) -> f32 {
-    0.6
-  }
-
-  pub fn get_count(&self, current_normal: usize) -> usize {
-    1
-  }
-
-  pub fn get_name(&self, current_normal: usize) -> Option<String> {
-    None
-  }
-
-  pub fn get_quality() -> usize {
+  fn get_quality(&self) -> usize {
     200
   }
 
-  pub fn get_size() -> f32 {
+  fn get_size(&self) -> f32 {
     30.0
   }
 
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
+  fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
     if current_normal == 0 {
       self.get_part_index(Point { x: pos.x, y: pos.y, z: 0.0 })
     } else {
@@ -90,7 +81,7 @@ impl Knot5Creator {
     }
   }
 
-  pub fn get_part_index(&self, mut pos: Point) -> PartIndex {
+  fn get_part_index(&self, mut pos: Point) -> PartIndex {
     let r = (sqr(pos.x) + sqr(pos.y)).sqrt();
     let p = f32::atan2(pos.y, pos.x);
     let loc_r = 1.0 + ((f32::min(p.abs() * 2.0, PI)).cos() + 1.0) * 0.5; // радиус тора
@@ -1,4 +1,5 @@
 use crate::model::*;
+use crate::part_creator::*;
 use crate::points3d::*;
 use crate::solid::*;
 
@@ -47,14 +48,6 @@ impl Lapis4Creator {
     }
   }
 
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
-    0
-  }
-
-  pub fn faces(&self) -> usize {
-    0
-  }
-
   pub fn get_part_index_impl(&self, pos: Point, current_normal: usize) -> PartIndex {
     let r = pos.len();
     let depth = self.ball_radius - r;
@@ -124,8 +117,10 @@ impl Lapis4Creator {
 
     return index;
   }
+}
 
-  pub fn get_part_index(&self, pos: Point) -> PartIndex {
+impl PartCreator for Lapis4Creator {
+  fn get_part_index(&self, pos: Point) -> PartIndex {
     self.get_part_index_impl(pos, self.axis.len())
   }
 }
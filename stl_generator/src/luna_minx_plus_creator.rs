@@ -1,4 +1,5 @@
 use crate::model::*;
+use crate::part_creator::*;
 use crate::points3d::*;
 use crate::solid::*;
 use num::Float;
@@ -88,14 +89,6 @@ impl LunaMinxPlusCreator {
     }
   }
 
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
-    0
-  }
-
-  pub fn faces(&self) -> usize {
-    0
-  }
-
   pub fn get_part_index_impl(&self, pos: Point, current_normal: usize) -> PartIndex {
     // if pos.x > 0.0 { return 0; }
     let r = pos.len();
@@ -178,8 +171,10 @@ impl LunaMinxPlusCreator {
 
     return index;
   }
+}
 
-  pub fn get_part_index(&self, pos: Point) -> PartIndex {
+impl PartCreator for LunaMinxPlusCreator {
+  fn get_part_index(&self, pos: Point) -> PartIndex {
     self.get_part_index_impl(pos, self.axis.len())
   }
 }
@@ -2,131 +2,803 @@
 
 use std::num::NonZeroU32;
 use std::ops::Deref;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Duration;
 
+use clap::{Args, Parser, Subcommand};
+use common::boolean::*;
+use common::cache::*;
+use common::clearance::*;
 use common::common_for_twisty_puzzles::*;
 use common::contour::*;
+use common::decimate::*;
+use common::hollow::*;
+use common::mass::*;
+use common::material::*;
 use common::matrix::*;
+use common::mesh_io::*;
 use common::model::*;
+use common::octree::*;
+use common::part_creator::*;
 use common::points2d;
 use common::points2d::AABB;
 use common::points3d::*;
+use common::preview::*;
+use common::progress::*;
+use common::repair::*;
+use common::report::*;
+use common::sharp::*;
+use common::slice::*;
 use common::solid::*;
-use fxhash::FxHashMap;
+use common::validation::*;
+use fxhash::{FxHashMap, FxHashSet};
 
 use common::solid::PartIndex;
 
+// old creators use paths relative to crate root
+use common::{common_for_twisty_puzzles, model, part_creator, points3d, solid};
+
 mod gl_utils;
 mod gl_window;
+mod interrupt;
 mod resources;
 
 #[macro_use]
 mod errors;
 
+mod arb_creator;
+mod assemble_gear_creator;
+mod banana_creator;
+mod bath_creator;
+mod bean_creator;
+mod beard_of_stars_creator;
+mod beggar_creator;
+mod beginner_jumbler_creator;
+mod braid_creator;
+mod bubbloid_creator;
+mod cannyminx_creator;
+mod cobblestone_hexaminx_creator;
+mod cobblestone_octo_creator;
+mod cobblestone_prism_creator;
+mod cobblestone_skewb_creator;
+mod confusing_creator;
+mod crazy_diamond_cube_creator;
+mod cube_creator;
+mod cube_creator_51;
+mod decaminx_big_creator;
+mod decaminx_creator;
+mod decaminx_o_creator;
+mod disk_creator;
+mod flower_hybrid_creator;
+mod fth_creator;
+mod galla_trip_creator;
+mod ghost_prism_creator;
+mod ghost_pyra_creator;
+mod ghost_redi_creator;
+mod gripshift_creator;
+mod hood_cube_creator;
+mod house_creator;
+mod hyper_creator;
+mod jigsaw_creator;
+mod knot5_creator;
+mod lapis4_creator;
+mod luna_minx_plus_creator;
+mod mid_redi_creator;
+mod nested_cube_creator;
+mod octo_mixup_creator;
+mod octopus_creator;
+mod octopus_forest_creator;
+mod osnik_creator;
+mod pillow_creator;
+mod pyra5_creator;
+mod pyra5_creator_tw;
+mod pyra5_lite_creator;
+mod pyra5_slice_creator;
+mod pyritorhomdo_creator;
+mod railroad_creator;
+mod reactor_skewb_creator;
+mod red_triangle_creator;
+mod redi_creator;
+mod rex_creator;
+mod rhomdo_tbp_creator;
+mod roach_creator;
+mod rounds_creator;
+mod semiosnik_creator;
+mod semiosnik_family_creator;
+mod sixdecaminx_creator;
+mod square_cube_creator;
+mod square_kilo_creator;
+mod sun_kitty_creator;
+mod sun_tower_creator;
+mod swamp_tod_creator;
+mod tower4_creator;
+mod tree_creator;
+mod tree_cube_creator;
+mod u3_minx_creator;
+mod very_eccentric_creator;
+mod washer_creator;
+mod zmey_gorynych_arb_creator;
+mod zmey_gorynych_creator;
+mod zmey_gorynych_curvy_copter_creator;
+mod zmey_gorynych_diogo_copter_creator;
+mod zmey_gorynych_jumble_prism_creator;
+mod zmey_gorynych_kites_creator;
+
+// outdated, need to be restored before use
+//mod pacific_creator;
 //mod sphere_creator;
-//type PartCreator = sphere_creator::SphereCreator;
+//mod swamp_tod2_creator;
 
-mod zmey_gorynych_curvy_copter_creator;
-type PartCreator = zmey_gorynych_curvy_copter_creator::ZmeyGorynychCurvyCopterCreator;
-
-fn generate_models() -> FxHashMap<PartIndex, Model> {
-  let part_creator = PartCreator::new();
-  let mut pf_timer = std::cell::RefCell::new(Duration::ZERO);
-  let part_func = &|p| {
-    let start = std::time::Instant::now();
-    let result = part_creator.get_part_index(p);
-    *pf_timer.borrow_mut() += std::time::Instant::now() - start;
-    result
+const CREATORS: &[RegisteredCreator] = common::creator_registry![
+  "arb" => arb_creator::ArbCreator,
+  "assemble_gear" => assemble_gear_creator::AssembleGearCreator [params],
+  "banana" => banana_creator::BananaCreator,
+  "bath" => bath_creator::BathCreator,
+  "bean" => bean_creator::BeanCreator,
+  "beard_of_stars" => beard_of_stars_creator::BeardOfStarsCreator,
+  "beggar" => beggar_creator::BeggarCreator,
+  "beginner_jumbler" => beginner_jumbler_creator::BeginnerJumblerCreator,
+  "braid" => braid_creator::BraidCreator,
+  "bubbloid" => bubbloid_creator::BubbloidCreator,
+  "cannyminx" => cannyminx_creator::CannyminxCreator,
+  "cobblestone_hexaminx" => cobblestone_hexaminx_creator::HexaminxCreator,
+  "cobblestone_octo" => cobblestone_octo_creator::OctoCreator,
+  "cobblestone_prism" => cobblestone_prism_creator::PrismCreator,
+  "cobblestone_skewb" => cobblestone_skewb_creator::SkewbCreator,
+  "confusing" => confusing_creator::ConfusingCreator,
+  "crazy_diamond_cube" => crazy_diamond_cube_creator::CubeCreator,
+  "cube" => cube_creator::CubeCreator,
+  "cube_51" => cube_creator_51::CubeCreator,
+  "decaminx_big" => decaminx_big_creator::DecaminxCreator,
+  "decaminx" => decaminx_creator::DecaminxCreator,
+  "decaminx_o" => decaminx_o_creator::DecaminxCreator,
+  "disk" => disk_creator::DiskCreator,
+  "flower_hybrid" => flower_hybrid_creator::FlowerHybridCreator,
+  "fth" => fth_creator::HouseCreator,
+  "galla_trip" => galla_trip_creator::GallaTripCreator,
+  "ghost_prism" => ghost_prism_creator::GhostPrismCreator,
+  "ghost_pyra" => ghost_pyra_creator::GhostPyraCreator,
+  "ghost_redi" => ghost_redi_creator::RediCreator,
+  "gripshift" => gripshift_creator::GripshiftCreator,
+  "hood_cube" => hood_cube_creator::HoodCubeCreator,
+  "house" => house_creator::HouseCreator,
+  "hyper" => hyper_creator::HyperCreator,
+  "jigsaw" => jigsaw_creator::JigsawCreator,
+  "knot5" => knot5_creator::Knot5Creator,
+  "lapis4" => lapis4_creator::Lapis4Creator,
+  "luna_minx_plus" => luna_minx_plus_creator::LunaMinxPlusCreator,
+  "mid_redi" => mid_redi_creator::RediCreator,
+  "nested_cube" => nested_cube_creator::CubeCreator,
+  "octo_mixup" => octo_mixup_creator::OctoMixupCreator,
+  "octopus" => octopus_creator::OctopusCreator,
+  "octopus_forest" => octopus_forest_creator::OctopusCreator,
+  "osnik" => osnik_creator::OsnikCreator,
+  "pillow" => pillow_creator::PillowCreator,
+  "pyra5" => pyra5_creator::Pyra5Creator,
+  "pyra5_tw" => pyra5_creator_tw::Pyra5Creator,
+  "pyra5_lite" => pyra5_lite_creator::Pyra5Creator,
+  "pyra5_slice" => pyra5_slice_creator::Pyra5Creator,
+  "pyritorhomdo" => pyritorhomdo_creator::PyritorhomdoCreator,
+  "railroad" => railroad_creator::RailroadCreator [params],
+  "reactor_skewb" => reactor_skewb_creator::ReactorScewbCreator,
+  "red_triangle" => red_triangle_creator::RedTriangleCreator,
+  "redi" => redi_creator::RediCreator,
+  "rex" => rex_creator::RexCreator,
+  "rhomdo_tbp" => rhomdo_tbp_creator::RhomdoTBPCreator,
+  "roach" => roach_creator::RoachCreator,
+  "rounds" => rounds_creator::RoundsCreator,
+  "semiosnik" => semiosnik_creator::SemiosnikCreator,
+  "semiosnik_family" => semiosnik_family_creator::SemiosnikCreator,
+  "sixdecaminx" => sixdecaminx_creator::SixdecaminxCreator,
+  "square_cube" => square_cube_creator::SquareCubeCreator,
+  "square_kilo" => square_kilo_creator::SquareKiloCreator,
+  "sun_kitty" => sun_kitty_creator::SunKittyCreator,
+  "sun_tower" => sun_tower_creator::SunTowerCreator,
+  "swamp_tod" => swamp_tod_creator::SwampTodCreator,
+  "tower4" => tower4_creator::Tower4Creator,
+  "tree" => tree_creator::TreeCreator,
+  "tree_cube" => tree_cube_creator::TreeCreator,
+  "u3_minx" => u3_minx_creator::U3minxCreator,
+  "very_eccentric" => very_eccentric_creator::VeryEccentricCreator,
+  "washer" => washer_creator::WasherCreator,
+  "zmey_gorynych_arb" => zmey_gorynych_arb_creator::ArbCreator,
+  "zmey_gorynych" => zmey_gorynych_creator::ZmeyGorynychCreator,
+  "zmey_gorynych_curvy_copter" => zmey_gorynych_curvy_copter_creator::ZmeyGorynychCurvyCopterCreator,
+  "zmey_gorynych_diogo_copter" => zmey_gorynych_diogo_copter_creator::ZmeyGorynychDiogoCopterCreator,
+  "zmey_gorynych_jumble_prism" => zmey_gorynych_jumble_prism_creator::ZmeyGorynychJumblePrismCreator,
+  "zmey_gorynych_kites" => zmey_gorynych_kites_creator::ZmeyGorynychKitesCreator,
+];
+
+#[derive(Parser)]
+#[command(about = "Generates 3d models and flat parts by creator name")]
+struct Cli {
+  #[command(subcommand)]
+  command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+  /// Print names of all known creators
+  List,
+  /// Generate models of the creator and show them
+  Generate {
+    name: String,
+    /// Override grid resolution of the creator
+    #[arg(long)]
+    quality: Option<usize>,
+    /// Override size of the meshed cube
+    #[arg(long)]
+    size: Option<f32>,
+    /// Json file overriding creator parameters, effective ones are saved to the output dir
+    #[arg(long)]
+    params: Option<PathBuf>,
+    /// Material of all parts instead of the ones assigned by the creator
+    #[arg(long, value_parser = parse_material)]
+    material: Option<Material>,
+    /// Extension of saved models: stl, ascii.stl, obj or ply, obj and ply have part colors
+    #[arg(long, default_value = "stl", value_parser = parse_mesh_format)]
+    mesh_format: MeshFormat,
+    /// Directory where `output`, `contours` and `extruded` folders are placed
+    #[arg(long, default_value = ".")]
+    output_dir: PathBuf,
+    /// Exit after saving instead of opening the window
+    #[arg(long)]
+    no_view: bool,
+    /// Threads evaluating layers, all available cores by default
+    #[arg(long)]
+    threads: Option<usize>,
+    /// Mesh all models instead of reusing unchanged ones from `cache` folder of the output dir
+    #[arg(long)]
+    no_cache: bool,
+    #[command(flatten)]
+    checkpoint: CheckpointArgs,
+    #[command(flatten)]
+    decimate: DecimateArgs,
+    #[command(flatten)]
+    hollow: HollowArgs,
+    #[command(flatten)]
+    adaptive: AdaptiveArgs,
+    #[command(flatten)]
+    domain: DomainArgs,
+    #[command(flatten)]
+    sharp: SharpArgs,
+    #[command(flatten)]
+    clearance: ClearanceArgs,
+    #[command(flatten)]
+    preview: PreviewArgs,
+  },
+  /// Weld vertices, drop bad faces, fix orientation and fill small holes of a mesh file,
+  /// format of the result is chosen by its extension
+  Repair {
+    input: PathBuf,
+    output: PathBuf,
+    /// Vertices closer than this are merged
+    #[arg(long, default_value_t = RepairParams::default().weld_tolerance)]
+    weld_tolerance: f32,
+    /// Boundary loops with more edges are left open
+    #[arg(long, default_value_t = RepairParams::default().max_hole_edges)]
+    max_hole_edges: usize,
+  },
+  /// Union, difference or intersection of two closed mesh files like a part and a screw hole,
+  /// format of the result is chosen by its extension
+  Boolean {
+    #[arg(value_parser = parse_boolean_op)]
+    op: BooleanOp,
+    first: PathBuf,
+    second: PathBuf,
+    output: PathBuf,
+  },
+  /// Save horizontal cross sections of a mesh file to dxf files like `slice_0.dxf`
+  Slice {
+    input: PathBuf,
+    /// Height of a cross section, can be repeated
+    #[arg(long = "z")]
+    heights: Vec<f32>,
+    /// Cut the whole model into layers of this thickness through their middles
+    #[arg(long)]
+    layer: Option<f32>,
+    #[arg(long, default_value = ".")]
+    output_dir: PathBuf,
+  },
+  /// Show models saved during the last generation
+  Load {
+    #[arg(long, default_value = ".")]
+    output_dir: PathBuf,
+    #[command(flatten)]
+    clearance: ClearanceArgs,
+    #[command(flatten)]
+    preview: PreviewArgs,
+  },
+}
+
+#[derive(Args)]
+struct PreviewArgs {
+  /// Save png snapshots of models to `previews` folder of the output dir
+  #[arg(long)]
+  preview: bool,
+  /// Camera angles around vertical and horizontal axes in degrees like `30,20`, can be repeated,
+  /// front, side, top and isometric views are saved by default
+  #[arg(long = "camera", value_parser = parse_camera)]
+  cameras: Vec<Camera>,
+  /// Width and height of previews in pixels
+  #[arg(long, default_value_t = 512)]
+  preview_size: usize,
+}
+
+#[derive(Args)]
+struct CheckpointArgs {
+  /// Seconds between saving meshing state to `checkpoint.bin` of the output dir, 0 disables it,
+  /// the state is also saved when meshing is interrupted by Ctrl-C in a Windows console
+  #[arg(long, default_value_t = 60)]
+  checkpoint_every: u64,
+  /// Continue meshing from the checkpoint of an interrupted run with the same inputs
+  #[arg(long)]
+  resume: bool,
+}
+
+#[derive(Args)]
+struct DecimateArgs {
+  /// Simplify every model by quadric error down to this number of triangles
+  #[arg(long)]
+  max_triangles: Option<usize>,
+  /// Simplify every model while surface moves less than this distance
+  #[arg(long)]
+  max_deviation: Option<f32>,
+}
+
+impl DecimateArgs {
+  fn params(&self) -> DecimateParams {
+    DecimateParams { max_triangles: self.max_triangles, max_deviation: self.max_deviation }
+  }
+}
+
+#[derive(Args)]
+struct HollowArgs {
+  /// Hollow every model leaving walls of this thickness
+  #[arg(long)]
+  hollow_wall: Option<f32>,
+  /// Radius of holes draining every hollowed cavity down through the bottom wall
+  #[arg(long, default_value_t = 0.0)]
+  drain_radius: f32,
+  /// Distance between beams of the lattice supporting hollowed cavities, 0 for no lattice
+  #[arg(long, default_value_t = 0.0)]
+  lattice_step: f32,
+  /// Thickness of lattice beams
+  #[arg(long, default_value_t = HollowParams::default().lattice_width)]
+  lattice_width: f32,
+}
+
+impl HollowArgs {
+  /// `cell_size` is the grid step of the meshed models
+  fn params(&self, cell_size: f32) -> Option<HollowParams> {
+    self.hollow_wall.map(|wall| HollowParams {
+      wall,
+      cell_size,
+      drain_radius: self.drain_radius,
+      lattice_step: self.lattice_step,
+      lattice_width: self.lattice_width,
+    })
+  }
+}
+
+#[derive(Args)]
+struct AdaptiveArgs {
+  /// Mesh on an octree refined only near borders of parts instead of the uniform grid,
+  /// checkpoints are not saved
+  #[arg(long)]
+  adaptive: bool,
+  /// Number of times the first octree grid is split down to the quality grid
+  #[arg(long, default_value_t = 3)]
+  adaptive_levels: usize,
+  /// Borders of parts turning less than this angle in degrees keep bigger cells,
+  /// it works for creators knowing distances to their parts
+  #[arg(long, default_value_t = 0.0)]
+  max_angle: f32,
+}
+
+impl AdaptiveArgs {
+  fn params(&self, quality: usize) -> Option<AdaptiveParams> {
+    let params = AdaptiveParams::for_quality(quality, self.adaptive_levels, self.max_angle);
+    self.adaptive.then_some(params)
+  }
+}
+
+#[derive(Args)]
+struct SharpArgs {
+  /// Keep edges and corners of parts sharp instead of letting the grid and smoothing round them
+  #[arg(long)]
+  sharp: bool,
+  /// Faces meeting at a smaller angle in degrees are smoothed over
+  #[arg(long, default_value_t = SharpParams::default().angle)]
+  sharp_angle: f32,
+}
+
+impl SharpArgs {
+  /// `cell_size` is the grid step of the meshed models
+  fn params(&self, cell_size: f32) -> Option<SharpParams> {
+    self.sharp.then_some(SharpParams { angle: self.sharp_angle, cell_size })
+  }
+}
+
+#[derive(Args)]
+struct DomainArgs {
+  /// Mesh only the box `min_x,min_y,min_z,max_x,max_y,max_z` instead of the box of the creator
+  #[arg(long, value_parser = parse_bounds)]
+  bounds: Option<(Point, Point)>,
+  /// Grid steps along x, y and z like `0.1,0.1,0.05` instead of the step of `quality` points
+  #[arg(long, value_parser = parse_cell_size)]
+  cell_size: Option<Point>,
+  /// Axis x, y or z the grid layers are filled along, the longest side of the box by default
+  #[arg(long, value_parser = parse_axis)]
+  sweep: Option<usize>,
+  /// Mesh only these parts like `1,4,7`, the grid covers only their boxes found on a coarse grid
+  #[arg(long, value_delimiter = ',')]
+  parts: Vec<PartIndex>,
+}
+
+impl DomainArgs {
+  /// Cube of `size` with `quality` points along every side unless the box or the grid are changed
+  fn domain(&self, part_creator: &dyn PartCreator, quality: usize, size: f32) -> MeshDomain {
+    let cube = MeshDomain::cube(quality, size);
+    let bounds = self.bounds.or(part_creator.mesh_aabb());
+    if bounds.is_none() && self.cell_size.is_none() && self.sweep.is_none() {
+      return cube;
+    }
+    let (min, max) = bounds.unwrap_or((cube.min, cube.max));
+    let step = cube.step(0);
+    let cell = self.cell_size.unwrap_or(Point { x: step, y: step, z: step });
+    let domain = MeshDomain::with_cell_size(min, max, cell);
+    match self.sweep {
+      Some(sweep) => domain.with_sweep(sweep),
+      None => domain,
+    }
+  }
+
+  /// `None` means all parts
+  fn selected(&self) -> Option<FxHashSet<PartIndex>> {
+    (!self.parts.is_empty()).then(|| self.parts.iter().copied().collect())
+  }
+}
+
+// part of the grid around `parts` found on every 4th point of it, the whole grid is meshed if
+// some of them are missed
+fn selected_domain(
+  domain: &MeshDomain,
+  parts: &FxHashSet<PartIndex>,
+  part_f: &dyn Fn(Point) -> PartIndex,
+) -> MeshDomain {
+  // a finer search would cost about as much as meshing itself
+  let boxes = domain.part_boxes(4, part_f);
+  let mut missing: Vec<_> = parts.iter().filter(|m| !boxes.contains_key(m)).copied().collect();
+  missing.sort();
+  if !missing.is_empty() {
+    println!("Parts {missing:?} are not found, meshing the whole grid");
+    return domain.clone();
+  }
+  let union = parts.iter().map(|m_index| boxes[m_index]).reduce(|(min1, max1), (min2, max2)| {
+    let min = Point { x: min1.x.min(min2.x), y: min1.y.min(min2.y), z: min1.z.min(min2.z) };
+    let max = Point { x: max1.x.max(max2.x), y: max1.y.max(max2.y), z: max1.z.max(max2.z) };
+    (min, max)
+  });
+  let Some((min, max)) = union else {
+    return domain.clone();
   };
+  let result = domain.sub_domain(min, max);
+  println!("meshing {:?} of {:?} grid points around selected parts", result.points, domain.points);
+  result
+}
+
+#[derive(Args)]
+struct ClearanceArgs {
+  /// Report parts intersecting or closer to each other than this gap
+  /// and save all gaps to `clearance.json` of the output dir
+  #[arg(long)]
+  min_gap: Option<f32>,
+  /// Parts farther from each other are not measured
+  #[arg(long, default_value_t = ClearanceParams::default().max_distance)]
+  max_gap: f32,
+}
+
+impl ClearanceArgs {
+  fn check(&self, models: &FxHashMap<PartIndex, Model>, output_dir: &Path) {
+    let Some(min_gap) = self.min_gap else {
+      return;
+    };
+    let params = ClearanceParams { min_gap, max_distance: self.max_gap.max(min_gap) };
+    let report = check_clearance(models, &params, &ConsoleProgress::new());
+    println!();
+    for p in report.intersections().chain(report.too_tight()) {
+      let l = p.location();
+      let problem =
+        if p.intersecting { "intersect".to_string() } else { format!("have gap {}", p.distance) };
+      println!("parts {} and {} {problem} at {} {} {}", p.parts[0], p.parts[1], l.x, l.y, l.z);
+    }
+    println!("{}", report.summary());
+    if let Err(msg) = report.save_to_json(&output_dir.join("clearance.json")) {
+      println!("{}", msg);
+    }
+  }
+}
+
+impl PreviewArgs {
+  fn save(&self, models: &FxHashMap<PartIndex, Model>, output_dir: &Path) {
+    if !self.preview {
+      return;
+    }
+    let cameras = if self.cameras.is_empty() { &Camera::DEFAULT_VIEWS[..] } else { &self.cameras };
+    let models = models.iter().map(|(m_index, m)| (*m_index, m));
+    let size = self.preview_size;
+    if let Err(msg) = save_previews(models, cameras, size, size, &output_dir.join("previews")) {
+      println!("{}", msg);
+    }
+  }
+}
+
+fn parse_material(s: &str) -> Result<Material, String> {
+  find_material(s).ok_or_else(|| {
+    let names: Vec<_> = MATERIALS.iter().map(|m| m.name).collect();
+    format!("unknown material, available are {}", names.join(", "))
+  })
+}
+
+fn parse_mesh_format(s: &str) -> Result<MeshFormat, String> {
+  MeshFormat::from_extension(s).ok_or_else(|| {
+    let names: Vec<_> = MeshFormat::ALL.iter().map(|f| f.extension()).collect();
+    format!("unknown format, available are {}", names.join(", "))
+  })
+}
+
+fn parse_boolean_op(s: &str) -> Result<BooleanOp, String> {
+  BooleanOp::from_name(s).ok_or_else(|| {
+    let names: Vec<_> = BooleanOp::ALL.iter().map(|op| op.name()).collect();
+    format!("unknown operation, available are {}", names.join(", "))
+  })
+}
+
+fn parse_camera(s: &str) -> Result<Camera, String> {
+  let (angle_y, angle_x) = s.split_once(',').ok_or("expected two angles like `30,20`")?;
+  let parse =
+    |a: &str| a.trim().parse::<f32>().map(f32::to_radians).map_err(|e| format!("{a}: {e}"));
+  Ok(Camera { angle_y: parse(angle_y)?, angle_x: parse(angle_x)? })
+}
+
+fn parse_numbers<const N: usize>(s: &str) -> Result<[f32; N], String> {
+  let numbers = s
+    .split(',')
+    .map(|a| a.trim().parse::<f32>().map_err(|e| format!("{a}: {e}")))
+    .collect::<Result<Vec<_>, _>>()?;
+  numbers.try_into().map_err(|_| format!("expected {N} numbers separated by commas"))
+}
+
+fn parse_bounds(s: &str) -> Result<(Point, Point), String> {
+  let [x1, y1, z1, x2, y2, z2] = parse_numbers(s)?;
+  if x1 >= x2 || y1 >= y2 || z1 >= z2 {
+    return Err("minimal coordinates should be less than maximal ones".to_string());
+  }
+  Ok((Point { x: x1, y: y1, z: z1 }, Point { x: x2, y: y2, z: z2 }))
+}
+
+fn parse_cell_size(s: &str) -> Result<Point, String> {
+  let [x, y, z] = parse_numbers(s)?;
+  if x <= 0.0 || y <= 0.0 || z <= 0.0 {
+    return Err("steps should be positive".to_string());
+  }
+  Ok(Point { x, y, z })
+}
+
+fn parse_axis(s: &str) -> Result<usize, String> {
+  ["x", "y", "z"].iter().position(|&a| a == s).ok_or("expected x, y or z".to_string())
+}
+
+fn create_dir(path: PathBuf) -> PathBuf {
+  if let Err(e) = std::fs::create_dir_all(&path) {
+    println!("Unable to create directory {}: {}", path.to_string_lossy(), e);
+  }
+  path
+}
+
+fn model_name(part_creator: &dyn PartCreator, m_index: PartIndex) -> String {
+  part_creator.get_model_name(m_index).unwrap_or(format!("part_{m_index}"))
+}
 
+/// Calls `f` adding its duration in nanoseconds to `timer`
+fn measure<T>(timer: &AtomicU64, f: impl FnOnce() -> T) -> T {
   let start = std::time::Instant::now();
-  let mut cc = ContourCreator::new(points2d::AABB::around_zero(100.0), 0.15, 20);
+  let result = f();
+  timer.fetch_add(start.elapsed().as_nanos() as u64, Ordering::Relaxed);
+  result
+}
+
+/// Options of `generate` which are not a part of `CacheInputs`
+struct GenerateOptions<'a> {
+  material: Option<Material>,
+  mesh_format: MeshFormat,
+  output_dir: &'a Path,
+  checkpoint: &'a CheckpointArgs,
+  decimate: &'a DecimateParams,
+  selected: Option<&'a FxHashSet<PartIndex>>,
+}
+
+fn generate_models(
+  part_creator: &dyn PartCreator,
+  pool: Option<&CreatorPool>,
+  inputs: &CacheInputs,
+  mut cache: Option<PartCache>,
+  options: GenerateOptions,
+) -> FxHashMap<PartIndex, Model> {
+  let GenerateOptions { material, mesh_format, output_dir, checkpoint, decimate, selected } =
+    options;
+  let (name, quality, size) = (inputs.creator.as_str(), inputs.quality, inputs.size);
+  let (hollow, adaptive, sharp) =
+    (inputs.hollow.as_ref(), inputs.adaptive.as_ref(), inputs.sharp.as_ref());
+  // nanoseconds summed over all threads
+  let pf_timer = AtomicU64::new(0);
+  let part_func = &|p| measure(&pf_timer, || part_creator.get_part_index(p));
+  let pool_func = &|p| measure(&pf_timer, || pool.unwrap().get_part_index(p));
+  let distance_func = &|p, i| measure(&pf_timer, || part_creator.get_part_distance(p, i));
+  let pool_distance_func = &|p, i| measure(&pf_timer, || pool.unwrap().get_part_distance(p, i));
+  let progress = ConsoleProgress::new();
+
+  let start = std::time::Instant::now();
+  let contours_dir = create_dir(output_dir.join("contours"));
+  let extruded_dir = create_dir(output_dir.join("extruded"));
+  let models_dir = create_dir(output_dir.join("output"));
 
   let mut total_length = 0.0;
   let mut total_square = 0.0;
+  let mut report = BuildReport::new(name);
 
   for i in 0..part_creator.faces() {
-    let (mut contours, _) = cc.make_contour(&|p| part_creator.get_sticker_index(p, i));
+    let aabb = part_creator.aabb(i).unwrap_or(AABB::around_zero(100.0));
+    let cc = ContourCreator::new(aabb, 0.15, 20);
+    let topologys = cc.make_topology(&|p| part_creator.get_sticker_index(p, i), &progress);
     let h = part_creator.get_height(i);
-    let name = part_creator.get_name(i).map(|s| s.to_string()).unwrap_or(format!("part_{i}"));
+    let part_name = part_creator.get_name(i).unwrap_or(format!("part_{i}"));
 
     let thickness = h;
     let count = part_creator.get_count(i);
-    let name = format!("(THICK={thickness}, AMOUNT={count}) {name}");
+    let name = format!("(THICK={thickness}, AMOUNT={count}) {part_name}");
 
-    let mut single_i = contours.len() == 1;
-
-    if !single_i {
-      // if need single file
-      let mut common = ContourSet::new();
-      for (_, cc) in std::mem::take(&mut contours) {
-        common.append(cc)
-      }
-      contours.insert(1, common);
-      single_i = true;
-    }
-
-    for (index, mut cc) in contours {
-      cc.optimize(0.01);
-      cc.remove_trash();
+    let single_i = topologys.len() == 1;
+    for (index, mut topology) in topologys {
+      topology.optimize(0.01);
+      topology.remove_trash();
 
+      let figure = topology.to_flat_figure();
       let name = if single_i { name.clone() } else { format!("{name}_{index}") };
+      let part_name = if single_i { part_name.clone() } else { format!("{part_name}_{index}") };
 
-      let square = cc.get_square();
-      let length = cc.get_length();
+      let square = figure.get_square();
+      let length = figure.get_length();
 
       total_length += length * count as f32;
       total_square += square * count as f32;
 
       println!(
         "save {name} ({} points, {square} square, {length} length) to dxf...",
-        cc.points_count()
+        figure.points_count()
       );
-      if let Err(msg) =
-        cc.save_to_dxf(&std::path::Path::new("contours").join(format!("{name}.dxf")))
-      {
+      if let Err(msg) = figure.save_to_dxf(&contours_dir.join(format!("{name}.dxf"))) {
         println!("{}", msg);
       }
 
-      let ex = cc.extrude(h);
-
-      let single_j = ex.len() == 1;
-      for j in 0..ex.len() {
-        let name = if single_j { name.clone() } else { format!("{name}_{j}") };
-
-        if let Err(msg) =
-          ex[j].save_to_stl(&std::path::Path::new("extruded").join(format!("{name}.stl")))
-        {
-          println!("{}", msg);
-        }
-      }
-
-      let mut appended = Model::new();
-      for ex in ex {
-        appended.append(ex);
-      }
-
-      let name = format!("{name}_appended");
-      if let Err(msg) =
-        appended.save_to_stl(&std::path::Path::new("extruded").join(format!("{name}.stl")))
-      {
+      let ex = figure.extrude(h);
+      if let Err(msg) = ex.save_to_stl(&extruded_dir.join(format!("{name}.stl"))) {
         println!("{}", msg);
       }
+
+      let file = format!("contours/{name}.dxf");
+      let material = material.or(part_creator.get_face_material(i)).unwrap_or(DEFAULT_MATERIAL);
+      let face = FlatFace { index: i as u32, name: part_name, file, count, thickness: h, material };
+      report.add(PartReport::from_flat_figure(face, &figure, &ex));
     }
   }
 
   println!("total {total_length} length, {total_square} square");
 
-  let quality = PartCreator::get_quality();
-
-  let mut mc = ModelCreator::new(quality, PartCreator::get_size(), 20, 0, part_func);
   let width = 0.05;
-  println!();
-  while !mc.finished() {
-    mc.fill_next_layer(part_func);
+  let cache_key = |m_index| inputs.key(part_creator.get_cache_key(m_index).as_deref());
+  let is_selected = |m_index: PartIndex| selected.is_none_or(|s| s.contains(&m_index));
+  let mut models = FxHashMap::default();
+  let mut all_cached = false;
+  if let Some(cache) = &cache {
+    let parts: Vec<_> = cache.parts().filter(|&m_index| is_selected(m_index)).collect();
+    for &m_index in &parts {
+      if let Some(m) = cache.load(m_index, &cache_key(m_index)) {
+        models.insert(m_index, m);
+      }
+    }
+    all_cached = match selected {
+      Some(selected) => selected.iter().all(|m_index| models.contains_key(m_index)),
+      None => !cache.is_partial() && !parts.is_empty() && models.len() == parts.len(),
+    };
+    println!("{} of {} models are taken from cache", models.len(), parts.len());
+  }
+  let cached: FxHashSet<PartIndex> = models.keys().copied().collect();
+  // parts kept from a run of all of them are still all parts
+  let complete_cache =
+    cache.as_ref().is_some_and(|c| !c.is_partial() && c.parts().next().is_some());
+
+  if let Some(adaptive) = adaptive.filter(|_| !all_cached) {
+    let mut oc = OctreeCreator::new(adaptive.clone(), size, 20);
+    oc.skip_models(cached.iter().copied());
+    if let Some(selected) = selected {
+      oc.select_models(selected.iter().copied());
+    }
+    println!();
+    let meshed = match pool {
+      Some(pool) => oc.mesh_parallel(pool_func, pool_distance_func, pool.len(), &progress),
+      None => oc.mesh(part_func, distance_func, &progress),
+    };
+    println!();
+    println!("got {} points, cells of depths {:?}", oc.got_samples(), oc.leaves_per_depth());
+    models.extend(meshed);
+  } else if !all_cached {
+    let checkpoint_path = output_dir.join("checkpoint.bin");
+    let run_key = inputs.key(None);
+    let resumed = match checkpoint.resume {
+      true => match ModelCreator::resume(&checkpoint_path, &run_key) {
+        Ok(mc) if mc.selected_models() != selected => {
+          println!("Checkpoint meshes other parts, meshing from the start");
+          None
+        }
+        Ok(mc) if mc.skipped_models().is_subset(&cached) => Some(mc),
+        Ok(_) => {
+          println!("Checkpoint skips models which are not in cache, meshing from the start");
+          None
+        }
+        Err(msg) => {
+          println!("{}", msg);
+          None
+        }
+      },
+      false => None,
+    };
+    let mut mc = resumed.unwrap_or_else(|| {
+      let Some(selected) = selected else {
+        return ModelCreator::with_domain(inputs.domain.clone(), 20, 0, part_func);
+      };
+      let meshed = selected.difference(&cached).copied().collect();
+      let domain = selected_domain(&inputs.domain, &meshed, part_func);
+      let mut mc = ModelCreator::with_domain(domain, 20, 0, part_func);
+      mc.select_models(selected.iter().copied());
+      mc
+    });
+    mc.skip_models(cached.iter().copied());
+    let mut last_checkpoint = std::time::Instant::now();
+    let interrupts = interrupt::InterruptGuard::new();
+    println!();
+    while !mc.finished() {
+      match pool {
+        Some(pool) => {
+          mc.fill_next_layer_parallel_exact(pool_func, pool_distance_func, pool.len(), &progress)
+        }
+        None => mc.fill_next_layer_exact(part_func, distance_func, &progress),
+      }
+      let every = checkpoint.checkpoint_every;
+      if every > 0 && last_checkpoint.elapsed() >= Duration::from_secs(every) {
+        if let Err(msg) = mc.save_checkpoint(&checkpoint_path, &run_key) {
+          progress.log(&msg);
+        }
+        last_checkpoint = std::time::Instant::now();
+      }
+      if interrupts.requested() {
+        match mc.save_checkpoint(&checkpoint_path, &run_key) {
+          Ok(()) => progress.log("Interrupted, meshing is continued from here with --resume"),
+          Err(msg) => progress.log(&msg),
+        }
+        std::process::exit(130);
+      }
+    }
+    drop(interrupts);
+    // finished meshing is never resumed
+    std::fs::remove_file(&checkpoint_path).ok();
+    println!();
+    println!("got {} points {} edges", mc.got_points(), mc.got_edges());
+    // partially meshed before resuming
+    models.extend(mc.get_models().into_iter().filter(|(m_index, _)| !cached.contains(m_index)));
   }
-  println!();
-  println!("got {} points {} edges", mc.got_points(), mc.got_edges());
 
   let end_layers = std::time::Instant::now();
 
@@ -134,9 +806,10 @@ fn generate_models() -> FxHashMap<PartIndex, Model> {
   let mut sum_v = 0;
   let mut max_v_after = 0;
   let mut sum_v_after = 0;
-  let mut models = mc.get_models();
   let mut sum_volumes = 0.0;
+  let mut sum_masses = 0.0;
   let mut weights = Vec::new();
+  let mut mass_properties = Vec::new();
   let mut groups_of_models = FxHashMap::<u32, Model>::default();
   let mut sum_t_before = 0;
   let mut sum_t_after = 0;
@@ -144,56 +817,141 @@ fn generate_models() -> FxHashMap<PartIndex, Model> {
   //models.clear();
   // models.insert(1, Model::cuboid(10, 10, 10, 10.0));
 
+  // vertices where parts touch each other stay in place while sharpening or decimating moves
+  // them, so their faces keep matching
+  let keep_shared = sharp.is_some() || decimate.is_limited();
+  let shared = if keep_shared { shared_points(models.values()) } else { Default::default() };
   for (&m_index, m) in &mut models {
     sum_v += m.vertices.len();
     max_v = std::cmp::max(max_v, m.vertices.len());
-    m.validate_and_delete_small_groups();
-    let smooth_cnt = quality / 5;
-    if smooth_cnt > 0 {
-      println!();
-      for i in 0..smooth_cnt {
-        m.smooth(0.1);
-        print!("\rmake model {m_index} smooth, progress [{i}/{smooth_cnt}]");
+    // cached models are saved after all changes
+    let mut manifold = true;
+    // cached models were hollowed before they were saved, the report is kept with them
+    let mut hollowed = match &cache {
+      Some(cache) if cached.contains(&m_index) => cache.hollow_report(m_index).cloned(),
+      _ => None,
+    };
+    if !cached.contains(&m_index) {
+      // broken parts are repaired or saved as is instead of crashing topology based steps
+      let validation = m.validate();
+      if !validation.is_manifold() {
+        progress.log(&format!("model {m_index} is not closed: {}", validation.summary()));
+        let repair = m.repair(&RepairParams::default());
+        progress.log(&format!("model {m_index} repaired: {}", repair.summary()));
+        manifold = m.validate().is_manifold();
+      }
+      if manifold {
+        m.validate_and_delete_small_groups();
+      }
+      let smooth_cnt = quality / 5;
+      if let Some(sharp) = sharp.filter(|_| manifold) {
+        println!();
+        let (mut features, report) = m.sharpen(&|p| part_func(p) == m_index, sharp, &progress);
+        progress.log(&format!("model {m_index} sharpened: {}", report.summary()));
+        for (f, s) in features.iter_mut().zip(m.vertices_at(&shared)) {
+          *f |= s;
+        }
+        let released = m.smooth_features(0.1, smooth_cnt, &mut features, &progress);
+        if released > 0 {
+          progress.log(&format!("model {m_index} smoothed over {released} fixed vertices"));
+        }
+      } else if keep_shared && smooth_cnt > 0 {
+        println!();
+        let mut fixed = m.vertices_at(&shared);
+        let released = m.smooth_features(0.1, smooth_cnt, &mut fixed, &progress);
+        if released > 0 {
+          progress.log(&format!("model {m_index} smoothed over {released} shared vertices"));
+        }
+      } else if smooth_cnt > 0 {
+        println!();
+        for i in 0..smooth_cnt {
+          m.smooth(0.1);
+          print!("\rmake model {m_index} smooth, progress [{i}/{smooth_cnt}]");
+        }
       }
     }
+    if let Some(hollow) = hollow.filter(|_| manifold && !cached.contains(&m_index)) {
+      println!();
+      let report = m.hollow(hollow, &progress);
+      progress.log(&format!("model {m_index} hollowed: {}", report.summary()));
+      hollowed = Some(report);
+    }
     sum_t_before += m.triangles.len();
-    if quality >= 200 {
+    if quality >= 200 && manifold && !cached.contains(&m_index) {
       println!("tcount before = {}", m.triangles.len());
 
       //for m in m.clone().split_by_normal(0.9, 1.0) {
       //   groups_of_models.insert(groups_of_models.len() as u32, m);
       //  }
 
-      m.optimize(0.03, 1.0);
+      m.optimize(0.03, 1.0, &progress);
       println!("tcount after {}", m.triangles.len());
     }
+    if manifold && !cached.contains(&m_index) {
+      m.decimate_keeping(decimate, &m.vertices_at(&shared), &progress);
+    }
     sum_t_after += m.triangles.len();
     m.delete_unused_v();
+    if manifold && !cached.contains(&m_index) {
+      let validation = m.validate();
+      if !validation.is_valid() {
+        progress.log(&format!("model {m_index} is not valid: {}", validation.summary()));
+      }
+    }
+
+    if let Some(cache) = &mut cache {
+      if !cached.contains(&m_index) {
+        if let Err(msg) = cache.store(m_index, cache_key(m_index), m, hollowed.clone()) {
+          println!("{}", msg);
+        }
+      }
+    }
 
     let volume = m.get_volume();
+    let material =
+      material.or(part_creator.get_model_material(m_index)).unwrap_or(DEFAULT_MATERIAL);
+    let mass = material.mass(volume);
     sum_volumes += volume;
+    sum_masses += mass;
 
     sum_v_after += m.vertices.len();
     max_v_after = std::cmp::max(max_v_after, m.vertices.len());
 
-    weights.push((m_index, volume * 7.850 * 0.001));
+    weights.push((m_index, mass, material.cost(mass, None)));
+    mass_properties.push(m.mass_properties(material.density));
+    let file_name = format!("output_{m_index}.{}", mesh_format.extension());
+    let file = format!("output/{file_name}");
+    let part_name = model_name(part_creator, m_index);
+    let count = part_creator.get_model_count(m_index);
+    let part = PartReport::from_model(m_index, part_name, file, &material, m);
+    let saved_volume = hollowed.as_ref().map(HollowReport::saved_volume);
+    let saved_mass = saved_volume.map(|v| material.mass(v));
+    report.add(PartReport { count, saved_volume, saved_mass, ..part });
 
     if quality > 3 {
       println!(
-        "save {m_index} to stl... {} vertices {} triangles {} volume {} mass",
+        "save {m_index} to {file_name}... {} vertices {} triangles {} volume {} mass of {}",
         m.vertices.len(),
         m.triangles.len(),
         volume,
-        volume * 7.850 * 0.001
+        mass,
+        material.name
       );
-      if let Err(msg) =
-        m.save_to_stl(&std::path::Path::new("output").join(format!("output_{}.stl", m_index)))
-      {
+      if let Err(msg) = m.save_to_file(&models_dir.join(&file_name), Some(part_color(m_index))) {
         println!("{}", msg);
       }
     }
   }
 
+  if let Some(cache) = &mut cache {
+    // parts which are not selected are kept for the next runs
+    cache.retain(|m_index| models.contains_key(&m_index) || !is_selected(m_index));
+    cache.set_partial(selected.is_some() && !complete_cache);
+    if let Err(msg) = cache.save() {
+      println!("{}", msg);
+    }
+  }
+
   println!("Has {} groups", groups_of_models.len());
   //models = groups_of_models;
 
@@ -206,7 +964,7 @@ fn generate_models() -> FxHashMap<PartIndex, Model> {
     sum_v_after,
     max_v_after,
     sum_volumes,
-    sum_volumes * 7.850 * 0.001
+    sum_masses
   );
 
   println!(
@@ -218,25 +976,55 @@ fn generate_models() -> FxHashMap<PartIndex, Model> {
     "layers time: {:?}, opt time: {:?}, pf time: {:?}",
     end_layers - start,
     end_opt - end_layers,
-    *pf_timer.borrow()
+    Duration::from_nanos(pf_timer.load(Ordering::Relaxed))
   );
 
-  weights.sort_by(|(_, w1), (_, w2)| w1.partial_cmp(w2).unwrap());
-  for (i, w) in weights {
-    println!("{i}\t{w}");
+  weights.sort_by(|(_, w1, _), (_, w2, _)| w1.partial_cmp(w2).unwrap());
+  for (i, w, cost) in weights {
+    match cost {
+      Some(cost) => println!("{i}\t{w}\t{cost}"),
+      None => println!("{i}\t{w}"),
+    }
+  }
+  println!("total cost {}", report.totals.cost);
+  if !mass_properties.is_empty() {
+    let assembly = MassProperties::combine(&mass_properties);
+    println!("assembly {}", assembly.summary());
+    report.assembly = Some(assembly);
+  }
+  if hollow.is_some() {
+    println!(
+      "hollowing saved {} volume, {} mass",
+      report.totals.saved_volume, report.totals.saved_mass
+    );
+  }
+
+  if let Err(msg) = report.save(output_dir) {
+    println!("{}", msg);
+  }
+
+  let object_info = |m_index| ObjectInfo {
+    name: model_name(part_creator, m_index),
+    color: part_color(m_index),
+    count: part_creator.get_model_count(m_index),
+  };
+  if let Err(msg) = save_models_to_3mf(&models, object_info, &output_dir.join("models.3mf")) {
+    println!("{}", msg);
   }
   models
 }
 
-fn load_last_models(period: std::time::Duration) -> FxHashMap<PartIndex, Model> {
-  let path = std::path::Path::new("output");
+fn load_last_models(output_dir: &Path, period: std::time::Duration) -> FxHashMap<PartIndex, Model> {
+  let path = output_dir.join("output");
   let entries: Vec<_> = std::fs::read_dir(&path)
     .unwrap()
     .map(Result::unwrap)
     .filter_map(|e| {
       let name = e.file_name();
       let name = name.to_str().unwrap();
-      let number: PartIndex = name.strip_prefix("output_")?.strip_suffix(".stl")?.parse().ok()?;
+      MeshFormat::from_path(Path::new(name))?;
+      let (number, _) = name.strip_prefix("output_")?.split_once('.')?;
+      let number: PartIndex = number.parse().ok()?;
       Some((e, number))
     })
     .collect();
@@ -258,7 +1046,7 @@ fn load_last_models(period: std::time::Duration) -> FxHashMap<PartIndex, Model>
   for (e, number) in &entries {
     let mod_time = e.metadata().unwrap().modified().unwrap();
     if mod_time + period > last_time {
-      let model = Model::load_from_stl(&path.join(&e.file_name())).unwrap();
+      let model = Model::load_from_file(&path.join(&e.file_name())).unwrap();
       result.insert(*number, model);
     }
   }
@@ -267,29 +1055,165 @@ fn load_last_models(period: std::time::Duration) -> FxHashMap<PartIndex, Model>
 }
 
 fn main() {
-  let mut models;
-  if std::env::args().any(|s| s == "--load") {
-    models = load_last_models(std::time::Duration::from_mins(5));
-  } else {
-    models = generate_models();
-  }
+  let mut models = match Cli::parse().command {
+    Command::List => {
+      for c in CREATORS {
+        println!("{}", c.name);
+      }
+      return;
+    }
+    Command::Generate {
+      name,
+      quality,
+      size,
+      params,
+      material,
+      mesh_format,
+      output_dir,
+      no_view,
+      threads,
+      no_cache,
+      checkpoint,
+      decimate,
+      hollow,
+      adaptive,
+      domain,
+      sharp,
+      clearance,
+      preview,
+    } => {
+      let Some(registered) = find_creator(CREATORS, &name) else {
+        println!("Unknown creator {name}, see `list` for available ones");
+        std::process::exit(1);
+      };
+      if params.is_some() && !registered.has_params {
+        let names = creators_with_params(CREATORS).join(", ");
+        println!("Creator {name} has no parameters, `--params` works only for {names}");
+        std::process::exit(1);
+      }
+      let instance = match registered.instantiate(params.as_deref(), &output_dir) {
+        Ok(instance) => instance,
+        Err(msg) => {
+          println!("{}", msg);
+          std::process::exit(1);
+        }
+      };
+      let threads = threads
+        .unwrap_or_else(|| std::thread::available_parallelism().map_or(1, |n| n.get()))
+        .max(1);
+      let pool = match threads {
+        1 => None,
+        _ => match registered.instantiate_pool(params.as_deref(), threads) {
+          Ok(pool) => Some(pool),
+          Err(msg) => {
+            println!("{}", msg);
+            std::process::exit(1);
+          }
+        },
+      };
+      let part_creator = instance.creator;
+      let quality = quality.unwrap_or(part_creator.get_quality());
+      let size = size.unwrap_or(part_creator.get_size());
+      let selected = domain.selected();
+      let domain = domain.domain(part_creator.as_ref(), quality, size);
+      // the smallest step
+      let cell_size = (0..3).map(|axis| domain.step(axis)).fold(f32::MAX, f32::min);
+      let inputs = CacheInputs {
+        creator: name.clone(),
+        params: instance.params,
+        quality,
+        size,
+        version: concat!(env!("CARGO_PKG_VERSION"), "-", env!("SOURCE_HASH")).to_string(),
+        max_triangles: decimate.max_triangles,
+        max_deviation: decimate.max_deviation,
+        hollow: hollow.params(cell_size),
+        adaptive: adaptive.params(quality),
+        domain,
+        sharp: sharp.params(cell_size),
+      };
+      let cache = (!no_cache).then(|| PartCache::open(&output_dir.join("cache"), &name));
+      let options = GenerateOptions {
+        material,
+        mesh_format,
+        output_dir: &output_dir,
+        checkpoint: &checkpoint,
+        decimate: &decimate.params(),
+        selected: selected.as_ref(),
+      };
+      let models = generate_models(part_creator.as_ref(), pool.as_ref(), &inputs, cache, options);
+      clearance.check(&models, &output_dir);
+      preview.save(&models, &output_dir);
+      if no_view {
+        return;
+      }
+      models
+    }
+    Command::Repair { input, output, weld_tolerance, max_hole_edges } => {
+      let params = RepairParams { weld_tolerance, max_hole_edges };
+      let result = Model::load_from_file(&input).and_then(|mut m| {
+        let report = m.repair(&params);
+        println!("{}", report.summary());
+        println!("{}", m.validate().summary());
+        m.save_to_file(&output, None)
+      });
+      if let Err(msg) = result {
+        println!("{}", msg);
+      }
+      return;
+    }
+    Command::Boolean { op, first, second, output } => {
+      let result = Model::load_from_file(&first).and_then(|first| {
+        let result = first.boolean(&Model::load_from_file(&second)?, op);
+        println!("{}", result.validate().summary());
+        result.save_to_file(&output, None)
+      });
+      if let Err(msg) = result {
+        println!("{}", msg);
+      }
+      return;
+    }
+    Command::Slice { input, mut heights, layer, output_dir } => {
+      let model = match Model::load_from_file(&input) {
+        Ok(model) => model,
+        Err(msg) => {
+          println!("{}", msg);
+          std::process::exit(1);
+        }
+      };
+      if let Some(layer) = layer {
+        let (min, max) = model.get_aabb();
+        let layers = ((max.z - min.z) / layer).ceil() as usize;
+        heights.extend((0..layers).map(|i| min.z + (i as f32 + 0.5) * layer));
+      }
+      let output_dir = create_dir(output_dir);
+      for (i, figure) in model.slice_at_heights(&heights).iter().enumerate() {
+        let path = output_dir.join(format!("slice_{i}.dxf"));
+        println!(
+          "slice {i} at z={} ({} contours, {} square) to {}",
+          heights[i],
+          figure.contours().len(),
+          figure.get_square(),
+          path.to_string_lossy()
+        );
+        if let Err(msg) = figure.save_to_dxf(&path) {
+          println!("{}", msg);
+        }
+      }
+      return;
+    }
+    Command::Load { output_dir, clearance, preview } => {
+      let models = load_last_models(&output_dir, Duration::from_mins(5));
+      clearance.check(&models, &output_dir);
+      preview.save(&models, &output_dir);
+      models
+    }
+  };
 
   if let Err(_) = crate::gl_window::run(
     "test window",
     &mut models.iter().map(|(m_index, m)| {
       // println!("model {m_index} has {} vertices", m.vertices.len());
-      let color = match m_index / 100000 {
-        1 => 0x00FF00,
-        2 => 0xFF2000,
-        3 => 0xFFFF00,
-        4 => 0x0080FF,
-        5 => 0xFF8000,
-        6 => 0xEEEEEE,
-        7 => 0xFF00FF,
-        8 => 0xFF80FF,
-        _ => m_index.wrapping_mul(0x2743811) as u32 | 0x808080,
-      };
-      (color, m)
+      (part_color(*m_index), m)
     }),
   ) {
     // Do nothing, read message and exit
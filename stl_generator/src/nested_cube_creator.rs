@@ -1,4 +1,5 @@
 use crate::model::*;
+use crate::part_creator::*;
 use crate::points3d::*;
 use crate::solid::*;
 
@@ -53,37 +54,6 @@ impl CubeCreator {
     Self { axis, normals, n_basis, screw_diam, head_diam, thread_diam, axis_dst }
   }
 
-  pub fn faces(&self) -> usize {
-    self.normals.len()
-  }
-
-  pub fn get_part_index(&self, pos: Point) -> PartIndex {
-    self.get_part_index_impl(pos, self.normals.len())
-  }
-
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
-    let n = self.normals[current_normal];
-    let (n1, n2) = self.n_basis[current_normal];
-    let pos = if pos.x > 30.0 {
-      let pos = crate::points2d::Point { x: pos.x - 45.0, y: pos.y };
-      if pos.len() > 10.8 {
-        return 0;
-      }
-      (n.scale(25.1 / n.sqr_len()) + n1.scale(pos.x) + n2.scale(pos.y))
-        .norm()
-        .scale(25.1)
-    } else if pos.x < -30.0 {
-      let pos = crate::points2d::Point { x: pos.x + 35.0, y: pos.y };
-      let y_size = if current_normal < 3 { 16.0 } else { 4.0 };
-      return ((pos.x.abs() - 1.0).abs() < 0.35 && pos.y.abs() < y_size * 0.5) as PartIndex;
-    } else {
-      n.scale(35.0 / n.sqr_len()) + n1.scale(pos.x) + n2.scale(pos.y)
-    };
-
-    let result = self.get_part_index_impl(pos, current_normal);
-    (result > 0) as PartIndex
-  }
-
   pub fn get_part_index_impl(&self, pos: Point, current_normal: usize) -> PartIndex {
     let r = pos.len();
     if pos.x.abs() > 64.999 || pos.y.abs() > 64.999 || pos.z.abs() > 64.999 {
@@ -282,3 +252,36 @@ impl CubeCreator {
     return index;
   }
 }
+
+impl PartCreator for CubeCreator {
+  fn faces(&self) -> usize {
+    self.normals.len()
+  }
+
+  fn get_part_index(&self, pos: Point) -> PartIndex {
+    self.get_part_index_impl(pos, self.normals.len())
+  }
+
+  fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
+    let n = self.normals[current_normal];
+    let (n1, n2) = self.n_basis[current_normal];
+    let pos = if pos.x > 30.0 {
+      let pos = crate::points2d::Point { x: pos.x - 45.0, y: pos.y };
+      if pos.len() > 10.8 {
+        return 0;
+      }
+      (n.scale(25.1 / n.sqr_len()) + n1.scale(pos.x) + n2.scale(pos.y))
+        .norm()
+        .scale(25.1)
+    } else if pos.x < -30.0 {
+      let pos = crate::points2d::Point { x: pos.x + 35.0, y: pos.y };
+      let y_size = if current_normal < 3 { 16.0 } else { 4.0 };
+      return ((pos.x.abs() - 1.0).abs() < 0.35 && pos.y.abs() < y_size * 0.5) as PartIndex;
+    } else {
+      n.scale(35.0 / n.sqr_len()) + n1.scale(pos.x) + n2.scale(pos.y)
+    };
+
+    let result = self.get_part_index_impl(pos, current_normal);
+    (result > 0) as PartIndex
+  }
+}
@@ -1,4 +1,5 @@
 use common::model::*;
+use common::part_creator::*;
 use common::points3d::*;
 use common::solid::*;
 
@@ -30,14 +31,6 @@ impl OctoMixupCreator {
     Self { axis }
   }
 
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
-    0
-  }
-
-  pub fn faces(&self) -> usize {
-    0
-  }
-
   pub fn get_part_index_impl(&self, pos: Point, current_normal: usize) -> PartIndex {
     if sqr(pos.x) + sqr(pos.y) < sqr(1.25) {
       return 0;
@@ -110,28 +103,18 @@ impl OctoMixupCreator {
 
     return index;
   }
+}
 
-  pub fn get_part_index(&self, pos: Point) -> PartIndex {
+impl PartCreator for OctoMixupCreator {
+  fn get_part_index(&self, pos: Point) -> PartIndex {
     self.get_part_index_impl(pos, 8)
   }
-  
-  pub fn get_height(&self, current_normal: usize) -> f32 {
-    0.6
-  }
-
-  pub fn get_count(&self, current_normal: usize) -> usize {
-    1
-  }
-  
-  pub fn get_name(&self, current_normal: usize) -> Option<String> {
-    None
-  }
 
-  pub fn get_quality() -> usize {
+  fn get_quality(&self) -> usize {
     200
   }
 
-  pub fn get_size() -> f32 {
+  fn get_size(&self) -> f32 {
     160.0
   }
 }
@@ -1,4 +1,5 @@
 use crate::model::*;
+use crate::part_creator::*;
 use crate::points3d::*;
 use crate::solid::*;
 use num::Float;
@@ -92,30 +93,6 @@ impl OctopusCreator {
     Self { axis, normals, cone_angle, screw_diam, head_diam, thread_diam }
   }
 
-  pub fn faces(&self) -> usize {
-    self.normals.len() + 4 - 4
-  }
-
-  pub fn get_part_index(&self, pos: Point) -> PartIndex {
-    self.get_part_index_impl(pos, self.normals.len())
-  }
-
-  pub fn get_height(current_normal: usize) -> f32 {
-    0.6
-  }
-
-  pub fn get_quality() -> usize {
-    128
-  }
-
-  pub fn get_size() -> f32 {
-    130.0
-  }
-
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
-    0
-  }
-
   pub fn get_part_index_impl(&self, pos: Point, current_normal: usize) -> PartIndex {
     let r = pos.len();
 
@@ -140,3 +117,21 @@ impl OctopusCreator {
     return index;
   }
 }
+
+impl PartCreator for OctopusCreator {
+  fn faces(&self) -> usize {
+    self.normals.len() + 4 - 4
+  }
+
+  fn get_part_index(&self, pos: Point) -> PartIndex {
+    self.get_part_index_impl(pos, self.normals.len())
+  }
+
+  fn get_quality(&self) -> usize {
+    128
+  }
+
+  fn get_size(&self) -> f32 {
+    130.0
+  }
+}
@@ -1,4 +1,5 @@
 use crate::model::*;
+use crate::part_creator::*;
 use crate::points3d::*;
 use crate::solid::*;
 
@@ -145,44 +146,6 @@ impl OsnikCreator {
     }
   }
 
-  pub fn faces(&self) -> usize {
-    self.normals.len() + 4 - 4
-  }
-
-  pub fn get_part_index(&self, pos: Point) -> PartIndex {
-    self.get_part_index_impl(pos, self.normals.len())
-  }
-
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
-    let (current_normal, d) = match current_normal {
-      6 => (6, 0),
-      7 => (6, 1),
-      8 => (6, 2),
-      9 => (7, 0),
-      10 => (7, 1),
-      11 => (7, 2),
-      c => (c, 3),
-    };
-
-    if d < self.sectors_for_stickers.len()
-      && crate::points2d::dot(pos, self.sectors_for_stickers[d]) < 0.5 * pos.len()
-    {
-      return 0;
-    }
-
-    let n = self.normals[current_normal];
-    let (n1, n2) = self.n_basis[current_normal];
-
-    let pos = n.scale(35.0 / n.sqr_len()) + n1.scale(pos.x) + n2.scale(pos.y);
-    let result = self.get_part_index_impl(pos, current_normal);
-
-    (result > 0) as PartIndex
-  }
-
-  pub fn get_height(current_normal: usize) -> f32 {
-    0.6
-  }
-
   pub fn get_part_index_impl(&self, pos: Point, current_normal: usize) -> PartIndex {
     let r = pos.len();
     if pos.x.abs() > 64.999 || pos.y.abs() > 64.999 || pos.z.abs() > 64.999 {
@@ -433,3 +396,39 @@ impl OsnikCreator {
     return index;
   }
 }
+
+impl PartCreator for OsnikCreator {
+  fn faces(&self) -> usize {
+    self.normals.len() + 4 - 4
+  }
+
+  fn get_part_index(&self, pos: Point) -> PartIndex {
+    self.get_part_index_impl(pos, self.normals.len())
+  }
+
+  fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
+    let (current_normal, d) = match current_normal {
+      6 => (6, 0),
+      7 => (6, 1),
+      8 => (6, 2),
+      9 => (7, 0),
+      10 => (7, 1),
+      11 => (7, 2),
+      c => (c, 3),
+    };
+
+    if d < self.sectors_for_stickers.len()
+      && crate::points2d::dot(pos, self.sectors_for_stickers[d]) < 0.5 * pos.len()
+    {
+      return 0;
+    }
+
+    let n = self.normals[current_normal];
+    let (n1, n2) = self.n_basis[current_normal];
+
+    let pos = n.scale(35.0 / n.sqr_len()) + n1.scale(pos.x) + n2.scale(pos.y);
+    let result = self.get_part_index_impl(pos, current_normal);
+
+    (result > 0) as PartIndex
+  }
+}
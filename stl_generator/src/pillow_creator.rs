@@ -1,5 +1,6 @@
 use crate::common_for_twisty_puzzles::*;
 use crate::model::*;
+use crate::part_creator::*;
 use crate::points3d::*;
 use crate::solid::*;
 use num::Float;
@@ -80,30 +81,6 @@ impl PillowCreator {
     Self { axis, axis1, axis2, groove, axis_pos, axis_neg }
   }
 
-  pub fn faces(&self) -> usize {
-    0
-  }
-
-  pub fn get_part_index(&self, pos: Point) -> PartIndex {
-    self.get_part_index_impl(pos, 1)
-  }
-
-  pub fn get_height(current_normal: usize) -> f32 {
-    0.6
-  }
-
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
-    0
-  }
-
-  pub fn get_quality() -> usize {
-    512
-  }
-
-  pub fn get_size() -> f32 {
-    60.0
-  }
-
   pub fn get_part_index_impl(&self, pos: Point, current_normal: usize) -> PartIndex {
     let r = pos.len();
     if pos.x.abs() > 29.999 || pos.y.abs() > 29.999 || pos.z.abs() > 29.999 {
@@ -271,3 +248,17 @@ impl PillowCreator {
     return index;
   }
 }
+
+impl PartCreator for PillowCreator {
+  fn get_part_index(&self, pos: Point) -> PartIndex {
+    self.get_part_index_impl(pos, 1)
+  }
+
+  fn get_quality(&self) -> usize {
+    512
+  }
+
+  fn get_size(&self) -> f32 {
+    60.0
+  }
+}
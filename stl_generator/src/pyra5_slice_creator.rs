@@ -1,5 +1,6 @@
 use common::common_for_twisty_puzzles::*;
 use common::model::*;
+use common::part_creator::*;
 use common::points3d::*;
 use common::solid::*;
 use num::Float;
@@ -185,88 +186,6 @@ impl Pyra5Creator {
     }
   }
 
-  pub fn get_height(&self, current_normal: usize) -> f32 {
-    0.9
-  }
-
-  pub fn get_count(&self, current_normal: usize) -> usize {
-    1
-  }
-
-  pub fn get_name(&self, current_normal: usize) -> Option<String> {
-    None
-  }
-
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
-    if current_normal == 0 {
-      return self.get_part_index_impl(Point { x: 0.0, y: pos.x, z: pos.y }, 5);
-    }
-    if current_normal == 1 {
-      return self.get_part_index_impl(Point { x: pos.x, y: 0.0, z: pos.y }, 5);
-    }
-    if current_normal == 2 {
-      return self
-        .get_part_index_impl(Point { x: pos.x * 0.5.sqrt(), y: pos.x * 0.5.sqrt(), z: pos.y }, 5);
-    }
-    if current_normal == 3 {
-      return self.get_part_index_impl(Point { x: pos.x, y: pos.y, z: 0.0 }, 5);
-    }
-
-    let n = self.normals[current_normal];
-
-    fn sinc(x: f32) -> f32 {
-      if x == 0.0 {
-        1.0
-      } else {
-        x.sin() / x
-      }
-    }
-
-    fn versinc(x: f32) -> f32 {
-      if x == 0.0 {
-        0.0
-      } else {
-        (1.0 - x.cos()) / x
-      }
-    }
-
-    let k = 0.006;
-    let k2 = k * 2.0;
-    let last_groove = self.groove[self.groove.len() - 2];
-    let max = last_groove + 2.2;
-
-    let r = pos.len();
-    let a = r * k2;
-
-    let n1 = n.any_perp().norm();
-    let n2 = cross(n, n1).norm();
-
-    let pos = pos.scale(sinc(a));
-    let pos = n.scale(max - r * versinc(a)) + n1.scale(pos.x) + n2.scale(pos.y);
-
-    let r = pos.len();
-    let d = dot(pos, n);
-
-    let control_c = n.scale(max - k2.recip());
-    let delta = d + (max * max + r * r) * k - (2.0 * d * k + 1.0) * max;
-
-    let result = self.get_part_index_impl(pos, current_normal);
-
-    (result > 0) as PartIndex
-  }
-
-  pub fn faces(&self) -> usize {
-    self.normals.len() * 1
-  }
-
-  pub fn get_quality() -> usize {
-    320
-  }
-
-  pub fn get_size() -> f32 {
-    110.0
-  }
-
   pub fn get_part_index_impl(&self, pos: Point, current_normal: usize) -> PartIndex {
     if pos.x.abs() > 59.0 || pos.y.abs() > 59.0 || pos.z.abs() > 59.0 {
       return 0;
@@ -567,8 +486,84 @@ impl Pyra5Creator {
 
     return index;
   }
+}
+
+impl PartCreator for Pyra5Creator {
+  fn get_height(&self, current_normal: usize) -> f32 {
+    0.9
+  }
+
+  fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
+    if current_normal == 0 {
+      return self.get_part_index_impl(Point { x: 0.0, y: pos.x, z: pos.y }, 5);
+    }
+    if current_normal == 1 {
+      return self.get_part_index_impl(Point { x: pos.x, y: 0.0, z: pos.y }, 5);
+    }
+    if current_normal == 2 {
+      return self
+        .get_part_index_impl(Point { x: pos.x * 0.5.sqrt(), y: pos.x * 0.5.sqrt(), z: pos.y }, 5);
+    }
+    if current_normal == 3 {
+      return self.get_part_index_impl(Point { x: pos.x, y: pos.y, z: 0.0 }, 5);
+    }
+
+    let n = self.normals[current_normal];
+
+    fn sinc(x: f32) -> f32 {
+      if x == 0.0 {
+        1.0
+      } else {
+        x.sin() / x
+      }
+    }
+
+    fn versinc(x: f32) -> f32 {
+      if x == 0.0 {
+        0.0
+      } else {
+        (1.0 - x.cos()) / x
+      }
+    }
+
+    let k = 0.006;
+    let k2 = k * 2.0;
+    let last_groove = self.groove[self.groove.len() - 2];
+    let max = last_groove + 2.2;
+
+    let r = pos.len();
+    let a = r * k2;
+
+    let n1 = n.any_perp().norm();
+    let n2 = cross(n, n1).norm();
+
+    let pos = pos.scale(sinc(a));
+    let pos = n.scale(max - r * versinc(a)) + n1.scale(pos.x) + n2.scale(pos.y);
+
+    let r = pos.len();
+    let d = dot(pos, n);
+
+    let control_c = n.scale(max - k2.recip());
+    let delta = d + (max * max + r * r) * k - (2.0 * d * k + 1.0) * max;
+
+    let result = self.get_part_index_impl(pos, current_normal);
+
+    (result > 0) as PartIndex
+  }
+
+  fn faces(&self) -> usize {
+    self.normals.len() * 1
+  }
+
+  fn get_quality(&self) -> usize {
+    320
+  }
+
+  fn get_size(&self) -> f32 {
+    110.0
+  }
 
-  pub fn get_part_index(&self, pos: Point) -> PartIndex {
+  fn get_part_index(&self, pos: Point) -> PartIndex {
     self.get_part_index_impl(pos, self.axis.len())
   }
 }
@@ -1,5 +1,6 @@
 use common::common_for_twisty_puzzles::*;
 use common::model::*;
+use common::part_creator::*;
 use common::points3d::*;
 use common::solid::*;
 use num::Float;
@@ -84,52 +85,6 @@ impl RedTriangleCreator {
     Self { axis, axis1, axis2, groove, axis_pos, axis_neg }
   }
 
-  pub fn faces(&self) -> usize {
-    self.axis.len() + 2
-  }
-
-  pub fn get_part_index(&self, pos: Point) -> PartIndex {
-    self.get_part_index_impl(pos, self.faces())
-  }
-
-  pub fn get_height(&self, current_normal: usize) -> f32 {
-    0.6
-  }
-
-  pub fn get_count(&self, current_normal: usize) -> usize {
-    1
-  }
-
-  pub fn get_name(&self, current_normal: usize) -> Option<String> {
-    None
-  }
-
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
-    if current_normal < self.axis.len() {
-      let n = self.axis[current_normal];
-      let n1 = n.any_perp();
-      let n2 = cross(n, n1);
-      let sz = self.groove[self.groove.len() - 2] + 2.2;
-      let pos = n.scale(sz - 0.5) + n1.scale(pos.x) + n2.scale(pos.y);
-      let result = self.get_part_index_impl(pos, current_normal);
-      (result > 0) as PartIndex
-    } else if current_normal == self.axis.len() {
-      let pos = Point { x: pos.x, y: pos.y, z: 0.0 };
-      self.get_part_index_impl(pos, current_normal)
-    } else  {
-      let pos = Point { x: pos.x, y: 0.0, z: pos.y };
-      self.get_part_index_impl(pos, current_normal)
-    }
-  }
-
-  pub fn get_quality() -> usize {
-    384
-  }
-
-  pub fn get_size() -> f32 {
-    120.0
-  }
-
   pub fn get_part_index_impl(&self, pos: Point, current_normal: usize) -> PartIndex {
     if current_normal == self.faces() {
       return 0;
@@ -305,3 +260,39 @@ impl RedTriangleCreator {
     index
   }
 }
+
+impl PartCreator for RedTriangleCreator {
+  fn faces(&self) -> usize {
+    self.axis.len() + 2
+  }
+
+  fn get_part_index(&self, pos: Point) -> PartIndex {
+    self.get_part_index_impl(pos, self.faces())
+  }
+
+  fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
+    if current_normal < self.axis.len() {
+      let n = self.axis[current_normal];
+      let n1 = n.any_perp();
+      let n2 = cross(n, n1);
+      let sz = self.groove[self.groove.len() - 2] + 2.2;
+      let pos = n.scale(sz - 0.5) + n1.scale(pos.x) + n2.scale(pos.y);
+      let result = self.get_part_index_impl(pos, current_normal);
+      (result > 0) as PartIndex
+    } else if current_normal == self.axis.len() {
+      let pos = Point { x: pos.x, y: pos.y, z: 0.0 };
+      self.get_part_index_impl(pos, current_normal)
+    } else  {
+      let pos = Point { x: pos.x, y: 0.0, z: pos.y };
+      self.get_part_index_impl(pos, current_normal)
+    }
+  }
+
+  fn get_quality(&self) -> usize {
+    384
+  }
+
+  fn get_size(&self) -> f32 {
+    120.0
+  }
+}
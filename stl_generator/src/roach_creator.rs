@@ -1,4 +1,5 @@
 use crate::model::*;
+use crate::part_creator::*;
 use crate::points3d::*;
 use crate::solid::*;
 
@@ -41,16 +42,10 @@ impl RoachCreator {
   pub fn new() -> Self {
     Self {}
   }
+}
 
-  pub fn faces(&self) -> usize {
-    0
-  }
-
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
-    0
-  }
-
-  pub fn get_part_index(&self, pos: Point) -> PartIndex {
+impl PartCreator for RoachCreator {
+  fn get_part_index(&self, pos: Point) -> PartIndex {
     let mut pos = pos;
     pos.z *= 0.5;
     if pos.z < 0.0 || pos.z > 6.0 {
@@ -1,4 +1,5 @@
 use crate::model::*;
+use crate::part_creator::*;
 use crate::points3d::*;
 use crate::solid::*;
 
@@ -152,36 +153,6 @@ impl SemiosnikCreator {
     }
   }
 
-  pub fn faces(&self) -> usize {
-    self.normals.len()
-  }
-
-  pub fn get_part_index(&self, pos: Point) -> PartIndex {
-    self.get_part_index_impl(pos, self.normals.len())
-  }
-
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
-    let current_normal = if current_normal == 7 {
-      return 0;
-    } else if current_normal == 8 {
-      7
-    } else {
-      current_normal
-    };
-
-    let n = self.normals[current_normal];
-    let (n1, n2) = self.n_basis[current_normal];
-
-    if current_normal == 0 && pos.x > 13.0 {
-      return self.get_sticker_index(crate::points2d::Point { x: pos.x - 12.0, y: pos.y }, 8);
-    }
-
-    let pos = n.scale(35.0 / n.sqr_len()) + n1.scale(pos.x) + n2.scale(pos.y);
-    let result = self.get_part_index_impl(pos, current_normal);
-
-    (result > 0) as PartIndex
-  }
-
   pub fn get_part_index_impl(&self, pos: Point, current_normal: usize) -> PartIndex {
     let r = pos.len();
     if pos.x.abs() > 64.999 || pos.y.abs() > 64.999 || pos.z.abs() > 64.999 {
@@ -440,3 +411,35 @@ impl SemiosnikCreator {
     return index;
   }
 }
+
+impl PartCreator for SemiosnikCreator {
+  fn faces(&self) -> usize {
+    self.normals.len()
+  }
+
+  fn get_part_index(&self, pos: Point) -> PartIndex {
+    self.get_part_index_impl(pos, self.normals.len())
+  }
+
+  fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
+    let current_normal = if current_normal == 7 {
+      return 0;
+    } else if current_normal == 8 {
+      7
+    } else {
+      current_normal
+    };
+
+    let n = self.normals[current_normal];
+    let (n1, n2) = self.n_basis[current_normal];
+
+    if current_normal == 0 && pos.x > 13.0 {
+      return self.get_sticker_index(crate::points2d::Point { x: pos.x - 12.0, y: pos.y }, 8);
+    }
+
+    let pos = n.scale(35.0 / n.sqr_len()) + n1.scale(pos.x) + n2.scale(pos.y);
+    let result = self.get_part_index_impl(pos, current_normal);
+
+    (result > 0) as PartIndex
+  }
+}
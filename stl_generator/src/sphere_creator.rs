@@ -1,4 +1,5 @@
 use common::model::*;
+use common::part_creator::*;
 use common::points3d::*;
 use common::solid::*;
 use num::Float;
@@ -82,36 +83,26 @@ impl SphereCreator {
 
     Self { starts, rounds, rounds_deep }
   }
+}
 
-  pub fn faces(&self) -> usize {
-    1
-  }
-
-  pub fn get_height(&self, current_normal: usize) -> f32 {
-    0.6
-  }
-
-  pub fn get_count(&self, current_normal: usize) -> usize {
+impl PartCreator for SphereCreator {
+  fn faces(&self) -> usize {
     1
   }
 
-  pub fn get_name(&self, current_normal: usize) -> Option<String> {
-    None
-  }
-
-  pub fn get_quality() -> usize {
+  fn get_quality(&self) -> usize {
     200
   }
 
-  pub fn get_size() -> f32 {
+  fn get_size(&self) -> f32 {
     100.0
   }
 
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
+  fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
     self.get_part_index(Point { x: pos.x, y: 0.0, z: pos.y })
   }
 
-  pub fn get_part_index(&self, mut pos: Point) -> PartIndex {
+  fn get_part_index(&self, mut pos: Point) -> PartIndex {
 
     if r > 23.1 {
       return 0;
@@ -1,5 +1,6 @@
 use common::common_for_twisty_puzzles::*;
 use common::model::*;
+use common::part_creator::*;
 use common::points3d::*;
 use common::solid::*;
 use fxhash::*;
@@ -215,45 +216,6 @@ impl SquareCubeCreator {
     }
   }
 
-  pub fn faces(&self) -> usize {
-    //self.normals.len()
-    2
-  }
-
-  pub fn get_part_index(&self, pos: Point) -> PartIndex {
-    self.get_part_index_impl(pos, self.faces())
-  }
-
-  pub fn get_height(&self, current_normal: usize) -> f32 {
-    0.6
-  }
-
-  pub fn get_count(&self, current_normal: usize) -> usize {
-    1
-  }
-
-  pub fn get_name(&self, current_normal: usize) -> Option<String> {
-    None
-  }
-
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
-    if current_normal == 0 {
-      return self.get_part_index(Point { x: pos.x, y: pos.y, z: 0.0 });
-    }
-    if current_normal == 1 {
-      return self.get_part_index(Point { x: pos.x, y: 0.0, z: pos.y });
-    }
-    0
-  }
-
-  pub fn get_quality() -> usize {
-    512
-  }
-
-  pub fn get_size() -> f32 {
-    150.0
-  }
-
   pub fn get_part_index_impl(&self, pos: Point, current_normal: usize) -> PartIndex {
     let r = pos.len();
     if pos.x.abs() > 49.999 || pos.y.abs() > 49.999 || pos.z.abs() > 49.999 {
@@ -532,3 +494,32 @@ impl SquareCubeCreator {
     return index;
   }
 }
+
+impl PartCreator for SquareCubeCreator {
+  fn faces(&self) -> usize {
+    //self.normals.len()
+    2
+  }
+
+  fn get_part_index(&self, pos: Point) -> PartIndex {
+    self.get_part_index_impl(pos, self.faces())
+  }
+
+  fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
+    if current_normal == 0 {
+      return self.get_part_index(Point { x: pos.x, y: pos.y, z: 0.0 });
+    }
+    if current_normal == 1 {
+      return self.get_part_index(Point { x: pos.x, y: 0.0, z: pos.y });
+    }
+    0
+  }
+
+  fn get_quality(&self) -> usize {
+    512
+  }
+
+  fn get_size(&self) -> f32 {
+    150.0
+  }
+}
@@ -1,5 +1,6 @@
 use common::common_for_twisty_puzzles::*;
 use common::model::*;
+use common::part_creator::*;
 use common::points2d;
 use common::points3d::*;
 use common::solid::*;
@@ -2908,8 +2909,10 @@ impl SwampTodCreator {
 
     0
   }
+}
 
-  pub fn get_part_index(&self, pos: Point) -> PartIndex {
+impl PartCreator for SwampTodCreator {
+  fn get_part_index(&self, pos: Point) -> PartIndex {
     if pos.x.abs() > 95.0 || pos.y.abs() > 95.0 || pos.z.abs() > 95.0 {
       return 0;
     }
@@ -2969,24 +2972,16 @@ impl SwampTodCreator {
     return 0;
   }
 
-  pub fn get_height(&self, current_normal: usize) -> f32 {
-    0.6
-  }
-
-  pub fn get_count(&self, current_normal: usize) -> usize {
-    1
-  }
-
-  pub fn faces(&self) -> usize {
+  fn faces(&self) -> usize {
     self.sections.len()
   }
 
-  pub fn get_name(&self, current_normal: usize) -> Option<String> {
+  fn get_name(&self, current_normal: usize) -> Option<String> {
     let s = &self.sections[current_normal];
     Some(s.name.clone())
   }
 
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
+  fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
     let s = &self.sections[current_normal];
     match s.kind {
       SectionKind::Sweep(r) => {
@@ -3012,11 +3007,11 @@ impl SwampTodCreator {
     }
   }
 
-  pub fn get_quality() -> usize {
+  fn get_quality(&self) -> usize {
     100
   }
 
-  pub fn get_size() -> f32 {
+  fn get_size(&self) -> f32 {
     200.0
   }
 }
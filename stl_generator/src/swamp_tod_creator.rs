@@ -1,5 +1,6 @@
 use common::common_for_twisty_puzzles::*;
 use common::model::*;
+use common::part_creator::*;
 use common::points2d;
 use common::points3d::*;
 use common::solid::*;
@@ -1934,66 +1935,6 @@ impl SwampTodCreator {
     result
   }
 
-  pub fn faces(&self) -> usize {
-    0
-  }
-
-  pub fn get_part_index(&self, pos: Point) -> PartIndex { 
-    if pos.x.abs() > 79.0 || pos.y.abs() > 79.0 || pos.z.abs() > 79.0 {
-      return 0;
-    }
-
-    if pos.y < 0.0 {
-   //   return 0;
-    }
-
-    let result = self.get_component_index(pos);
-
-   // if result != 100300 && result != 100301 { return 0; }
-
-    if result / 100000 == 4 {
-      return 0;
-    }
-
-    if result > 500000 {
-      if pos.y < -0.11 {
-        return result;
-      }
-
-      if pos.y > 0.11 {
-        return result + 1;
-      }
-
-      return 0;
-    }
-
-    return result;
-  }
-
-  pub fn get_height(&self, current_normal: usize) -> f32 {
-    0.6
-  }
-
-  pub fn get_count(&self, current_normal: usize) -> usize {
-    1
-  }
-
-  pub fn get_name(&self, current_normal: usize) -> Option<String> {
-    None
-  }
-
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
-    0
-  }
-
-  pub fn get_quality() -> usize {
-    250
-  }
-
-  pub fn get_size() -> f32 {
-    160.0
-  }
-
   pub fn get_component_index(&self, pos: Point) -> PartIndex {
     for g in &self.gears {
       if g.contains(pos, self.err, self.g_err) {
@@ -2064,3 +2005,45 @@ impl SwampTodCreator {
     return 0;
   }
 }
+
+impl PartCreator for SwampTodCreator {
+  fn get_part_index(&self, pos: Point) -> PartIndex { 
+    if pos.x.abs() > 79.0 || pos.y.abs() > 79.0 || pos.z.abs() > 79.0 {
+      return 0;
+    }
+
+    if pos.y < 0.0 {
+   //   return 0;
+    }
+
+    let result = self.get_component_index(pos);
+
+   // if result != 100300 && result != 100301 { return 0; }
+
+    if result / 100000 == 4 {
+      return 0;
+    }
+
+    if result > 500000 {
+      if pos.y < -0.11 {
+        return result;
+      }
+
+      if pos.y > 0.11 {
+        return result + 1;
+      }
+
+      return 0;
+    }
+
+    return result;
+  }
+
+  fn get_quality(&self) -> usize {
+    250
+  }
+
+  fn get_size(&self) -> f32 {
+    160.0
+  }
+}
@@ -1,18 +1,27 @@
+use crate::part_creator::*;
 use crate::points3d::*;
 use crate::solid::*;
-use rand::Rng;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::sync::OnceLock;
 
 pub struct Node {
   pos: Point,
   r: f32,
 }
 pub struct TreeCreator {
-  rng: rand::rngs::ThreadRng,
+  rng: StdRng,
   nodes: Vec<Node>,
   wall: Vec<crate::points2d::Point>,
   h: Vec<i32>,
 }
 
+// every instance made by the same process grows the same tree
+fn process_seed() -> u64 {
+  static SEED: OnceLock<u64> = OnceLock::new();
+  *SEED.get_or_init(|| rand::thread_rng().gen())
+}
+
 pub fn sqr(x: f32) -> f32 {
   x * x
 }
@@ -67,8 +76,12 @@ impl TreeCreator {
   }
 
   pub fn new() -> Self {
-    let mut result =
-      Self { rng: rand::thread_rng(), nodes: Vec::new(), wall: Vec::new(), h: Vec::new() };
+    let mut result = Self {
+      rng: StdRng::seed_from_u64(process_seed()),
+      nodes: Vec::new(),
+      wall: Vec::new(),
+      h: Vec::new(),
+    };
     result.generate(Point { x: 0.0, y: 0.0, z: -18.0 }, Point { x: 0.0, y: 0.0, z: 1.5 }, 0, 0.98);
     result
       .wall
@@ -94,17 +107,10 @@ impl TreeCreator {
     }
     result
   }
+}
 
-
-  pub fn faces(&self) -> usize {
-    0
-  }
-
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
-    0
-  }
-  
-  pub fn get_part_index(&self, pos: Point) -> PartIndex {
+impl PartCreator for TreeCreator {
+  fn get_part_index(&self, pos: Point) -> PartIndex {
     if pos.x.abs() > 129.999 || pos.y.abs() > 129.999 || pos.z.abs() > 129.999 {
       return 0;
     }
@@ -1,4 +1,5 @@
 use common::model::*;
+use common::part_creator::*;
 use common::points3d::*;
 use common::solid::*;
 use num::Float;
@@ -18,36 +19,22 @@ impl WasherCreator {
   pub fn new() -> Self {
     Self {}
   }
+}
 
-  pub fn faces(&self) -> usize {
-    0
-  }
-
-  pub fn get_height(&self, current_normal: usize) -> f32 {
-    0.6
-  }
-
-  pub fn get_count(&self, current_normal: usize) -> usize {
-    1
-  }
-
-  pub fn get_name(&self, current_normal: usize) -> Option<String> {
-    None
-  }
-
-  pub fn get_quality() -> usize {
+impl PartCreator for WasherCreator {
+  fn get_quality(&self) -> usize {
     512
   }
 
-  pub fn get_size() -> f32 {
+  fn get_size(&self) -> f32 {
     25.0
   }
 
-  pub fn get_sticker_index(&self, pos: crate::points2d::Point, current_normal: usize) -> PartIndex {
-    0
+  fn mesh_aabb(&self) -> Option<(Point, Point)> {
+    Some((Point { x: -2.6, y: -2.6, z: -0.1 }, Point { x: 2.6, y: 2.6, z: 1.3 }))
   }
 
-  pub fn get_part_index(&self, pos: Point) -> PartIndex {
+  fn get_part_index(&self, pos: Point) -> PartIndex {
     
     if pos.z < 5.5 {
      // return 0; //tmp